@@ -42,7 +42,7 @@ use client::{
 	ChainNotify, PruningInfo, ProvingBlockChainClient,
 };
 use encoded;
-use engines::Engine;
+use engines::{Engine, ConsensusIntrospection};
 use env_info::EnvInfo;
 use env_info::LastHashes;
 use error::{ImportError, ExecutionError, CallError, BlockError, ImportResult, Error as EthcoreError};
@@ -389,6 +389,21 @@ impl Client {
 			return Err(());
 		}
 
+		// Check the block doesn't fork off below finalized blocks.
+		// The fork point is only looked up for engines with finality and blocks not extending the best block.
+		if let Some(finalized) = engine.finalized_block(best_block_number) {
+			if *header.parent_hash() != chain.best_block_hash() {
+				let fork_point = chain.tree_route(chain.best_block_hash(), header.parent_hash().clone())
+					.and_then(|route| chain.block_number(&route.ancestor));
+				if let Some(ancestor) = fork_point {
+					if ancestor < finalized {
+						warn!(target: "client", "Block import failed for #{} ({})\nBlock forks off at #{} below finalized #{} (current best block: #{}).", header.number(), header.hash(), ancestor, finalized, best_block_number);
+						return Err(());
+					}
+				}
+			}
+		}
+
 		// Verify Block Family
 		let verify_family_result = self.verifier.verify_block_family(header, &block.bytes, engine, &**chain);
 		if let Err(e) = verify_family_result {
@@ -431,6 +446,20 @@ impl Client {
 		}
 	}
 
+	fn notify_engine_of_reorg(&self, enacted: &[H256], retracted: &[H256]) {
+		if !enacted.is_empty() {
+			self.engine.on_canonical_import(enacted);
+		}
+		if retracted.is_empty() {
+			return;
+		}
+		let headers: Vec<_> = {
+			let chain = self.chain.read();
+			retracted.iter().filter_map(|hash| chain.block_header(hash)).collect()
+		};
+		self.engine.on_reorg(&headers);
+	}
+
 	fn calculate_enacted_retracted(&self, import_results: &[ImportRoute]) -> (Vec<H256>, Vec<H256>) {
 		fn map_to_vec(map: Vec<(H256, bool)>) -> Vec<H256> {
 			map.into_iter().map(|(k, _v)| k).collect()
@@ -518,6 +547,7 @@ impl Client {
 		{
 			if !imported_blocks.is_empty() && is_empty {
 				let (enacted, retracted) = self.calculate_enacted_retracted(&import_results);
+				self.notify_engine_of_reorg(&enacted, &retracted);
 
 				if is_empty {
 					self.miner.chain_new_blocks(self, &imported_blocks, &invalid_blocks, &enacted, &retracted);
@@ -866,6 +896,7 @@ impl Client {
 	fn check_garbage(&self) {
 		self.chain.read().collect_garbage();
 		self.block_queue.collect_garbage();
+		self.block_queue.retry_future();
 		self.tracedb.read().collect_garbage();
 	}
 
@@ -1622,6 +1653,10 @@ impl MiningBlockChainClient for Client {
 		self.engine.schedule(self.latest_env_info().number)
 	}
 
+	fn consensus(&self) -> Option<&ConsensusIntrospection> {
+		self.engine.consensus()
+	}
+
 	fn prepare_open_block(&self, author: Address, gas_range_target: (U256, U256), extra_data: Bytes) -> OpenBlock {
 		let engine = &*self.engine;
 		let chain = self.chain.read();
@@ -1690,6 +1725,7 @@ impl MiningBlockChainClient for Client {
 			route
 		};
 		let (enacted, retracted) = self.calculate_enacted_retracted(&[route]);
+		self.notify_engine_of_reorg(&enacted, &retracted);
 		self.miner.chain_new_blocks(self, &[h.clone()], &[], &enacted, &retracted);
 		self.notify(|notify| {
 			notify.new_blocks(
@@ -1721,6 +1757,10 @@ impl EngineClient for Client {
 	fn broadcast_consensus_message(&self, message: Bytes) {
 		self.notify(|notify| notify.broadcast(message.clone()));
 	}
+
+	fn epoch_transitions(&self) -> Vec<(u64, BlockNumber)> {
+		self.chain.read().epoch_transitions().map(|(epoch, transition)| (epoch, transition.block_number)).collect()
+	}
 }
 
 impl MayPanic for Client {
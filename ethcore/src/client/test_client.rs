@@ -49,6 +49,7 @@ use error::CallError;
 use trace::LocalizedTrace;
 use state_db::StateDB;
 use encoded;
+use engines::ConsensusIntrospection;
 
 /// Test client.
 pub struct TestBlockChainClient {
@@ -96,6 +97,8 @@ pub struct TestBlockChainClient {
 	pub traces: RwLock<Option<Vec<LocalizedTrace>>>,
 	/// Pruning history size to report.
 	pub history: RwLock<Option<u64>>,
+	/// Epoch transitions to report, by epoch number and block number.
+	pub epoch_transitions: RwLock<Vec<(u64, BlockNumber)>>,
 }
 
 /// Used for generating test client blocks.
@@ -162,6 +165,7 @@ impl TestBlockChainClient {
 			first_block: RwLock::new(None),
 			traces: RwLock::new(None),
 			history: RwLock::new(None),
+			epoch_transitions: RwLock::new(vec![(0, 0)]),
 		};
 
 		// insert genesis hash.
@@ -356,6 +360,10 @@ impl MiningBlockChainClient for TestBlockChainClient {
 		Schedule::new_post_eip150(24576, true, true, true, true)
 	}
 
+	fn consensus(&self) -> Option<&ConsensusIntrospection> {
+		None
+	}
+
 	fn prepare_open_block(&self, author: Address, gas_range_target: (U256, U256), extra_data: Bytes) -> OpenBlock {
 		let engine = &*self.spec.engine;
 		let genesis_header = self.spec.genesis_header();
@@ -786,4 +794,8 @@ impl EngineClient for TestBlockChainClient {
 	}
 
 	fn broadcast_consensus_message(&self, _message: Bytes) {}
+
+	fn epoch_transitions(&self) -> Vec<(u64, BlockNumber)> {
+		self.epoch_transitions.read().clone()
+	}
 }
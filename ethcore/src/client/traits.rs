@@ -32,6 +32,7 @@ use evm::{Factory as EvmFactory, Schedule};
 use executive::Executed;
 use env_info::LastHashes;
 use block_import_error::BlockImportError;
+use engines::ConsensusIntrospection;
 use ipc::IpcConfig;
 use types::ids::*;
 use types::basic_account::BasicAccount;
@@ -299,6 +300,9 @@ pub trait MiningBlockChainClient: BlockChainClient {
 
 	/// Returns latest schedule.
 	fn latest_schedule(&self) -> Schedule;
+
+	/// Returns the introspection of the consensus engine, if it runs consensus rounds.
+	fn consensus(&self) -> Option<&ConsensusIntrospection>;
 }
 
 /// Client facilities used by internally sealing Engines.
@@ -311,6 +315,9 @@ pub trait EngineClient: MiningBlockChainClient {
 
 	/// Broadcast a consensus message to the network.
 	fn broadcast_consensus_message(&self, message: Bytes);
+
+	/// Epoch number and block number of every epoch transition on the canonical chain.
+	fn epoch_transitions(&self) -> Vec<(u64, BlockNumber)>;
 }
 
 /// Extended client interface for providing proofs of the state.
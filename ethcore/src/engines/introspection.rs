@@ -0,0 +1,60 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+// Introspection of engines running consensus rounds.
+
+use rustc_serialize::json::Json;
+use util::{Address, Bytes};
+use error::Error;
+use header::{Header, BlockNumber};
+use super::tendermint::{ConsensusState, Phase, ViewLatencyStats, RuntimeStats, VerificationStage};
+
+/// View of the consensus rounds of an engine, for operators.
+///
+/// Obtained through `Engine::consensus`, engines without rounds do not provide one.
+pub trait ConsensusIntrospection: Send + Sync {
+	/// Current height, view and step, along with votes for the current proposal.
+	fn consensus_state(&self) -> ConsensusState;
+
+	/// Only log the given phases of the rounds from now on.
+	fn set_log_phases(&self, phases: &[Phase]);
+
+	/// Average and longest time spent in recent views.
+	fn view_latency_stats(&self) -> ViewLatencyStats;
+
+	/// Committed blocks in `from..to` (inclusive) which were proposed or voted for by `address`.
+	fn validator_signed_blocks(&self, address: &Address, from: BlockNumber, to: BlockNumber) -> Vec<BlockNumber>;
+
+	/// Run the engine checks on an externally built RLP encoded block without importing it.
+	/// Returns the stage which rejected the block on failure.
+	fn check_block(&self, block: &[u8]) -> Result<(), (VerificationStage, Error)>;
+
+	/// Validators which were caught double voting repeatedly.
+	fn equivocating_validators(&self) -> Vec<Address>;
+
+	/// Number of view changes needed to commit the last height.
+	fn last_commit_view_changes(&self) -> usize;
+
+	/// Raw signed consensus messages which can be rebuilt from the seal of `header`.
+	/// `None` if the seal does not carry any.
+	fn seal_messages(&self, header: &Header) -> Option<Vec<Bytes>>;
+
+	/// Uptime and counts of handled messages, proposals and commits.
+	fn runtime_stats(&self) -> RuntimeStats;
+
+	/// Parameters the engine runs with, defaults included. Keys follow the spec names.
+	fn effective_config(&self) -> Json;
+}
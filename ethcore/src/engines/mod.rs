@@ -20,6 +20,7 @@ mod authority_round;
 mod basic_authority;
 mod epoch_verifier;
 mod instant_seal;
+mod introspection;
 mod null_engine;
 mod signer;
 mod tendermint;
@@ -31,8 +32,9 @@ pub use self::authority_round::AuthorityRound;
 pub use self::basic_authority::BasicAuthority;
 pub use self::epoch_verifier::EpochVerifier;
 pub use self::instant_seal::InstantSeal;
+pub use self::introspection::ConsensusIntrospection;
 pub use self::null_engine::NullEngine;
-pub use self::tendermint::Tendermint;
+pub use self::tendermint::{Tendermint, ConsensusState, Step as ConsensusStep, Phase as ConsensusPhase, ViewLatencyStats, RuntimeStats, VerificationStage};
 
 use std::sync::Weak;
 
@@ -72,6 +74,18 @@ pub enum EngineError {
 	BadSealFieldSize(OutOfBounds<usize>),
 	/// Validation proof insufficient.
 	InsufficientProof(String),
+	/// Engine parameters from the spec can not be used.
+	InvalidParams(String),
+	/// Consensus reached the highest allowed view without committing.
+	ViewLimitReached(usize),
+	/// Seal view field does not decode as a view number.
+	BadSealView(String),
+	/// Seal proposal signature field does not decode as a signature.
+	BadProposalSignature(String),
+	/// Seal precommits field does not decode as a list of signatures.
+	BadPrecommits(String),
+	/// Author field differs from the signer of the proposal.
+	AuthorMismatch(Mismatch<Address>),
 }
 
 impl fmt::Display for EngineError {
@@ -84,6 +98,12 @@ impl fmt::Display for EngineError {
 			UnexpectedMessage => "This Engine should not be fed messages.".into(),
 			BadSealFieldSize(ref oob) => format!("Seal field has an unexpected length: {}", oob),
 			InsufficientProof(ref msg) => format!("Insufficient validation proof: {}", msg),
+			InvalidParams(ref msg) => format!("Invalid engine parameters: {}", msg),
+			ViewLimitReached(ref view) => format!("View limit {} reached without a commit.", view),
+			BadSealView(ref err) => format!("Seal view field is malformed: {}", err),
+			BadProposalSignature(ref err) => format!("Seal proposal signature field is malformed: {}", err),
+			BadPrecommits(ref err) => format!("Seal precommits field is malformed: {}", err),
+			AuthorMismatch(ref mis) => format!("Author is not the proposal signer: {}", mis),
 		};
 
 		f.write_fmt(format_args!("Engine error ({})", msg))
@@ -140,6 +160,9 @@ pub trait Engine : Sync + Send {
 	/// Additional engine-specific information for the user/developer concerning `header`.
 	fn extra_info(&self, _header: &Header) -> BTreeMap<String, String> { BTreeMap::new() }
 
+	/// Introspection of the consensus rounds, `None` if the engine does not run any.
+	fn consensus(&self) -> Option<&ConsensusIntrospection> { None }
+
 	/// Additional information.
 	fn additional_params(&self) -> HashMap<String, String> { HashMap::new() }
 
@@ -161,6 +184,8 @@ pub trait Engine : Sync + Send {
 	fn maximum_uncle_age(&self) -> usize { 6 }
 	/// The nonce with which accounts begin.
 	fn account_start_nonce(&self) -> U256 { self.params().account_start_nonce }
+	/// Seconds a block timestamp may be ahead of local time.
+	fn allowed_future_block_time(&self) -> u64 { ::verification::ALLOWED_FUTURE_BLOCK_TIME }
 
 	/// Block transformation functions, before the transactions.
 	fn on_new_block(&self, block: &mut ExecutedBlock, last_hashes: Arc<LastHashes>) -> Result<(), Error> {
@@ -299,6 +324,20 @@ pub trait Engine : Sync + Send {
 	/// Sign using the EngineSigner, to be used for consensus tx signing.
 	fn sign(&self, _hash: H256) -> Result<Signature, Error> { unimplemented!() }
 
+	/// Sort transactions for a new block into the order required by the engine.
+	/// Returns `false` if the engine does not require any order.
+	fn order_transactions(&self, _transactions: &mut [SignedTransaction]) -> bool { false }
+
+	/// Highest block which can no longer be reorganized away, given the current `best_block`.
+	/// Imports forking off the canonical chain below it are rejected, `None` allows every fork.
+	fn finalized_block(&self, _best_block: BlockNumber) -> Option<BlockNumber> { None }
+
+	/// Called after a reorganisation with the headers of blocks which are no longer canonical.
+	fn on_reorg(&self, _retracted: &[Header]) {}
+
+	/// Called after blocks became canonical, once their epoch transitions are stored.
+	fn on_canonical_import(&self, _enacted: &[H256]) {}
+
 	/// Add Client which can be used for sealing, querying the state and sending messages.
 	fn register_client(&self, _client: Weak<Client>) {}
 
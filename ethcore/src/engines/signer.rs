@@ -25,6 +25,8 @@ pub struct EngineSigner {
 	account_provider: Mutex<Arc<AccountProvider>>,
 	address: RwLock<Address>,
 	password: RwLock<Option<String>>,
+	/// Keys tried in order when signing with `address` fails.
+	backups: RwLock<Vec<(Address, Option<String>)>>,
 }
 
 impl Default for EngineSigner {
@@ -33,6 +35,7 @@ impl Default for EngineSigner {
 			account_provider: Mutex::new(Arc::new(AccountProvider::transient_provider())),
 			address: Default::default(),
 			password: Default::default(),
+			backups: Default::default(),
 		}
 	}
 }
@@ -46,9 +49,21 @@ impl EngineSigner {
 		debug!(target: "poa", "Setting Engine signer to {}", address);
 	}
 
-	/// Sign a consensus message hash.
+	/// Add a key to sign with when the signing address is unavailable.
+	pub fn add_backup(&self, address: Address, password: String) {
+		self.backups.write().push((address, Some(password)));
+		debug!(target: "poa", "Adding Engine backup signer {}", address);
+	}
+
+	/// Sign a consensus message hash, falling back to the backup keys in order.
 	pub fn sign(&self, hash: H256) -> Result<Signature, account_provider::SignError> {
-		self.account_provider.lock().sign(*self.address.read(), self.password.read().clone(), hash)
+		let ap = self.account_provider.lock();
+		ap.sign(*self.address.read(), self.password.read().clone(), hash).or_else(|e| {
+			self.backups.read().iter()
+				.filter_map(|&(ref address, ref password)| ap.sign(*address, password.clone(), hash).ok())
+				.next()
+				.ok_or(e)
+		})
 	}
 
 	/// Signing address.
@@ -56,8 +71,8 @@ impl EngineSigner {
 		self.address.read().clone()
 	}
 
-	/// Check if the given address is the signing address.
+	/// Check if the given address is the signing address or one of the backup keys.
 	pub fn is_address(&self, address: &Address) -> bool {
-		*self.address.read() == *address
+		*self.address.read() == *address || self.backups.read().iter().any(|&(ref backup, _)| backup == address)
 	}
 }
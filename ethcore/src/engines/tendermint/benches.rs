@@ -0,0 +1,103 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! benchmarking for Tendermint seal verification
+//! should be started with:
+//! ```bash
+//! multirust run nightly cargo bench --features benches
+//! ```
+
+extern crate test;
+
+use self::test::Bencher;
+
+use util::*;
+use ethkey::{Generator, Random, sign};
+use header::Header;
+use engines::validator_set::SimpleList;
+use super::message::*;
+use super::verify::{CommitRules, verify_commit, recover_signer, recover_signers};
+use super::Step;
+
+/// Header committed by all of `n` fresh validators.
+fn committed_header(n: usize) -> (Header, SimpleList) {
+	let mut keys: Vec<_> = (0..n).map(|_| Random.generate().unwrap()).collect();
+	keys.sort_by_key(|k| k.address());
+	let mut header = Header::default();
+	header.set_number(1);
+	let vote_info = message_info_rlp(&VoteStep::new(1, 0, Step::Precommit), Some(header.bare_hash()));
+	let precommits: Vec<_> = keys.iter().map(|k| H520::from(sign(k.secret(), &vote_info.sha3()).unwrap())).collect();
+	header.set_seal(vec![
+		::rlp::encode(&0usize).to_vec(),
+		::rlp::NULL_RLP.to_vec(),
+		::rlp::encode_list(&precommits).to_vec()
+	]);
+	(header, SimpleList::new(keys.iter().map(|k| k.address()).collect()))
+}
+
+fn verify_seal(n: usize, b: &mut Bencher) {
+	let (header, validators) = committed_header(n);
+	b.iter(|| {
+		assert!(verify_commit(&header, &validators, 0, &CommitRules::default()).is_ok());
+	});
+}
+
+fn signatures(n: usize) -> (H256, Vec<H520>) {
+	let hash = H256::random();
+	let signatures = (0..n).map(|_| H520::from(sign(Random.generate().unwrap().secret(), &hash).unwrap())).collect();
+	(hash, signatures)
+}
+
+#[bench]
+fn recover_256_serial(b: &mut Bencher) {
+	let (hash, signatures) = signatures(256);
+	b.iter(|| {
+		assert!(signatures.iter().all(|s| recover_signer(s, &hash).is_ok()));
+	});
+}
+
+#[bench]
+fn recover_256_parallel(b: &mut Bencher) {
+	let (hash, signatures) = signatures(256);
+	b.iter(|| {
+		assert!(recover_signers(&signatures, &hash).is_ok());
+	});
+}
+
+#[bench]
+fn verify_seal_4(b: &mut Bencher) {
+	verify_seal(4, b)
+}
+
+#[bench]
+fn verify_seal_16(b: &mut Bencher) {
+	verify_seal(16, b)
+}
+
+#[bench]
+fn verify_seal_64(b: &mut Bencher) {
+	verify_seal(64, b)
+}
+
+#[bench]
+fn verify_seal_100(b: &mut Bencher) {
+	verify_seal(100, b)
+}
+
+#[bench]
+fn verify_seal_256(b: &mut Bencher) {
+	verify_seal(256, b)
+}
@@ -24,6 +24,9 @@ use rlp::{Rlp, UntrustedRlp, RlpStream, Encodable, Decodable, DecoderError};
 use ethkey::{recover, public_to_address};
 use super::super::vote_collector::Message;
 
+/// Number of the validator set epoch, as reported by the set on a transition.
+pub type Epoch = u64;
+
 /// Message transmitted between consensus participants.
 #[derive(Debug, PartialEq, Eq, Clone, Hash, Default)]
 pub struct ConsensusMessage {
@@ -95,10 +98,11 @@ impl ConsensusMessage {
 		})
 	}
 
-	pub fn verify(&self) -> Result<Address, Error> {
+	/// Recover the sender of a message cast in `epoch`.
+	pub fn verify(&self, epoch: Epoch) -> Result<Address, Error> {
 		let full_rlp = ::rlp::encode(self);
 		let block_info = Rlp::new(&full_rlp).at(1);
-		let public_key = recover(&self.signature.into(), &block_info.as_raw().sha3())?;
+		let public_key = recover(&self.signature.into(), &vote_hash(epoch, block_info.as_raw()))?;
 		Ok(public_to_address(&public_key))
 	}
 }
@@ -192,8 +196,21 @@ pub fn message_full_rlp(signature: &H520, vote_info: &Bytes) -> Bytes {
 	s.out()
 }
 
-pub fn message_hash(vote_step: VoteStep, block_hash: H256) -> H256 {
-	message_info_rlp(&vote_step, Some(block_hash)).sha3()
+/// Hash signed by a vote cast in `epoch`.
+///
+/// Votes of later epochs also sign the epoch, so that they can not be replayed after a validator set
+/// transition, even at the same height and view. The initial epoch signs the bare vote info.
+pub fn vote_hash(epoch: Epoch, vote_info: &[u8]) -> H256 {
+	if epoch == 0 {
+		return vote_info.sha3();
+	}
+	let mut s = RlpStream::new_list(2);
+	s.append(&epoch).append_raw(vote_info, 1);
+	s.out().sha3()
+}
+
+pub fn message_hash(epoch: Epoch, vote_step: VoteStep, block_hash: H256) -> H256 {
+	vote_hash(epoch, &message_info_rlp(&vote_step, Some(block_hash)))
 }
 
 #[cfg(test)]
@@ -258,7 +275,22 @@ mod tests {
 
 		let rlp = UntrustedRlp::new(&raw_rlp);
 		let message: ConsensusMessage = rlp.as_val().unwrap();
-		match message.verify() { Ok(a) if a == addr => {}, _ => panic!(), };
+		match message.verify(0) { Ok(a) if a == addr => {}, _ => panic!(), };
+	}
+
+	#[test]
+	fn signs_epoch() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let addr = tap.insert_account("0".sha3().into(), "0").unwrap();
+		tap.unlock_account_permanently(addr, "0".into()).unwrap();
+
+		let mi = message_info_rlp(&VoteStep::new(123, 2, Step::Prevote), None);
+		let raw_rlp = message_full_rlp(&tap.sign(addr, None, vote_hash(1, &mi)).unwrap().into(), &mi);
+		let message: ConsensusMessage = UntrustedRlp::new(&raw_rlp).as_val().unwrap();
+		assert_eq!(message.verify(1).unwrap(), addr);
+		// Same height and view in another epoch.
+		assert!(message.verify(2).map_or(true, |a| a != addr));
+		assert!(message.verify(0).map_or(true, |a| a != addr));
 	}
 
 	#[test]
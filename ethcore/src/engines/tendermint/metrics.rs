@@ -0,0 +1,242 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tendermint consensus message counters and view timing.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::time::{Duration, Instant};
+use util::Mutex;
+use super::Step;
+
+/// Number of most recent views used for latency statistics.
+const VIEW_LATENCY_WINDOW: usize = 128;
+
+/// Number of consensus messages, labeled by message type.
+#[derive(Debug, Default)]
+pub struct MessageCounter {
+	propose: AtomicUsize,
+	prevote: AtomicUsize,
+	precommit: AtomicUsize,
+}
+
+impl MessageCounter {
+	fn counter(&self, step: Step) -> Option<&AtomicUsize> {
+		match step {
+			Step::Propose => Some(&self.propose),
+			Step::Prevote => Some(&self.prevote),
+			Step::Precommit => Some(&self.precommit),
+			// Commits are not gossiped as messages.
+			Step::Commit => None,
+		}
+	}
+
+	/// Count one more message of the given type.
+	pub fn increment(&self, step: Step) {
+		if let Some(c) = self.counter(step) {
+			c.fetch_add(1, AtomicOrdering::SeqCst);
+		}
+	}
+
+	/// Number of messages of the given type.
+	pub fn get(&self, step: Step) -> usize {
+		self.counter(step).map_or(0, |c| c.load(AtomicOrdering::SeqCst))
+	}
+}
+
+/// Gossip volume of the engine.
+#[derive(Debug, Default)]
+pub struct MessageMetrics {
+	/// Messages received from peers, including proposal blocks.
+	pub messages_in: MessageCounter,
+	/// Messages sent to peers, including relayed ones.
+	pub messages_out: MessageCounter,
+}
+
+/// Engine activity since it was created.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeStats {
+	/// Seconds since the engine was created.
+	pub uptime_secs: u64,
+	/// Valid consensus messages handled.
+	pub messages_processed: usize,
+	/// Proposals received from peers.
+	pub proposals_seen: usize,
+	/// Heights committed.
+	pub commits: usize,
+}
+
+/// Counters behind `RuntimeStats`.
+#[derive(Debug)]
+pub struct RuntimeCounters {
+	started: Instant,
+	messages_processed: AtomicUsize,
+	proposals_seen: AtomicUsize,
+	commits: AtomicUsize,
+}
+
+impl Default for RuntimeCounters {
+	fn default() -> Self {
+		RuntimeCounters {
+			started: Instant::now(),
+			messages_processed: AtomicUsize::new(0),
+			proposals_seen: AtomicUsize::new(0),
+			commits: AtomicUsize::new(0),
+		}
+	}
+}
+
+impl RuntimeCounters {
+	/// Count a handled message.
+	pub fn note_message(&self) {
+		self.messages_processed.fetch_add(1, AtomicOrdering::SeqCst);
+	}
+
+	/// Count a received proposal.
+	pub fn note_proposal(&self) {
+		self.proposals_seen.fetch_add(1, AtomicOrdering::SeqCst);
+	}
+
+	/// Count a committed height.
+	pub fn note_commit(&self) {
+		self.commits.fetch_add(1, AtomicOrdering::SeqCst);
+	}
+
+	/// Current values of the counters.
+	pub fn stats(&self) -> RuntimeStats {
+		RuntimeStats {
+			uptime_secs: self.started.elapsed().as_secs(),
+			messages_processed: self.messages_processed.load(AtomicOrdering::SeqCst),
+			proposals_seen: self.proposals_seen.load(AtomicOrdering::SeqCst),
+			commits: self.commits.load(AtomicOrdering::SeqCst),
+		}
+	}
+}
+
+/// Gauges of the consensus round.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsensusMetrics {
+	/// Current height.
+	pub height: usize,
+	/// Current view.
+	pub view: usize,
+	/// Proposals received in the current view.
+	pub proposals: usize,
+	/// Prevotes collected in the current view.
+	pub prevotes: usize,
+	/// Precommits collected in the current view.
+	pub precommits: usize,
+	/// Messages held by the vote collector, for all rounds.
+	pub known_messages: usize,
+}
+
+/// Time spent in recent views.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewLatencyStats {
+	/// Number of views in the window.
+	pub views: usize,
+	/// Average time spent in a view.
+	pub average: Duration,
+	/// Longest time spent in a view.
+	pub max: Duration,
+}
+
+/// Tracks how long views last until either a commit or a view change.
+#[derive(Debug)]
+pub struct ViewLatency {
+	view_start: Mutex<Instant>,
+	durations: Mutex<VecDeque<Duration>>,
+}
+
+impl Default for ViewLatency {
+	fn default() -> Self {
+		ViewLatency {
+			view_start: Mutex::new(Instant::now()),
+			durations: Mutex::new(VecDeque::with_capacity(VIEW_LATENCY_WINDOW)),
+		}
+	}
+}
+
+impl ViewLatency {
+	/// Record the end of the current view.
+	pub fn end_view(&self) {
+		self.end_view_at(Instant::now());
+	}
+
+	fn end_view_at(&self, now: Instant) {
+		let mut start = self.view_start.lock();
+		let mut durations = self.durations.lock();
+		if durations.len() == VIEW_LATENCY_WINDOW {
+			durations.pop_front();
+		}
+		durations.push_back(now.duration_since(*start));
+		*start = now;
+	}
+
+	/// Statistics over the recent views.
+	pub fn stats(&self) -> ViewLatencyStats {
+		let durations = self.durations.lock();
+		let total = durations.iter().fold(Duration::new(0, 0), |acc, d| acc + *d);
+		ViewLatencyStats {
+			views: durations.len(),
+			average: if durations.is_empty() { total } else { total / durations.len() as u32 },
+			max: durations.iter().max().cloned().unwrap_or_else(|| Duration::new(0, 0)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+	use super::super::Step;
+	use super::*;
+
+	#[test]
+	fn counts_by_step() {
+		let counter = MessageCounter::default();
+		counter.increment(Step::Prevote);
+		counter.increment(Step::Prevote);
+		counter.increment(Step::Precommit);
+		counter.increment(Step::Commit);
+		assert_eq!(counter.get(Step::Propose), 0);
+		assert_eq!(counter.get(Step::Prevote), 2);
+		assert_eq!(counter.get(Step::Precommit), 1);
+		assert_eq!(counter.get(Step::Commit), 0);
+	}
+
+	#[test]
+	fn view_latency_over_window() {
+		let latency = ViewLatency::default();
+		assert_eq!(latency.stats().views, 0);
+
+		let start = *latency.view_start.lock();
+		latency.end_view_at(start + Duration::from_millis(100));
+		latency.end_view_at(start + Duration::from_millis(400));
+		let stats = latency.stats();
+		assert_eq!(stats.views, 2);
+		assert_eq!(stats.average, Duration::from_millis(200));
+		assert_eq!(stats.max, Duration::from_millis(300));
+
+		let mut now = Instant::now();
+		for _ in 0..(VIEW_LATENCY_WINDOW + 10) {
+			now = now + Duration::from_millis(10);
+			latency.end_view_at(now);
+		}
+		let stats = latency.stats();
+		assert_eq!(stats.views, VIEW_LATENCY_WINDOW);
+		assert_eq!(stats.max, Duration::from_millis(10));
+	}
+}
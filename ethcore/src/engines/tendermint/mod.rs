@@ -23,30 +23,49 @@
 /// Once enough votes have been gathered the proposer issues that block in the `Commit` step.
 
 mod message;
+mod metrics;
 mod params;
+mod span;
+mod store;
+pub mod verify;
+#[cfg(all(feature="benches", test))]
+mod benches;
 
-use std::sync::Weak;
-use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+pub use self::metrics::{ViewLatencyStats, RuntimeStats};
+
+use std::sync::{Weak, mpsc};
+use std::sync::atomic::{AtomicUsize, AtomicBool, Ordering as AtomicOrdering};
+use std::time::{Instant, Duration as StdDuration};
+use rand::{Rng, thread_rng};
+use rustc_serialize::json::Json;
 use util::*;
 use client::{Client, EngineClient};
-use error::{Error, BlockError};
+use error::{Error, BlockError, TransactionError};
 use header::{Header, BlockNumber};
 use builtin::Builtin;
-use rlp::UntrustedRlp;
+use rlp::{UntrustedRlp, RlpStream};
 use ethkey::{recover, public_to_address, Signature};
 use account_provider::AccountProvider;
 use block::*;
 use spec::CommonParams;
-use engines::{Engine, Seal, EngineError};
+use engines::{Engine, Seal, EngineError, ConsensusIntrospection};
 use evm::Schedule;
-use state::CleanupMode;
-use io::IoService;
+use state::{State, CleanupMode};
+use state_db::StateDB;
+use io::{IoService, IoContext, IoHandler, IoError, TimerToken};
+use ids::BlockId;
+use transaction::{SignedTransaction, UnverifiedTransaction};
+use views::BlockView;
 use super::signer::EngineSigner;
 use super::validator_set::ValidatorSet;
-use super::transition::TransitionHandler;
+use super::transition::{TransitionHandler, Timeouts, retry_io};
 use super::vote_collector::VoteCollector;
 use self::message::*;
-use self::params::TendermintParams;
+use self::params::{TendermintParams, TendermintTimeouts, RequiredTransaction, SealSignatures, PrimaryStrategy};
+use self::span::RoundSpan;
+use self::store::{VoteStore, NoopStore, DatabaseStore, StoredVotes};
+use self::metrics::{MessageMetrics, ViewLatency, RuntimeCounters, ConsensusMetrics};
+use self::verify::{CommitRules, check_above_ratio, verify_precommits_with_rules, verify_commit, is_signed_by, commit_messages, finality_proof};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub enum Step {
@@ -69,19 +88,75 @@ pub type Height = usize;
 pub type View = usize;
 pub type BlockHash = H256;
 
+/// Part of the consensus process which can be logged separately.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Phase {
+	Propose,
+	Prevote,
+	Precommit,
+	Commit,
+	NewView,
+}
+
+impl From<Step> for Phase {
+	fn from(step: Step) -> Self {
+		match step {
+			Step::Propose => Phase::Propose,
+			Step::Prevote => Phase::Prevote,
+			Step::Precommit => Phase::Precommit,
+			Step::Commit => Phase::Commit,
+		}
+	}
+}
+
+/// Runtime switches for per phase logging, everything is logged by default.
+#[derive(Debug)]
+struct LogPhases(AtomicUsize);
+
+impl Default for LogPhases {
+	fn default() -> Self {
+		LogPhases(AtomicUsize::new(usize::max_value()))
+	}
+}
+
+impl LogPhases {
+	fn set(&self, phases: &[Phase]) {
+		let mask = phases.iter().fold(0, |mask, phase| mask | (1 << *phase as usize));
+		self.0.store(mask, AtomicOrdering::SeqCst);
+	}
+
+	fn is_enabled(&self, phase: Phase) -> bool {
+		self.0.load(AtomicOrdering::SeqCst) & (1 << phase as usize) != 0
+	}
+}
+
+/// Log under the engine target, unless the phase is silenced.
+/// Messages are tagged with the round of the current `RoundSpan`.
+macro_rules! phase_log {
+	($engine: expr, $phase: expr, $level: ident, $($arg: tt)*) => {
+		if $engine.log_phases.is_enabled($phase.into()) {
+			$level!(target: "engine", "{}{}", span::log_prefix(), format_args!($($arg)*));
+		}
+	}
+}
+
 /// Engine using `Tendermint` consensus algorithm, suitable for EVM chain.
 pub struct Tendermint {
 	params: CommonParams,
 	gas_limit_bound_divisor: U256,
 	builtins: BTreeMap<Address, Builtin>,
 	step_service: IoService<Step>,
+	/// Last step transition reached the step service.
+	step_timer_healthy: AtomicBool,
 	client: RwLock<Option<Weak<EngineClient>>>,
 	block_reward: U256,
+	/// Block rewards from given blocks on, sorted by block number.
+	block_reward_schedule: Vec<(BlockNumber, U256)>,
 	registrar: Address,
 	/// Blockchain height.
 	height: AtomicUsize,
 	/// Consensus view.
-	view: AtomicUsize,
+	view: Arc<AtomicUsize>,
 	/// Consensus step.
 	step: RwLock<Step>,
 	/// Vote accumulator.
@@ -100,11 +175,396 @@ pub struct Tendermint {
 	last_proposed: RwLock<H256>,
 	/// Set used to determine the current validators.
 	validators: Box<ValidatorSet>,
+	/// Counts of gossiped messages.
+	metrics: MessageMetrics,
+	/// Time spent in recent views.
+	view_latency: ViewLatency,
+	/// Activity since creation.
+	runtime: RuntimeCounters,
+	/// Senders not allowed to have transactions in blocks.
+	sender_blacklist: HashSet<Address>,
+	/// Phases which are currently logged.
+	log_phases: LogPhases,
+	/// Proposers of the first view at the given heights, overriding the rotation.
+	emergency_proposers: BTreeMap<Height, Address>,
+	/// Leave the height on a peer commit of it.
+	adopt_peer_commits: bool,
+	/// Rotation of proposers.
+	primary_strategy: PrimaryStrategy,
+	/// Proposer nonce is mapped onto cumulative validator weights.
+	weighted_proposer: bool,
+	/// Time to keep collecting precommits after reaching quorum.
+	commit_grace_window: Option<StdDuration>,
+	/// Commit seal waiting for the grace window to pass.
+	pending_seal: Mutex<Option<PendingSeal>>,
+	/// Last looked up proposer, keyed by parent hash and round.
+	proposer_cache: RwLock<Option<(H256, Height, View, Address)>>,
+	/// Receives commit proofs.
+	commit_sink: RwLock<Box<CommitSink>>,
+	/// Saves votes of the current height and the lock if `persist_votes` is set.
+	vote_store: Box<VoteStore>,
+	/// Contents of the vote store when the engine was created, restored once the client is registered.
+	stored_votes: Mutex<Option<StoredVotes>>,
+	/// Votes are saved and restored across restarts.
+	persist_votes: bool,
+	/// Receives committed blocks imported by the client.
+	finality_gadget: RwLock<Box<FinalityGadget>>,
+	/// How far below the best block a proposal can be built.
+	max_proposal_depth: Option<BlockNumber>,
+	/// Number of double votes seen from each validator.
+	double_votes: RwLock<HashMap<Address, usize>>,
+	/// Proof of the first double vote of a validator in a round.
+	double_vote_proofs: RwLock<HashMap<(Address, VoteStep), Bytes>>,
+	/// View in which the last height was committed.
+	last_commit_view: AtomicUsize,
+	/// Height and hash of the last committed block, if known.
+	last_commit: RwLock<Option<(Height, H256)>>,
+	/// Highest height known to be committed, locally or in the chain.
+	finalized_height: AtomicUsize,
+	/// Views in which this node was the proposer, but did not propose before the timeout.
+	missed_primary_slots: AtomicUsize,
+	/// Height, parent and transactions root of the last block proposed by each validator.
+	height_proposals: RwLock<HashMap<Address, (Height, H256, H256)>>,
+	/// Number of times each validator proposed different blocks at one height.
+	height_equivocations: RwLock<HashMap<Address, usize>>,
+	/// Number of heights the network may be ahead for this node to sign proposals and votes.
+	sync_distance: Option<usize>,
+	/// Highest height each validator voted at, which tells how far the network is ahead.
+	voted_heights: RwLock<HashMap<Address, Height>>,
+	/// Highest future view each validator voted in, along with its height.
+	future_views: RwLock<HashMap<Address, (Height, View)>>,
+	/// Proposal counts as a prevote of the proposer.
+	proposer_vote_counts: bool,
+	/// Vote epoch starting at each height, loaded from the epoch transitions of the canonical chain.
+	epochs: RwLock<BTreeMap<Height, Epoch>>,
+	/// Number of blocks a block reward is held for.
+	reward_maturity: Option<BlockNumber>,
+	/// Receivers of lifecycle events.
+	lifecycle_subscribers: Mutex<Vec<mpsc::Sender<LifecycleEvent>>>,
+	/// Receivers of commit events.
+	commit_subscribers: Mutex<Vec<mpsc::SyncSender<CommittedBlock>>>,
+	/// Step service was stopped.
+	stopped: AtomicBool,
+	/// Proposals are generated when this validator is the proposer.
+	proposing_enabled: AtomicBool,
+	/// Transactions have to be in canonical order.
+	canonical_transaction_order: bool,
+	/// Calls which have to be in every block.
+	required_transactions: Vec<RequiredTransaction>,
+	/// Precommits included in commit seals.
+	seal_signatures: SealSignatures,
+	/// Step timeouts, as used by the step service.
+	timeouts: TendermintTimeouts,
+	/// Hashes of messages rebroadcast since the last height change.
+	rebroadcast: Mutex<HashSet<H256>>,
+	/// Highest view entered on timeouts.
+	max_view: Option<View>,
+	/// Seconds a block timestamp can be ahead of the local clock.
+	allowed_future_time: u64,
+	/// Never propose, vote or rebroadcast.
+	observer_only: bool,
+	/// New validator messages applied in one slice.
+	max_messages_per_slice: Option<usize>,
+	/// New validator messages applied in the current slice.
+	slice_messages: AtomicUsize,
+	/// Validator messages above the limit of their slice or the rate of their sender,
+	/// with their sender, in order of arrival.
+	deferred_messages: Mutex<VecDeque<(ConsensusMessage, Bytes, Address)>>,
+	/// New messages applied from one validator per second.
+	messages_per_second: Option<usize>,
+	/// Start of the current second and messages accepted in it, for each validator.
+	message_rates: Mutex<HashMap<Address, (Instant, usize)>>,
+	/// Views below the current one which keep their votes.
+	max_retained_views: Option<View>,
+	/// Messages kept by the vote collector above which votes of past views are dropped.
+	max_retained_messages: usize,
+	eip160_transition: BlockNumber,
+	eip161abc_transition: BlockNumber,
+	eip161d_transition: BlockNumber,
+	max_code_size: u64,
+	/// Maximum random delay of the rebroadcast on timeouts.
+	broadcast_jitter: ::time::Duration,
+	/// Delay of the rebroadcast requested by the last timeout, until the rebroadcast handler arms it.
+	rebroadcast_delay: Mutex<Option<StdDuration>>,
+	/// Rules which commit seals are checked against.
+	commit_rules: CommitRules,
+}
+
+/// Step timeouts which grow with the current view.
+struct ViewTimeouts {
+	timeouts: TendermintTimeouts,
+	view: Arc<AtomicUsize>,
+}
+
+impl Timeouts<Step> for ViewTimeouts {
+	fn initial(&self) -> ::time::Duration {
+		self.timeouts.initial()
+	}
+
+	/// Steps are sent to the handler after the view they are entered in is set.
+	fn timeout(&self, step: &Step) -> ::time::Duration {
+		self.timeouts.view_timeout(step, self.view.load(AtomicOrdering::SeqCst))
+	}
+}
+
+/// Timer token of message processing slices.
+const MESSAGE_SLICE_TOKEN: TimerToken = 24;
+
+/// Starts a new message processing slice periodically.
+struct MessageSliceHandler {
+	engine: Weak<Tendermint>,
+	interval: ::time::Duration,
+}
+
+impl IoHandler<Step> for MessageSliceHandler {
+	fn initialize(&self, io: &IoContext<Step>) {
+		io.register_timer(MESSAGE_SLICE_TOKEN, self.interval.num_milliseconds() as u64)
+			.unwrap_or_else(|e| warn!(target: "engine", "Failed to set message slice timer: {}.", e))
+	}
+
+	fn timeout(&self, _io: &IoContext<Step>, timer: TimerToken) {
+		if timer == MESSAGE_SLICE_TOKEN {
+			if let Some(engine) = self.engine.upgrade() {
+				engine.next_message_slice();
+			}
+		}
+	}
+}
+
+/// Timer token of a delayed rebroadcast.
+const REBROADCAST_TOKEN: TimerToken = 25;
+
+/// Rebroadcasts after the delay requested by a step timeout.
+/// The delay is armed when the step transition of the timeout is delivered, next to the step timer.
+struct RebroadcastHandler {
+	engine: Weak<Tendermint>,
+}
+
+impl IoHandler<Step> for RebroadcastHandler {
+	fn message(&self, io: &IoContext<Step>, _next: &Step) {
+		if let Some(engine) = self.engine.upgrade() {
+			if let Some(delay) = engine.rebroadcast_delay.lock().take() {
+				let ms = delay.as_secs() * 1000 + (delay.subsec_nanos() / 1_000_000) as u64;
+				if let Err(e) = retry_io(|| io.register_timer_once(REBROADCAST_TOKEN, ms)) {
+					warn!(target: "engine", "Failed to set rebroadcast timer, rebroadcasting now: {}.", e);
+					engine.broadcast_old_messages();
+				}
+			}
+		}
+	}
+
+	fn timeout(&self, _io: &IoContext<Step>, timer: TimerToken) {
+		if timer == REBROADCAST_TOKEN {
+			if let Some(engine) = self.engine.upgrade() {
+				engine.broadcast_old_messages();
+			}
+		}
+	}
+}
+
+/// Timer token of the commit grace window.
+const GRACE_WINDOW_TOKEN: TimerToken = 26;
+
+/// Submits the pending seal once its grace window has passed.
+/// The timer is armed when the commit step transition is delivered.
+struct GraceWindowHandler {
+	engine: Weak<Tendermint>,
+}
+
+impl IoHandler<Step> for GraceWindowHandler {
+	fn message(&self, io: &IoContext<Step>, _next: &Step) {
+		if let Some(engine) = self.engine.upgrade() {
+			if let Some(delay) = engine.arm_pending_seal() {
+				// Rounded up, so that the deadline has passed when the timer fires.
+				let ms = delay.as_secs() * 1000 + (delay.subsec_nanos() / 1_000_000) as u64 + 1;
+				if let Err(e) = retry_io(|| io.register_timer_once(GRACE_WINDOW_TOKEN, ms)) {
+					warn!(target: "engine", "Failed to set grace window timer, sealing now: {}.", e);
+					engine.submit_pending_seal(true);
+				}
+			}
+		}
+	}
+
+	fn timeout(&self, _io: &IoContext<Step>, timer: TimerToken) {
+		if timer == GRACE_WINDOW_TOKEN {
+			if let Some(engine) = self.engine.upgrade() {
+				engine.submit_pending_seal(false);
+			}
+		}
+	}
+}
+
+/// Live consensus state, for operators.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsensusState {
+	/// Height being voted on.
+	pub height: Height,
+	/// View within the height.
+	pub view: View,
+	/// Current step.
+	pub step: Step,
+	/// This node is the proposer of the current view.
+	pub is_proposer: bool,
+	/// Votes in the current step for the proposal of the current view.
+	pub proposal_votes: usize,
+}
+
+/// Verification stage which rejected a block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VerificationStage {
+	/// Checks of the header alone.
+	Basic,
+	/// Checks of the block contents.
+	Unordered,
+	/// Checks against the parent block and the current validators.
+	Family,
+}
+
+/// Receives proofs of blocks committed by this node, for example to relay them to another chain.
+pub trait CommitSink: Send + Sync {
+	/// Called with the precommit signatures used to seal our proposal at the given round.
+	fn on_commit(&self, block_hash: &H256, height: Height, view: View, signatures: &[H520]);
+}
+
+/// Receives every committed block imported by the client, for example to emit
+/// finality messages of a light client protocol.
+pub trait FinalityGadget: Send + Sync {
+	/// Called with a committed header and the raw precommit messages which committed it.
+	fn on_finalized(&self, header: &Header, proof: &[Bytes]);
+}
+
+/// Gadget which ignores committed blocks.
+pub struct NoopGadget;
+
+impl FinalityGadget for NoopGadget {
+	fn on_finalized(&self, _header: &Header, _proof: &[Bytes]) {}
+}
+
+/// Change in the state of the engine, for external supervisors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LifecycleEvent {
+	/// Engine is created and running.
+	Started,
+	/// Signer of a current validator is set, the engine takes part in consensus.
+	Participating(Address),
+	/// Engine was stopped.
+	Stopped,
+}
+
+/// Block which reached a quorum of precommits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommittedBlock {
+	/// Height of the block.
+	pub height: Height,
+	/// View in which it was committed.
+	pub view: View,
+	/// Bare hash of the block.
+	pub block_hash: H256,
+	/// Validators which precommitted to the block, ordered by address.
+	pub signers: Vec<Address>,
+}
+
+/// Sink which discards commits.
+pub struct NoopSink;
+
+impl CommitSink for NoopSink {
+	fn on_commit(&self, _block_hash: &H256, _height: Height, _view: View, _signatures: &[H520]) {}
+}
+
+/// Number of commit events buffered for each subscriber, later events are dropped until it catches up.
+pub const COMMIT_SUBSCRIPTION_CAPACITY: usize = 64;
+
+/// Maximal size of the messages sent by one call of `broadcast_old_messages`.
+pub const MAX_REBROADCAST_BYTES: usize = 512 * 1024;
+
+/// Maximal number of messages waiting for a processing slice, further messages are dropped.
+pub const MAX_DEFERRED_MESSAGES: usize = 4096;
+
+/// Default number of messages kept by the vote collector above which votes of past views are dropped.
+pub const MAX_RETAINED_MESSAGES: usize = 16 * 1024;
+
+/// Number of double votes after which a validator is reported as persistently equivocating.
+pub const DOUBLE_VOTE_ALERT_THRESHOLD: usize = 3;
+
+/// Maximal number of blocks scanned by `Tendermint::validator_signed_blocks`.
+pub const MAX_SIGNED_BLOCKS_RANGE: BlockNumber = 10_000;
+
+/// Commit of our own proposal which waits for additional precommits.
+struct PendingSeal {
+	vote_step: VoteStep,
+	block_hash: H256,
+	/// Parent of the proposal, the height moves on before the seal is submitted.
+	parent: H256,
+	deadline: Instant,
+	/// Whether the timer of the deadline is set.
+	armed: bool,
+}
+
+/// Derive the proposer nonce from the parent block hash mixed with the view.
+///
+/// Every node agrees on the parent once it is committed, so the schedule is deterministic,
+/// but it can not be computed before the parent is sealed, which rules out planning attacks
+/// on proposers further ahead. The proposer of the parent can still grind over the contents
+/// of its block to influence the next proposer, so this is no substitute for a random beacon.
+fn seeded_proposer_nonce(parent_hash: &H256, view: View) -> usize {
+	let mut s = RlpStream::new_list(2);
+	s.append(parent_hash).append(&view);
+	U256::from(s.out().sha3()).low_u64() as usize
+}
+
+/// Canonical transaction order, by nonce and then hash.
+/// Transactions of each sender stay in nonce order, while the proposer has no say over the order.
+fn canonical_order_key(t: &UnverifiedTransaction) -> (U256, H256) {
+	(t.nonce, t.hash())
+}
+
+/// Account whose storage maps block numbers to the authors of rewards which did not mature yet.
+fn pending_rewards_address() -> Address {
+	Address::from(0xff)
+}
+
+/// Hold the reward for block `number` and credit the reward which matures with it.
+/// Pending rewards are kept in state, so they follow the chain through reorganisations.
+/// The holder gets a nonce, otherwise touching it would kill the empty account with its storage.
+fn credit_matured_reward(state: &mut State<StateDB>, author: &Address, number: BlockNumber, maturity: BlockNumber, reward: &U256) -> ::util::trie::Result<()> {
+	let pending = pending_rewards_address();
+	if state.nonce(&pending)?.is_zero() {
+		state.inc_nonce(&pending)?;
+	}
+	state.set_storage(&pending, H256::from(number), H256::from(author))?;
+	if let Some(matured) = number.checked_sub(maturity) {
+		let key = H256::from(matured);
+		let owner = state.storage_at(&pending, &key)?;
+		if !owner.is_zero() {
+			state.add_balance(&Address::from(owner), reward, CleanupMode::NoEmpty)?;
+			state.set_storage(&pending, key, H256::zero())?;
+		}
+	}
+	Ok(())
 }
 
 impl Tendermint {
 	/// Create a new instance of Tendermint engine
 	pub fn new(params: CommonParams, our_params: TendermintParams, builtins: BTreeMap<Address, Builtin>) -> Result<Arc<Self>, Error> {
+		// Used as a divisor when deriving and verifying gas limits.
+		if our_params.gas_limit_bound_divisor.is_zero() {
+			return Err(EngineError::InvalidParams("gasLimitBoundDivisor has to be non-zero.".into()).into());
+		}
+		if !our_params.quorum_ratio.is_safe() {
+			let ratio = our_params.quorum_ratio;
+			return Err(EngineError::InvalidParams(format!("quorumRatio {}/{} has to be above 1/2 and at most 1.", ratio.numerator, ratio.denominator)).into());
+		}
+		// Minimal seals would leave out the precommits collected in the grace window.
+		if our_params.commit_grace_window.is_some() && our_params.seal_signatures == SealSignatures::Minimal {
+			return Err(EngineError::InvalidParams("commitGraceWindow can not be combined with minimalCommitSeal.".into()).into());
+		}
+		let vote_store: Box<VoteStore> = match (our_params.persist_votes, our_params.vote_store_path) {
+			(true, Some(path)) => Box::new(DatabaseStore::open(&path)
+				.map_err(|e| EngineError::InvalidParams(format!("Could not open the vote store at {}: {}", path, e)))?),
+			(true, None) => return Err(EngineError::InvalidParams("persistVotes requires voteStorePath.".into()).into()),
+			(false, _) => Box::new(NoopStore),
+		};
+		let stored_votes = vote_store.load();
 		let engine = Arc::new(
 			Tendermint {
 				params: params,
@@ -112,10 +572,12 @@ impl Tendermint {
 				builtins: builtins,
 				client: RwLock::new(None),
 				step_service: IoService::<Step>::start()?,
+				step_timer_healthy: AtomicBool::new(true),
 				block_reward: our_params.block_reward,
+				block_reward_schedule: our_params.block_reward_schedule,
 				registrar: our_params.registrar,
 				height: AtomicUsize::new(1),
-				view: AtomicUsize::new(0),
+				view: Arc::new(AtomicUsize::new(0)),
 				step: RwLock::new(Step::Propose),
 				votes: Default::default(),
 				signer: Default::default(),
@@ -125,9 +587,82 @@ impl Tendermint {
 				proposal_parent: Default::default(),
 				last_proposed: Default::default(),
 				validators: our_params.validators,
+				metrics: Default::default(),
+				view_latency: Default::default(),
+				runtime: Default::default(),
+				sender_blacklist: our_params.sender_blacklist,
+				log_phases: Default::default(),
+				emergency_proposers: our_params.emergency_proposers,
+				adopt_peer_commits: our_params.adopt_peer_commits,
+				primary_strategy: our_params.primary_strategy,
+				weighted_proposer: our_params.weighted_proposer,
+				commit_grace_window: our_params.commit_grace_window.and_then(|d| d.to_std().ok()),
+				pending_seal: Mutex::new(None),
+				proposer_cache: RwLock::new(None),
+				commit_sink: RwLock::new(Box::new(NoopSink)),
+				vote_store: vote_store,
+				stored_votes: Mutex::new(Some(stored_votes)),
+				persist_votes: our_params.persist_votes,
+				finality_gadget: RwLock::new(Box::new(NoopGadget)),
+				max_proposal_depth: our_params.max_proposal_depth,
+				double_votes: RwLock::new(HashMap::new()),
+				double_vote_proofs: RwLock::new(HashMap::new()),
+				last_commit_view: AtomicUsize::new(0),
+				last_commit: RwLock::new(None),
+				finalized_height: AtomicUsize::new(0),
+				missed_primary_slots: AtomicUsize::new(0),
+				height_proposals: RwLock::new(HashMap::new()),
+				height_equivocations: RwLock::new(HashMap::new()),
+				sync_distance: our_params.sync_distance,
+				voted_heights: RwLock::new(HashMap::new()),
+				future_views: RwLock::new(HashMap::new()),
+				proposer_vote_counts: our_params.proposer_vote_counts,
+				epochs: RwLock::new(BTreeMap::new()),
+				reward_maturity: our_params.reward_maturity,
+				lifecycle_subscribers: Mutex::new(Vec::new()),
+				commit_subscribers: Mutex::new(Vec::new()),
+				stopped: AtomicBool::new(false),
+				proposing_enabled: AtomicBool::new(true),
+				canonical_transaction_order: our_params.canonical_transaction_order,
+				required_transactions: our_params.required_transactions,
+				seal_signatures: our_params.seal_signatures,
+				timeouts: our_params.timeouts.clone(),
+				rebroadcast: Mutex::new(HashSet::new()),
+				max_view: our_params.max_view,
+				allowed_future_time: our_params.allowed_future_time,
+				observer_only: our_params.observer_only,
+				max_messages_per_slice: our_params.max_messages_per_slice,
+				slice_messages: AtomicUsize::new(0),
+				deferred_messages: Mutex::new(VecDeque::new()),
+				messages_per_second: our_params.messages_per_second,
+				message_rates: Mutex::new(HashMap::new()),
+				max_retained_views: our_params.max_retained_views,
+				max_retained_messages: our_params.max_retained_messages,
+				eip160_transition: our_params.eip160_transition,
+				eip161abc_transition: our_params.eip161abc_transition,
+				eip161d_transition: our_params.eip161d_transition,
+				max_code_size: our_params.max_code_size,
+				broadcast_jitter: our_params.broadcast_jitter,
+				rebroadcast_delay: Mutex::new(None),
+				commit_rules: CommitRules {
+					ordered_signers_transition: our_params.ordered_signers_transition,
+					signer_keys: our_params.signer_keys,
+					quorum_ratio: our_params.quorum_ratio,
+				},
 			});
-		let handler = TransitionHandler::new(Arc::downgrade(&engine) as Weak<Engine>, Box::new(our_params.timeouts));
+		let timeouts = ViewTimeouts { timeouts: our_params.timeouts, view: engine.view.clone() };
+		let handler = TransitionHandler::new(Arc::downgrade(&engine) as Weak<Engine>, Box::new(timeouts));
 		engine.step_service.register_handler(Arc::new(handler))?;
+		if engine.max_messages_per_slice.is_some() || engine.messages_per_second.is_some() {
+			let slices = MessageSliceHandler { engine: Arc::downgrade(&engine), interval: our_params.message_slice_interval };
+			engine.step_service.register_handler(Arc::new(slices))?;
+		}
+		if engine.broadcast_jitter > ::time::Duration::zero() {
+			engine.step_service.register_handler(Arc::new(RebroadcastHandler { engine: Arc::downgrade(&engine) }))?;
+		}
+		if engine.commit_grace_window.is_some() {
+			engine.step_service.register_handler(Arc::new(GraceWindowHandler { engine: Arc::downgrade(&engine) }))?;
+		}
 		Ok(engine)
 	}
 
@@ -147,807 +682,4321 @@ impl Tendermint {
 		}
 	}
 
+	/// Seal our proposal using all precommits collected for it and remove old votes.
+	/// Signatures and `seal[0]` both come from `vote_step` and never from `self.view`,
+	/// which may be changed concurrently by other messages or timeouts.
+	/// Precommits of validators which are not in the set at the proposal `parent` are left out.
+	fn submit_commit(&self, vote_step: &VoteStep, block_hash: H256, parent: &H256) {
+		let mut signers: Vec<_> = self.votes.round_signers(vote_step, &block_hash).into_iter()
+			.filter(|&(ref signer, _)| self.validators.contains(parent, signer))
+			.collect();
+		if self.seal_signatures == SealSignatures::Minimal {
+			// Signatures are ordered by signer, which makes the selection canonical.
+			let quorum = self.commit_rules.quorum_ratio.quorum(self.validators.total_weight(parent) as usize);
+			let mut weight = 0;
+			let needed = signers.iter()
+				.position(|&(ref signer, _)| { weight += self.vote_weight(parent, signer); weight >= quorum })
+				.map_or(signers.len(), |last| last + 1);
+			signers.truncate(needed);
+		}
+		let precommits: Vec<_> = signers.into_iter().map(|(_, signature)| signature).collect();
+		phase_log!(self, Phase::Commit, trace, "Collected seal: {:?}", precommits);
+		self.commit_sink.read().on_commit(&block_hash, vote_step.height, vote_step.view, &precommits);
+		let seal = vec![
+			::rlp::encode(&vote_step.view).to_vec(),
+			::rlp::NULL_RLP.to_vec(),
+			::rlp::encode_list(&precommits).to_vec()
+		];
+		self.submit_seal(block_hash, seal);
+		self.votes.throw_out_old(vote_step);
+	}
+
+	/// Take the pending commit if its grace window has passed or if `force` is set.
+	fn take_pending_seal(&self, force: bool) -> Option<PendingSeal> {
+		let mut pending = self.pending_seal.lock();
+		let due = pending.as_ref().map_or(false, |p| force || Instant::now() >= p.deadline);
+		if due { pending.take() } else { None }
+	}
+
+	fn submit_pending_seal(&self, force: bool) {
+		if let Some(pending) = self.take_pending_seal(force) {
+			self.submit_commit(&pending.vote_step, pending.block_hash, &pending.parent);
+		}
+	}
+
+	/// Time left in the grace window of a pending seal without a timer, which is then considered set.
+	fn arm_pending_seal(&self) -> Option<StdDuration> {
+		let mut pending = self.pending_seal.lock();
+		match *pending {
+			Some(ref mut pending) if !pending.armed => {
+				pending.armed = true;
+				let now = Instant::now();
+				Some(if pending.deadline > now { pending.deadline - now } else { StdDuration::from_millis(0) })
+			},
+			_ => None,
+		}
+	}
+
 	fn broadcast_message(&self, message: Bytes) {
+		if self.stopped.load(AtomicOrdering::SeqCst) {
+			return;
+		}
 		if let Some(ref weak) = *self.client.read() {
 			if let Some(c) = weak.upgrade() {
+				if let Ok(step) = UntrustedRlp::new(&message).at(1).and_then(|info| info.val_at::<Step>(2)) {
+					self.metrics.messages_out.increment(step);
+				}
 				c.broadcast_consensus_message(message);
 			}
 		}
 	}
 
-	fn generate_message(&self, block_hash: Option<BlockHash>) -> Option<Bytes> {
-		let h = self.height.load(AtomicOrdering::SeqCst);
-		let r = self.view.load(AtomicOrdering::SeqCst);
-		let s = *self.step.read();
-		let vote_info = message_info_rlp(&VoteStep::new(h, r, s), block_hash);
-		match self.signer.sign(vote_info.sha3()).map(Into::into) {
-			Ok(signature) => {
-				let message_rlp = message_full_rlp(&signature, &vote_info);
-				let message = ConsensusMessage::new(signature, h, r, s, block_hash);
-				let validator = self.signer.address();
-				self.votes.vote(message.clone(), &validator);
-				debug!(target: "engine", "Generated {:?} as {}.", message, validator);
-				self.handle_valid_message(&message);
+	/// Complete snapshot of the round state as JSON, for external analysis.
+	pub fn dump_state_json(&self) -> String {
+		let height = self.height.load(AtomicOrdering::SeqCst);
+		let view = self.view.load(AtomicOrdering::SeqCst);
+		let hex = |hash: &H256| Json::String(format!("0x{}", hash.hex()));
+		let votes = [Step::Propose, Step::Prevote, Step::Precommit].iter().map(|step| {
+			let voters = self.votes.round_voters(&VoteStep::new(height, view, *step))
+				.into_iter()
+				.map(|(address, bh)| (format!("0x{}", address.hex()), bh.as_ref().map_or(Json::Null, &hex)))
+				.collect();
+			(format!("{:?}", step).to_lowercase(), Json::Object(voters))
+		}).collect();
+		let proposer = self.view_proposer(&*self.proposal_parent.read(), height, view);
+		let locked = self.lock_change.read().as_ref().and_then(|m| m.block_hash);
+		let state: BTreeMap<String, Json> = map![
+			"height".into() => Json::U64(height as u64),
+			"view".into() => Json::U64(view as u64),
+			"step".into() => Json::String(format!("{:?}", *self.step.read()).to_lowercase()),
+			"proposal".into() => self.proposal.read().as_ref().map_or(Json::Null, &hex),
+			"proposer".into() => Json::String(format!("0x{}", proposer.hex())),
+			"locked".into() => locked.as_ref().map_or(Json::Null, &hex),
+			"votes".into() => Json::Object(votes)
+		];
+		Json::Object(state).to_string()
+	}
 
-				Some(message_rlp)
-			},
-			Err(e) => {
-				trace!(target: "engine", "Could not sign the message {}", e);
-				None
-			},
-		}
+	/// Reward of the block `number`: the last schedule step starting at or below it, or the flat reward before the first step.
+	fn block_reward_at(&self, number: BlockNumber) -> U256 {
+		self.block_reward_schedule.iter().rev()
+			.find(|&&(from, _)| from <= number)
+			.map_or(self.block_reward, |&(_, reward)| reward)
 	}
 
-	fn generate_and_broadcast_message(&self, block_hash: Option<BlockHash>) {
-		if let Some(message) = self.generate_message(block_hash) {
-			self.broadcast_message(message);
+	/// Check if no block is ever rewarded.
+	fn issues_no_rewards(&self) -> bool {
+		self.block_reward.is_zero() && self.block_reward_schedule.iter().all(|&(_, ref reward)| reward.is_zero())
+	}
+
+	/// Parameters in use as JSON, with defaults filled in for values missing from the spec.
+	/// Keys follow the spec names.
+	pub fn effective_config(&self) -> Json {
+		let ms = |d: &::time::Duration| Json::U64(d.num_milliseconds() as u64);
+		let std_ms = |d: &StdDuration| Json::U64(d.as_secs() * 1000 + (d.subsec_nanos() / 1_000_000) as u64);
+		let number = |n: &Option<BlockNumber>| n.map_or(Json::Null, Json::U64);
+		let address = |a: &Address| Json::String(format!("0x{}", a.hex()));
+		let config: BTreeMap<String, Json> = map![
+			"gasLimitBoundDivisor".into() => Json::String(format!("0x{:x}", self.gas_limit_bound_divisor)),
+			"blockReward".into() => Json::String(format!("0x{:x}", self.block_reward)),
+			"blockRewardSchedule".into() => Json::Array(self.block_reward_schedule.iter().map(|&(from, ref reward)| {
+				let step: BTreeMap<String, Json> = map![
+					"from".into() => Json::U64(from),
+					"reward".into() => Json::String(format!("0x{:x}", reward))
+				];
+				Json::Object(step)
+			}).collect()),
+			"registrar".into() => address(&self.registrar),
+			"timeoutPropose".into() => ms(&self.timeouts.propose),
+			"timeoutPrevote".into() => ms(&self.timeouts.prevote),
+			"timeoutPrecommit".into() => ms(&self.timeouts.precommit),
+			"timeoutCommit".into() => ms(&self.timeouts.commit),
+			"viewChangeBackoff".into() => Json::U64(self.timeouts.view_change_backoff as u64),
+			"maxTimeout".into() => self.timeouts.max_timeout.as_ref().map_or(Json::Null, &ms),
+			"senderBlacklist".into() => Json::Array(self.sender_blacklist.iter().map(&address).collect()),
+			"primaryStrategy".into() => Json::String(match self.primary_strategy {
+				PrimaryStrategy::RoundRobin => "roundRobin".into(),
+				PrimaryStrategy::HashBased => "hashBased".into(),
+			}),
+			"weightedProposer".into() => Json::Boolean(self.weighted_proposer),
+			"commitGraceWindow".into() => self.commit_grace_window.as_ref().map_or(Json::Null, &std_ms),
+			"maxProposalDepth".into() => number(&self.max_proposal_depth),
+			"proposerVoteCounts".into() => Json::Boolean(self.proposer_vote_counts),
+			"rewardMaturity".into() => number(&self.reward_maturity),
+			"canonicalTransactionOrder".into() => Json::Boolean(self.canonical_transaction_order),
+			"requiredTransactions".into() => Json::Array(self.required_transactions.iter().map(|r| address(&r.to)).collect()),
+			"minimalCommitSeal".into() => Json::Boolean(self.seal_signatures == SealSignatures::Minimal),
+			"syncDistance".into() => self.sync_distance.map_or(Json::Null, |d| Json::U64(d as u64)),
+			"persistVotes".into() => Json::Boolean(self.persist_votes),
+			"maxMessagesPerSlice".into() => self.max_messages_per_slice.map_or(Json::Null, |m| Json::U64(m as u64)),
+			"maxView".into() => self.max_view.map_or(Json::Null, |v| Json::U64(v as u64)),
+			"allowedFutureTime".into() => Json::U64(self.allowed_future_time),
+			"observerOnly".into() => Json::Boolean(self.observer_only),
+			"messagesPerSecond".into() => self.messages_per_second.map_or(Json::Null, |m| Json::U64(m as u64)),
+			"maxRetainedViews".into() => self.max_retained_views.map_or(Json::Null, |v| Json::U64(v as u64)),
+			"maxRetainedMessages".into() => Json::U64(self.max_retained_messages as u64),
+			"eip160Transition".into() => Json::U64(self.eip160_transition),
+			"eip161abcTransition".into() => Json::U64(self.eip161abc_transition),
+			"eip161dTransition".into() => Json::U64(self.eip161d_transition),
+			"maxCodeSize".into() => Json::U64(self.max_code_size),
+			"signerKeys".into() => Json::Object(self.commit_rules.signer_keys.iter().map(|(key, validator)| (key.hex(), Json::String(validator.hex()))).collect()),
+			"broadcastJitter".into() => ms(&self.broadcast_jitter),
+			"quorumRatio".into() => Json::String(format!("{}/{}", self.commit_rules.quorum_ratio.numerator, self.commit_rules.quorum_ratio.denominator)),
+			"orderedSignersTransition".into() => Json::U64(self.commit_rules.ordered_signers_transition),
+			"emergencyProposers".into() => Json::Object(self.emergency_proposers.iter().map(|(height, proposer)| (height.to_string(), Json::String(proposer.hex()))).collect()),
+			"adoptPeerCommits".into() => Json::Boolean(self.adopt_peer_commits)
+		];
+		Json::Object(config)
+	}
+
+	/// Vote counts of the current view, for external monitoring.
+	/// Only reads atomics and the vote collector, sealing is not waited for.
+	pub fn consensus_metrics(&self) -> ConsensusMetrics {
+		let height = self.height.load(AtomicOrdering::SeqCst);
+		let view = self.view.load(AtomicOrdering::SeqCst);
+		let count = |step| self.votes.round_voters(&VoteStep::new(height, view, step)).len();
+		ConsensusMetrics {
+			height: height,
+			view: view,
+			proposals: count(Step::Propose),
+			prevotes: count(Step::Prevote),
+			precommits: count(Step::Precommit),
+			known_messages: self.votes.message_count(),
 		}
 	}
 
-	/// Broadcast all messages since last issued block to get the peers up to speed.
-	fn broadcast_old_messages(&self) {
-		for m in self.votes.get_up_to(&VoteStep::new(self.height.load(AtomicOrdering::SeqCst), self.view.load(AtomicOrdering::SeqCst), Step::Precommit)).into_iter() {
-			self.broadcast_message(m);
+	/// Current height, view and step, along with votes for the current proposal.
+	/// The state is read without waiting on sealing or step transitions, so fields may be from adjacent steps.
+	pub fn consensus_state(&self) -> ConsensusState {
+		let height = self.height.load(AtomicOrdering::SeqCst);
+		let view = self.view.load(AtomicOrdering::SeqCst);
+		let step = *self.step.read();
+		let parent = self.proposal_parent.read().clone();
+		let proposal = self.proposal.read().clone();
+		let proposal_votes = match proposal {
+			Some(_) => self.votes.round_voters(&VoteStep::new(height, view, step))
+				.into_iter()
+				.filter(|&(_, ref bh)| *bh == proposal)
+				.count(),
+			None => 0,
+		};
+		ConsensusState {
+			height: height,
+			view: view,
+			step: step,
+			is_proposer: self.is_signer_proposer(&parent),
+			proposal_votes: proposal_votes,
 		}
 	}
 
-	fn to_next_height(&self, height: Height) {
-		let new_height = height + 1;
-		debug!(target: "engine", "Received a Commit, transitioning to height {}.", new_height);
-		self.last_lock.store(0, AtomicOrdering::SeqCst);
-		self.height.store(new_height, AtomicOrdering::SeqCst);
-		self.view.store(0, AtomicOrdering::SeqCst);
-		*self.lock_change.write() = None;
-		*self.proposal.write() = None;
+	/// Blocks in `from..to` (inclusive) with a proposal or precommit signature of `address`.
+	/// At most `MAX_SIGNED_BLOCKS_RANGE` blocks are scanned.
+	pub fn validator_signed_blocks(&self, address: &Address, from: BlockNumber, to: BlockNumber) -> Vec<BlockNumber> {
+		use client::BlockChainClient;
+		let client = match self.client.read().as_ref().and_then(Weak::upgrade) {
+			Some(client) => client,
+			None => return Vec::new(),
+		};
+		let to = ::std::cmp::min(to, from.saturating_add(MAX_SIGNED_BLOCKS_RANGE - 1));
+		(from..to.saturating_add(1))
+			.filter_map(|n| client.block_header(BlockId::Number(n)))
+			.map(|header| header.decode())
+			.filter(|header| is_signed_by(header, self.epoch_at(header.number() as Height), &self.commit_rules, address))
+			.map(|header| header.number())
+			.collect()
 	}
 
-	/// Use via step_service to transition steps.
-	fn to_step(&self, step: Step) {
-		if let Err(io_err) = self.step_service.send_message(step) {
-			warn!(target: "engine", "Could not proceed to step {}.", io_err)
-		}
-		*self.step.write() = step;
-		match step {
-			Step::Propose => {
-				self.update_sealing()
-			},
-			Step::Prevote => {
-				let block_hash = match *self.lock_change.read() {
-					Some(ref m) if !self.should_unlock(m.vote_step.view) => m.block_hash,
-					_ => self.proposal.read().clone(),
-				};
-				self.generate_and_broadcast_message(block_hash);
-			},
-			Step::Precommit => {
-				trace!(target: "engine", "to_step: Precommit.");
-				let block_hash = match *self.lock_change.read() {
-					Some(ref m) if self.is_view(m) && m.block_hash.is_some() => {
-						trace!(target: "engine", "Setting last lock: {}", m.vote_step.view);
-						self.last_lock.store(m.vote_step.view, AtomicOrdering::SeqCst);
-						m.block_hash
-					},
-					_ => None,
-				};
-				self.generate_and_broadcast_message(block_hash);
-			},
-			Step::Commit => {
-				trace!(target: "engine", "to_step: Commit.");
-			},
-		}
+	/// Raw precommit messages reconstructed from the seal of the committed block `number`.
+	/// Empty if the block is unknown or not a commit.
+	pub fn commit_messages(&self, number: BlockNumber) -> Vec<Bytes> {
+		use client::BlockChainClient;
+		self.client.read().as_ref()
+			.and_then(Weak::upgrade)
+			.and_then(|c| c.block_header(BlockId::Number(number)))
+			.and_then(|header| commit_messages(&header.decode()).ok())
+			.unwrap_or_else(Vec::new)
 	}
 
-	fn is_authority(&self, address: &Address) -> bool {
-		self.validators.contains(&*self.proposal_parent.read(), address)
+	/// Run all engine verification stages on an RLP encoded block, using the imported parent.
+	/// Returns the stage which rejected the block on failure.
+	pub fn check_block(&self, block: &[u8]) -> Result<(), (VerificationStage, Error)> {
+		use client::BlockChainClient;
+		let header: Header = UntrustedRlp::new(block).val_at(0).map_err(|e| (VerificationStage::Basic, Error::from(e)))?;
+		self.verify_block_basic(&header, Some(block)).map_err(|e| (VerificationStage::Basic, e))?;
+		self.verify_block_unordered(&header, Some(block)).map_err(|e| (VerificationStage::Unordered, e))?;
+		let parent = self.client.read().as_ref()
+			.and_then(Weak::upgrade)
+			.and_then(|c| c.block_header(BlockId::Hash(*header.parent_hash())))
+			.map(|parent| parent.decode())
+			.ok_or_else(|| (VerificationStage::Family, Error::from(BlockError::UnknownParent(*header.parent_hash()))))?;
+		self.verify_block_family(&header, &parent, Some(block)).map_err(|e| (VerificationStage::Family, e))
 	}
 
-	fn check_above_threshold(&self, n: usize) -> Result<(), EngineError> {
-		let threshold = self.validators.count(&*self.proposal_parent.read()) * 2/3;
-		if n > threshold {
-			Ok(())
-		} else {
-			Err(EngineError::BadSealFieldSize(OutOfBounds {
-				min: Some(threshold),
-				max: None,
-				found: n
-			}))
+	/// Highest committed height, blocks up to it can not be reverted.
+	pub fn finalized_height(&self) -> Height {
+		self.finalized_height.load(AtomicOrdering::SeqCst)
+	}
+
+	/// Number of views in which this node was the proposer, but no proposal was made before the propose timeout.
+	pub fn missed_primary_slots(&self) -> usize {
+		self.missed_primary_slots.load(AtomicOrdering::SeqCst)
+	}
+
+	fn note_finalized(&self, height: Height) {
+		let mut current = self.finalized_height.load(AtomicOrdering::SeqCst);
+		while current < height {
+			let previous = self.finalized_height.compare_and_swap(current, height, AtomicOrdering::SeqCst);
+			if previous == current {
+				break;
+			}
+			current = previous;
 		}
 	}
 
-	/// Find the designated for the given view.
-	fn view_proposer(&self, bh: &H256, height: Height, view: View) -> Address {
-		let proposer_nonce = height + view;
-		trace!(target: "engine", "Proposer nonce: {}", proposer_nonce);
-		self.validators.get(bh, proposer_nonce)
+	/// Proof that the block `number` is committed, checked with `verify_finality_proof`.
+	/// None if the block is unknown or not a commit.
+	pub fn finality_proof(&self, number: BlockNumber) -> Option<Bytes> {
+		use client::BlockChainClient;
+		self.client.read().as_ref()
+			.and_then(Weak::upgrade)
+			.and_then(|c| c.block_header(BlockId::Number(number)))
+			.and_then(|header| finality_proof(&header.decode()).ok())
 	}
 
-	/// Check if address is a proposer for given view.
-	fn check_view_proposer(&self, bh: &H256, height: Height, view: View, address: &Address) -> Result<(), EngineError> {
-		let proposer = self.view_proposer(bh, height, view);
-		if proposer == *address {
-			Ok(())
-		} else {
-			Err(EngineError::NotProposer(Mismatch { expected: proposer, found: address.clone() }))
+	/// Whether the header is a commit signed by a quorum under the configured ratio of the validators at its parent.
+	/// Proposals carry no precommits and are never finalized.
+	pub fn is_finalized(&self, header: &Header) -> bool {
+		let seal = header.seal();
+		if seal.len() != 3 || seal[1] != ::rlp::NULL_RLP.to_vec() {
+			return false;
 		}
+		let epoch = self.epoch_at(header.number() as Height);
+		verify_precommits_with_rules(header, &*self.validators, epoch, &self.commit_rules, |precommit| self.votes.get(precommit))
+			.and_then(|weight| check_above_ratio(weight, self.validators.total_weight(header.parent_hash()) as usize, &self.commit_rules.quorum_ratio).map_err(Into::into))
+			.is_ok()
 	}
 
-	/// Check if current signer is the current proposer.
-	fn is_signer_proposer(&self, bh: &H256) -> bool {
-		let proposer = self.view_proposer(bh, self.height.load(AtomicOrdering::SeqCst), self.view.load(AtomicOrdering::SeqCst));
-		self.signer.is_address(&proposer)
+	/// Number of view changes needed to commit the last height, equal to its commit view.
+	pub fn last_commit_view_changes(&self) -> usize {
+		self.last_commit_view.load(AtomicOrdering::SeqCst)
 	}
 
-	fn is_height(&self, message: &ConsensusMessage) -> bool {
-		message.vote_step.is_height(self.height.load(AtomicOrdering::SeqCst))
+	/// Number of double votes seen from `address`.
+	pub fn double_votes(&self, address: &Address) -> usize {
+		self.double_votes.read().get(address).cloned().unwrap_or(0)
 	}
 
-	fn is_view(&self, message: &ConsensusMessage) -> bool {
-		message.vote_step.is_view(self.height.load(AtomicOrdering::SeqCst), self.view.load(AtomicOrdering::SeqCst))
+	/// Validators which double voted at least `DOUBLE_VOTE_ALERT_THRESHOLD` times.
+	pub fn equivocating_validators(&self) -> Vec<Address> {
+		self.double_votes.read().iter()
+			.filter(|&(_, count)| *count >= DOUBLE_VOTE_ALERT_THRESHOLD)
+			.map(|(address, _)| address.clone())
+			.collect()
 	}
 
-	fn increment_view(&self, n: View) {
-		trace!(target: "engine", "increment_view: New view.");
-		self.view.fetch_add(n, AtomicOrdering::SeqCst);
+	fn note_double_vote(&self, address: &Address) {
+		let mut double_votes = self.double_votes.write();
+		let count = double_votes.entry(address.clone()).or_insert(0);
+		*count += 1;
+		if *count == DOUBLE_VOTE_ALERT_THRESHOLD {
+			error!(target: "engine", "Validator {} double voted {} times.", address, count);
+		}
 	}
 
-	fn should_unlock(&self, lock_change_view: View) -> bool {
-		self.last_lock.load(AtomicOrdering::SeqCst) < lock_change_view
-			&& lock_change_view < self.view.load(AtomicOrdering::SeqCst)
+	/// Proofs of double votes by `offender`, each an RLP list of the two conflicting signed messages.
+	pub fn double_vote_proofs(&self, offender: &Address) -> Vec<Bytes> {
+		self.double_vote_proofs.read().iter()
+			.filter(|&(&(ref address, _), _)| address == offender)
+			.map(|(_, proof)| proof.clone())
+			.collect()
 	}
 
+	/// Store a proof of the double vote and report it to the validator set.
+	/// Only the first double vote of `offender` in a round is reported.
+	fn on_double_vote(&self, offender: &Address, first: &ConsensusMessage, second: &ConsensusMessage) {
+		let proof = {
+			let mut proofs = self.double_vote_proofs.write();
+			let key = (offender.clone(), first.vote_step.clone());
+			if proofs.contains_key(&key) {
+				return;
+			}
+			let mut s = RlpStream::new_list(2);
+			s.append(first).append(second);
+			let proof = s.out();
+			proofs.insert(key, proof.clone());
+			proof
+		};
+		self.validators.report_malicious(offender, first.vote_step.height as BlockNumber, proof);
+	}
 
-	fn has_enough_any_votes(&self) -> bool {
-		let step_votes = self.votes.count_round_votes(&VoteStep::new(self.height.load(AtomicOrdering::SeqCst), self.view.load(AtomicOrdering::SeqCst), *self.step.read()));
-		self.check_above_threshold(step_votes).is_ok()
+	/// Pass committed blocks imported from now on to `gadget`.
+	/// Blocks sealed by this node are pushed to the `CommitSink` instead.
+	pub fn set_finality_gadget(&self, gadget: Box<FinalityGadget>) {
+		*self.finality_gadget.write() = gadget;
 	}
 
-	fn has_enough_future_step_votes(&self, vote_step: &VoteStep) -> bool {
-		if vote_step.view > self.view.load(AtomicOrdering::SeqCst) {
-			let step_votes = self.votes.count_round_votes(vote_step);
-			self.check_above_threshold(step_votes).is_ok()
-		} else {
-			false
-		}
+	/// Number of times `address` proposed a block different from its earlier proposal at the same height.
+	pub fn height_equivocations(&self, address: &Address) -> usize {
+		self.height_equivocations.read().get(address).cloned().unwrap_or(0)
 	}
 
-	fn has_enough_aligned_votes(&self, message: &ConsensusMessage) -> bool {
-		let aligned_count = self.votes.count_aligned_votes(&message);
-		self.check_above_threshold(aligned_count).is_ok()
+	/// Flag proposers which propose different content in different views of a height.
+	/// A proposer rebuilds its block in a new view, so only the parent and the transactions are compared.
+	/// Proposals are not rejected, since without a lock a new view may legitimately get a new block.
+	fn note_height_proposal(&self, proposer: &Address, height: Height, header: &Header) {
+		let content = (height, header.parent_hash().clone(), header.transactions_root().clone());
+		match self.height_proposals.write().insert(proposer.clone(), content) {
+			Some((h, parent, transactions)) if h == height && (&parent, &transactions) != (header.parent_hash(), header.transactions_root()) => {
+				warn!(target: "engine", "Validator {} proposed {} with different content than before at height {}.", proposer, header.bare_hash(), height);
+				*self.height_equivocations.write().entry(proposer.clone()).or_insert(0) += 1;
+			},
+			_ => {},
+		}
 	}
 
-	fn handle_valid_message(&self, message: &ConsensusMessage) {
+	/// Remember the highest view of a vote for a future view of the current height.
+	/// A validator can push for one view change at a time, so only that vote counts towards it.
+	fn note_future_view(&self, sender: &Address, message: &ConsensusMessage) {
 		let ref vote_step = message.vote_step;
-		let is_newer_than_lock = match *self.lock_change.read() {
-			Some(ref lock) => vote_step > &lock.vote_step,
-			None => true,
-		};
-		let lock_change = is_newer_than_lock
-			&& vote_step.step == Step::Prevote
-			&& message.block_hash.is_some()
-			&& self.has_enough_aligned_votes(message);
-		if lock_change {
-			trace!(target: "engine", "handle_valid_message: Lock change.");
-			*self.lock_change.write() = Some(message.clone());
+		if !self.is_height(message) || vote_step.view <= self.view.load(AtomicOrdering::SeqCst) {
+			return;
 		}
-		// Check if it can affect the step transition.
-		if self.is_height(message) {
-			let next_step = match *self.step.read() {
-				Step::Precommit if message.block_hash.is_none() && self.has_enough_aligned_votes(message) => {
-					self.increment_view(1);
-					Some(Step::Propose)
-				},
-				Step::Precommit if self.has_enough_aligned_votes(message) => {
-					let bh = message.block_hash.expect("previous guard ensures is_some; qed");
-					if *self.last_proposed.read() == bh {
-						// Commit the block using a complete signature set.
-						// Generate seal and remove old votes.
-						let precommits = self.votes.round_signatures(vote_step, &bh);
-						trace!(target: "engine", "Collected seal: {:?}", precommits);
-						let seal = vec![
-							::rlp::encode(&vote_step.view).to_vec(),
-							::rlp::NULL_RLP.to_vec(),
-							::rlp::encode_list(&precommits).to_vec()
-						];
-						self.submit_seal(bh, seal);
-						self.votes.throw_out_old(&vote_step);
-					}
-					self.to_next_height(self.height.load(AtomicOrdering::SeqCst));
-					Some(Step::Commit)
-				},
-				Step::Precommit if self.has_enough_future_step_votes(&vote_step) => {
-					self.increment_view(vote_step.view - self.view.load(AtomicOrdering::SeqCst));
-					Some(Step::Precommit)
-				},
-				// Avoid counting votes twice.
-				Step::Prevote if lock_change => Some(Step::Precommit),
-				Step::Prevote if self.has_enough_aligned_votes(message) => Some(Step::Precommit),
-				Step::Prevote if self.has_enough_future_step_votes(&vote_step) => {
-					self.increment_view(vote_step.view - self.view.load(AtomicOrdering::SeqCst));
-					Some(Step::Prevote)
+		let mut future_views = self.future_views.write();
+		let highest = future_views.entry(sender.clone()).or_insert((vote_step.height, vote_step.view));
+		if highest.0 != vote_step.height || highest.1 < vote_step.view {
+			*highest = (vote_step.height, vote_step.view);
+		}
+	}
+
+	/// Insert the valid votes loaded from the vote store, skipping heights below the current one.
+	/// Needs the chain head and the epochs, so it runs once the client is registered.
+	/// Votes are restored in round order, so only the first vote of a validator in a round is restored
+	/// and no double vote is introduced. The lock is restored last, since it has to be a restored prevote.
+	fn restore_votes(&self) {
+		let stored = match self.stored_votes.lock().take() {
+			Some(stored) => stored,
+			None => return,
+		};
+		let height = self.height.load(AtomicOrdering::SeqCst);
+		let decode = |rlp: &Bytes| match UntrustedRlp::new(rlp).as_val::<ConsensusMessage>() {
+			Ok(message) => Some(message),
+			Err(e) => {
+				warn!(target: "engine", "Skipping a stored vote which could not be decoded: {}.", e);
+				None
+			},
+		};
+		let mut messages: Vec<_> = stored.votes.iter().filter_map(&decode).collect();
+		messages.sort_by(|a, b| a.vote_step.cmp(&b.vote_step));
+		let mut restored = 0;
+		for message in messages {
+			if message.vote_step.height < height || self.votes.is_old_or_known(&message) {
+				continue;
+			}
+			let sender = match message.verify(self.epoch_at(message.vote_step.height)) {
+				Ok(key) => self.validator_of(key),
+				Err(e) => {
+					warn!(target: "engine", "Skipping a stored vote with an invalid signature: {}.", e);
+					continue;
 				},
-				_ => None,
 			};
-
-			if let Some(step) = next_step {
-				trace!(target: "engine", "Transition to {:?} triggered.", step);
-				self.to_step(step);
+			if !self.is_height_authority(message.vote_step.height, &sender)
+				|| self.votes.round_voters(&message.vote_step).iter().any(|&(ref voter, _)| *voter == sender) {
+				continue;
+			}
+			self.votes.vote(message, &sender);
+			restored += 1;
+		}
+		debug!(target: "engine", "Restored {} stored votes.", restored);
+		if let Some(lock) = stored.lock.as_ref().and_then(decode) {
+			if lock.vote_step.height == height && lock.vote_step.step == Step::Prevote && self.votes.is_old_or_known(&lock) {
+				debug!(target: "engine", "Restored the lock on {:?} from {:?}.", lock.block_hash, lock.vote_step);
+				*self.lock_change.write() = Some(lock);
 			}
 		}
 	}
-}
-
-impl Engine for Tendermint {
-	fn name(&self) -> &str { "Tendermint" }
-
-	fn version(&self) -> SemanticVersion { SemanticVersion::new(1, 0, 0) }
-
-	/// (consensus view, proposal signature, authority signatures)
-	fn seal_fields(&self) -> usize { 3 }
-
-	fn params(&self) -> &CommonParams { &self.params }
 
-	fn additional_params(&self) -> HashMap<String, String> { hash_map!["registrar".to_owned() => self.registrar.hex()] }
+	/// Save a vote which was just added to the collector.
+	fn persist_vote(&self, message: &ConsensusMessage) {
+		self.vote_store.insert(message.vote_step.height, &::rlp::encode(message));
+	}
 
-	fn builtins(&self) -> &BTreeMap<Address, Builtin> { &self.builtins }
+	/// Push proofs of blocks committed by this node to `sink`.
+	pub fn set_commit_sink(&self, sink: Box<CommitSink>) {
+		*self.commit_sink.write() = sink;
+	}
 
-	fn maximum_uncle_count(&self) -> usize { 0 }
+	/// Stop or resume proposing blocks, while still voting on proposals of other validators.
+	pub fn set_proposing_enabled(&self, enabled: bool) {
+		debug!(target: "engine", "Proposing {}.", if enabled { "enabled" } else { "disabled" });
+		self.proposing_enabled.store(enabled, AtomicOrdering::SeqCst);
+	}
 
-	fn maximum_uncle_age(&self) -> usize { 0 }
+	/// Receive lifecycle events of the engine.
+	/// The current state is replayed first: `Started`, followed by `Participating` and `Stopped` if they apply.
+	pub fn subscribe_lifecycle(&self) -> mpsc::Receiver<LifecycleEvent> {
+		let (tx, rx) = mpsc::channel();
+		let mut subscribers = self.lifecycle_subscribers.lock();
+		let _ = tx.send(LifecycleEvent::Started);
+		let signer = self.signer.address();
+		if self.is_authority(&signer) {
+			let _ = tx.send(LifecycleEvent::Participating(signer));
+		}
+		if self.stopped.load(AtomicOrdering::SeqCst) {
+			let _ = tx.send(LifecycleEvent::Stopped);
+		}
+		subscribers.push(tx);
+		rx
+	}
 
-	/// Additional engine-specific information for the user/developer concerning `header`.
-	fn extra_info(&self, header: &Header) -> BTreeMap<String, String> {
-		let message = ConsensusMessage::new_proposal(header).expect("Invalid header.");
-		map![
-			"signature".into() => message.signature.to_string(),
-			"height".into() => message.vote_step.height.to_string(),
-			"view".into() => message.vote_step.view.to_string(),
-			"block_hash".into() => message.block_hash.as_ref().map(ToString::to_string).unwrap_or("".into())
-		]
+	fn notify_lifecycle(&self, event: LifecycleEvent) {
+		trace!(target: "engine", "Lifecycle event: {:?}.", event);
+		// Subscribers which hung up are dropped.
+		self.lifecycle_subscribers.lock().retain(|tx| tx.send(event.clone()).is_ok());
 	}
 
-	fn schedule(&self, block_number: BlockNumber) -> Schedule {
-		let eip86 = block_number >= self.params.eip86_transition;
-		Schedule::new_post_eip150(usize::max_value(), true, true, true, eip86)
+	/// Receive an event whenever a block reaches a quorum of precommits, before it is imported.
+	/// Events are dropped while the receiver holds `COMMIT_SUBSCRIPTION_CAPACITY` unread ones.
+	pub fn subscribe_commits(&self) -> mpsc::Receiver<CommittedBlock> {
+		let (tx, rx) = mpsc::sync_channel(COMMIT_SUBSCRIPTION_CAPACITY);
+		self.commit_subscribers.lock().push(tx);
+		rx
 	}
 
-	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, _gas_ceil_target: U256) {
-		// Chain scoring: total weight is sqrt(U256::max_value())*height - view
-		let new_difficulty = U256::from(U128::max_value()) + consensus_view(parent).expect("Header has been verified; qed").into() - self.view.load(AtomicOrdering::SeqCst).into();
-		header.set_difficulty(new_difficulty);
-		header.set_gas_limit({
-			let gas_limit = parent.gas_limit().clone();
-			let bound_divisor = self.gas_limit_bound_divisor;
-			if gas_limit < gas_floor_target {
-				min(gas_floor_target, gas_limit + gas_limit / bound_divisor - 1.into())
-			} else {
-				max(gas_floor_target, gas_limit - gas_limit / bound_divisor + 1.into())
-			}
+	fn notify_commit(&self, vote_step: &VoteStep, block_hash: H256) {
+		let parent = self.proposal_parent.read().clone();
+		let signers = self.votes.round_signers(vote_step, &block_hash).into_iter()
+			.map(|(signer, _)| signer)
+			.filter(|signer| self.validators.contains(&parent, signer))
+			.collect();
+		let event = CommittedBlock { height: vote_step.height, view: vote_step.view, block_hash: block_hash, signers: signers };
+		// Never wait for subscribers, those which hung up are dropped.
+		self.commit_subscribers.lock().retain(|tx| match tx.try_send(event.clone()) {
+			Err(mpsc::TrySendError::Disconnected(_)) => false,
+			Err(mpsc::TrySendError::Full(_)) => {
+				debug!(target: "engine", "Dropping commit event of height {}, subscriber is full.", event.height);
+				true
+			},
+			Ok(_) => true,
 		});
 	}
 
-	/// Should this node participate.
-	fn seals_internally(&self) -> Option<bool> {
-		Some(self.signer.address() != Address::default())
+	/// Error if the view limit of the current height is reached and timeouts no longer change the view.
+	/// Clears once the height advances.
+	pub fn view_limit_status(&self) -> Result<(), EngineError> {
+		let view = self.view.load(AtomicOrdering::SeqCst);
+		match self.max_view {
+			Some(max) if view >= max => Err(EngineError::ViewLimitReached(view)),
+			_ => Ok(()),
+		}
 	}
 
-	/// Attempt to seal generate a proposal seal.
-	fn generate_seal(&self, block: &ExecutedBlock) -> Seal {
-		let header = block.header();
-		let author = header.author();
-		// Only proposer can generate seal if None was generated.
-		if !self.is_signer_proposer(header.parent_hash()) || self.proposal.read().is_some() {
-			return Seal::None;
+	/// Take a place in the current message slice, if there is any left.
+	fn take_slice_slot(&self) -> bool {
+		match self.max_messages_per_slice {
+			Some(max) => self.slice_messages.fetch_add(1, AtomicOrdering::SeqCst) < max,
+			None => true,
 		}
+	}
 
-		let height = header.number() as Height;
-		let view = self.view.load(AtomicOrdering::SeqCst);
-		let bh = Some(header.bare_hash());
-		let vote_info = message_info_rlp(&VoteStep::new(height, view, Step::Propose), bh.clone());
-		if let Ok(signature) = self.signer.sign(vote_info.sha3()).map(Into::into) {
-			// Insert Propose vote.
-			debug!(target: "engine", "Submitting proposal {} at height {} view {}.", header.bare_hash(), height, view);
-			self.votes.vote(ConsensusMessage::new(signature, height, view, Step::Propose, bh), author);
-			// Remember the owned block.
-			*self.last_proposed.write() = header.bare_hash();
-			// Remember proposal for later seal submission.
-			*self.proposal.write() = bh;
-			*self.proposal_parent.write() = header.parent_hash().clone();
-			Seal::Proposal(vec![
-				::rlp::encode(&view).to_vec(),
-				::rlp::encode(&signature).to_vec(),
-				::rlp::EMPTY_LIST_RLP.to_vec()
-			])
+	/// Count a new message from `sender`, false if the sender already used up its rate for the current second.
+	fn within_rate_limit(&self, sender: &Address) -> bool {
+		let limit = match self.messages_per_second {
+			Some(limit) => limit,
+			None => return true,
+		};
+		let now = Instant::now();
+		let mut rates = self.message_rates.lock();
+		let rate = rates.entry(*sender).or_insert((now, 0));
+		if now.duration_since(rate.0) >= StdDuration::from_secs(1) {
+			*rate = (now, 0);
+		}
+		rate.1 += 1;
+		rate.1 <= limit
+	}
+
+	/// Queue a message of the validator `sender` which did not fit into the current slice.
+	fn defer_message(&self, message: ConsensusMessage, rlp: &[u8], sender: Address) {
+		let mut deferred = self.deferred_messages.lock();
+		if deferred.len() < MAX_DEFERRED_MESSAGES {
+			deferred.push_back((message, rlp.to_vec(), sender));
 		} else {
-			warn!(target: "engine", "generate_seal: FAIL: accounts secret key unavailable");
-			Seal::None
+			debug!(target: "engine", "Dropping a consensus message, {} are waiting already.", deferred.len());
 		}
 	}
 
-	fn handle_message(&self, rlp: &[u8]) -> Result<(), Error> {
-		let rlp = UntrustedRlp::new(rlp);
-		let message: ConsensusMessage = rlp.as_val()?;
-		if !self.votes.is_old_or_known(&message) {
-			let sender = public_to_address(&recover(&message.signature.into(), &rlp.at(1)?.as_raw().sha3())?);
-			if !self.is_authority(&sender) {
-				return Err(EngineError::NotAuthorized(sender).into());
+	/// Start a new slice and process queued messages up to its limit.
+	fn next_message_slice(&self) {
+		let max = self.max_messages_per_slice.unwrap_or(usize::max_value());
+		self.slice_messages.store(0, AtomicOrdering::SeqCst);
+		// Messages queued again while handling wait for the next slice.
+		let queued = self.deferred_messages.lock().len();
+		for _ in 0..queued {
+			if self.slice_messages.load(AtomicOrdering::SeqCst) >= max {
+				break;
 			}
-			self.broadcast_message(rlp.as_raw().to_vec());
-			if let Some(double) = self.votes.vote(message.clone(), &sender) {
-				self.validators.report_malicious(&sender, message.vote_step.height as BlockNumber, ::rlp::encode(&double).to_vec());
-				return Err(EngineError::DoubleVote(sender).into());
+			// Do not hold the queue while handling, handling may queue again.
+			let next = self.deferred_messages.lock().pop_front();
+			match next {
+				Some((message, rlp, sender)) => {
+					if self.votes.is_old_or_known(&message) {
+						continue;
+					}
+					let _span = RoundSpan::enter(message.vote_step.height, message.vote_step.view);
+					self.slice_messages.fetch_add(1, AtomicOrdering::SeqCst);
+					if let Err(e) = self.apply_message(&message, &rlp, &sender) {
+						trace!(target: "engine", "Queued message is invalid: {}.", e);
+					}
+				},
+				None => break,
 			}
-			trace!(target: "engine", "Handling a valid {:?} from {}.", message, sender);
-			self.handle_valid_message(&message);
 		}
-		Ok(())
 	}
 
-	/// Apply the block reward on finalisation of the block.
-	fn on_close_block(&self, block: &mut ExecutedBlock) -> Result<(), Error>{
-		let fields = block.fields_mut();
-		// Bestow block reward
-		let res = fields.state.add_balance(fields.header.author(), &self.block_reward, CleanupMode::NoEmpty)
-			.map_err(::error::Error::from)
-			.and_then(|_| fields.state.commit());
-		// Commit state so that we can actually figure out the state root.
-		if let Err(ref e) = res {
-			warn!("Encountered error on closing block: {}", e);
+	/// Count and apply a new `message` of the validator `sender`, and pass it on to peers.
+	fn apply_message(&self, message: &ConsensusMessage, rlp: &[u8], sender: &Address) -> Result<(), Error> {
+		// Only validators are tracked, which bounds the memory used.
+		// Votes are not sent again, so messages over the rate are retried in a later slice.
+		if !self.within_rate_limit(sender) {
+			debug!(target: "engine", "Deferring a consensus message from {}, over its rate.", sender);
+			self.defer_message(message.clone(), rlp, *sender);
+			return Ok(());
 		}
-		res
+		self.metrics.messages_in.increment(message.vote_step.step);
+		self.runtime.note_message();
+		self.note_voted_height(sender, message.vote_step.height);
+		self.broadcast_message(rlp.to_vec());
+		if let Some(double) = self.votes.vote(message.clone(), sender) {
+			self.note_double_vote(sender);
+			self.on_double_vote(sender, &double.vote_one, &double.vote_two);
+			return Err(EngineError::DoubleVote(*sender).into());
+		}
+		self.throw_out_over_cap();
+		self.note_future_view(sender, message);
+		phase_log!(self, message.vote_step.step, trace, "Handling a valid {:?} from {}.", message, sender);
+		self.handle_valid_message(message);
+		self.submit_pending_seal(false);
+		Ok(())
 	}
 
-	fn verify_block_basic(&self, header: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
-		let seal_length = header.seal().len();
-		if seal_length == self.seal_fields() {
-			// Either proposal or commit.
-			if (header.seal()[1] == ::rlp::NULL_RLP.to_vec())
-				!= (header.seal()[2] == ::rlp::EMPTY_LIST_RLP.to_vec()) {
-				Ok(())
-			} else {
-				warn!(target: "engine", "verify_block_basic: Block is neither a Commit nor Proposal.");
-				Err(BlockError::InvalidSeal.into())
-			}
-		} else {
-			Err(BlockError::InvalidSealArity(
-				Mismatch { expected: self.seal_fields(), found: seal_length }
-			).into())
-		}
+	/// Only log the given phases from now on.
+	pub fn set_log_phases(&self, phases: &[Phase]) {
+		self.log_phases.set(phases);
 	}
 
-	fn verify_block_unordered(&self, _header: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
-		Ok(())
+	/// Number of consensus messages sent and received, by message type.
+	pub fn message_metrics(&self) -> &MessageMetrics {
+		&self.metrics
 	}
 
-	/// Verify validators and gas limit.
-	fn verify_block_family(&self, header: &Header, parent: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
-		if header.number() == 0 {
-			return Err(BlockError::RidiculousNumber(OutOfBounds { min: Some(1), max: None, found: header.number() }).into());
+	/// Uptime and counts of handled messages, proposals and commits.
+	pub fn runtime_stats(&self) -> RuntimeStats {
+		self.runtime.stats()
+	}
+
+	/// Average and longest time spent in recent views before a commit or a view change.
+	pub fn view_latency_stats(&self) -> ViewLatencyStats {
+		self.view_latency.stats()
+	}
+
+	/// Check if this node is close enough to the network head to originate consensus messages.
+	/// Without votes of other validators the network height is unknown and the node counts as synced.
+	fn is_synced(&self) -> bool {
+		let distance = match self.sync_distance {
+			Some(distance) => distance,
+			None => return true,
+		};
+		match self.network_height() {
+			Some(network) => network <= self.height.load(AtomicOrdering::SeqCst) + distance,
+			None => true,
 		}
+	}
 
-		if let Ok(proposal) = ConsensusMessage::new_proposal(header) {
-			let proposer = proposal.verify()?;
-			if !self.is_authority(&proposer) {
-				return Err(EngineError::NotAuthorized(proposer).into());
-			}
-			self.check_view_proposer(header.parent_hash(), proposal.vote_step.height, proposal.vote_step.view, &proposer)?;
-		} else {
-			let vote_step = VoteStep::new(header.number() as usize, consensus_view(header)?, Step::Precommit);
-			let precommit_hash = message_hash(vote_step.clone(), header.bare_hash());
-			let ref signatures_field = header.seal().get(2).expect("block went through verify_block_basic; block has .seal_fields() fields; qed");
-			let mut origins = HashSet::new();
-			for rlp in UntrustedRlp::new(signatures_field).iter() {
-				let precommit = ConsensusMessage {
-					signature: rlp.as_val()?,
-					block_hash: Some(header.bare_hash()),
-					vote_step: vote_step.clone(),
-				};
-				let address = match self.votes.get(&precommit) {
-					Some(a) => a,
-					None => public_to_address(&recover(&precommit.signature.into(), &precommit_hash)?),
-				};
-				if !self.validators.contains(header.parent_hash(), &address) {
-					return Err(EngineError::NotAuthorized(address.to_owned()).into());
-				}
+	/// Remember that the validator `sender` voted at `height`.
+	fn note_voted_height(&self, sender: &Address, height: Height) {
+		let mut heights = self.voted_heights.write();
+		let voted = heights.entry(*sender).or_insert(height);
+		*voted = max(*voted, height);
+	}
 
-				if !origins.insert(address) {
-					warn!(target: "engine", "verify_block_unordered: Duplicate signature from {} on the seal.", address);
-					return Err(BlockError::InvalidSeal.into());
-				}
-			}
+	/// Highest height which more validators voted at than can be faulty, so a single validator can not fake it.
+	fn network_height(&self) -> Option<Height> {
+		let validators = self.validators.count(&*self.proposal_parent.read());
+		let faulty = validators.saturating_sub(1) / 3;
+		let mut heights: Vec<Height> = self.voted_heights.read().values().cloned().collect();
+		heights.sort_by(|a, b| b.cmp(a));
+		heights.get(faulty).cloned()
+	}
 
-			self.check_above_threshold(origins.len())?
+	/// Check if the chain head already has a block at the engine height, which means that the engine did not follow a commit yet.
+	fn is_behind_chain_head(&self) -> bool {
+		use client::BlockChainClient;
+		match self.client.read().as_ref().and_then(Weak::upgrade) {
+			Some(client) => client.chain_info().best_block_number >= self.height.load(AtomicOrdering::SeqCst) as BlockNumber,
+			None => false,
 		}
+	}
 
-		let gas_limit_divisor = self.gas_limit_bound_divisor;
-		let min_gas = parent.gas_limit().clone() - parent.gas_limit().clone() / gas_limit_divisor;
-		let max_gas = parent.gas_limit().clone() + parent.gas_limit().clone() / gas_limit_divisor;
-		if header.gas_limit() <= &min_gas || header.gas_limit() >= &max_gas {
-			self.validators.report_malicious(header.author(), header.number(), Default::default());
-			return Err(BlockError::InvalidGasLimit(OutOfBounds { min: Some(min_gas), max: Some(max_gas), found: header.gas_limit().clone() }).into());
+	fn generate_message(&self, block_hash: Option<BlockHash>) -> Option<Bytes> {
+		if self.observer_only {
+			return None;
+		}
+		if !self.is_synced() {
+			trace!(target: "engine", "Not signing a message while syncing.");
+			return None;
+		}
+		let h = self.height.load(AtomicOrdering::SeqCst);
+		let r = self.view.load(AtomicOrdering::SeqCst);
+		let s = *self.step.read();
+		let vote_step = VoteStep::new(h, r, s);
+		let validator = self.signer.address();
+		// A vote restored after a restart may be for a different block.
+		if self.persist_votes && self.votes.round_voters(&vote_step).iter().any(|&(ref voter, ref bh)| *voter == validator && *bh != block_hash) {
+			warn!(target: "engine", "Not signing a conflicting vote in {:?}.", vote_step);
+			return None;
 		}
+		let vote_info = message_info_rlp(&vote_step, block_hash);
+		match self.signer.sign(vote_hash(self.epoch_at(h), &vote_info)).map(Into::into) {
+			Ok(signature) => {
+				let message_rlp = message_full_rlp(&signature, &vote_info);
+				let message = ConsensusMessage::new(signature, h, r, s, block_hash);
+				self.votes.vote(message.clone(), &validator);
+				phase_log!(self, s, debug, "Generated {:?} as {}.", message, validator);
+				self.handle_valid_message(&message);
 
-		Ok(())
+				Some(message_rlp)
+			},
+			Err(e) => {
+				trace!(target: "engine", "Could not sign the message {}", e);
+				None
+			},
+		}
 	}
 
-	fn set_signer(&self, ap: Arc<AccountProvider>, address: Address, password: String) {
-		{
-			self.signer.set(ap, address, password);
+	fn generate_and_broadcast_message(&self, block_hash: Option<BlockHash>) {
+		if let Some(message) = self.generate_message(block_hash) {
+			self.broadcast_message(message);
 		}
-		self.to_step(Step::Propose);
 	}
 
-	fn sign(&self, hash: H256) -> Result<Signature, Error> {
-		self.signer.sign(hash).map_err(Into::into)
+	/// Broadcast messages since last issued block to get the peers up to speed.
+	/// Each message is sent once per height, and at most `MAX_REBROADCAST_BYTES` are sent per call.
+	fn broadcast_old_messages(&self) {
+		if self.observer_only {
+			return;
+		}
+		let round = VoteStep::new(self.height.load(AtomicOrdering::SeqCst), self.view.load(AtomicOrdering::SeqCst), Step::Precommit);
+		let mut sent = 0;
+		for m in self.votes.get_up_to(&round).into_iter() {
+			if sent + m.len() > MAX_REBROADCAST_BYTES {
+				break;
+			}
+			if self.rebroadcast.lock().insert(m.sha3()) {
+				sent += m.len();
+				self.broadcast_message(m);
+			}
+		}
 	}
 
-	fn stop(&self) {
-		self.step_service.stop()
+	fn to_next_height(&self, height: Height) {
+		let new_height = height + 1;
+		phase_log!(self, Phase::Commit, debug, "Received a Commit, transitioning to height {}.", new_height);
+		self.view_latency.end_view();
+		self.last_lock.store(0, AtomicOrdering::SeqCst);
+		self.height.store(new_height, AtomicOrdering::SeqCst);
+		self.view.store(0, AtomicOrdering::SeqCst);
+		*self.lock_change.write() = None;
+		*self.proposal.write() = None;
+		self.rebroadcast.lock().clear();
+		self.vote_store.set_lock(None);
+		self.vote_store.prune(new_height);
 	}
 
-	fn is_proposal(&self, header: &Header) -> bool {
-		let signatures_len = header.seal()[2].len();
-		// Signatures have to be an empty list rlp.
-		if signatures_len != 1 {
-			// New Commit received, skip to next height.
-			trace!(target: "engine", "Received a commit: {:?}.", header.number());
-			self.to_next_height(header.number() as usize);
-			self.to_step(Step::Commit);
-			return false;
+	/// Catch up with a run of consecutive committed blocks in one pass.
+	/// The run has to start at the current height and extend the last known commit.
+	/// All seals are verified before the height is advanced past the last block.
+	pub fn fast_forward(&self, headers: &[Header]) -> Result<(), Error> {
+		let (first, last) = match (headers.first(), headers.last()) {
+			(Some(first), Some(last)) => (first, last.number() as Height),
+			_ => return Ok(()),
+		};
+		let height = self.height.load(AtomicOrdering::SeqCst);
+		if first.number() as Height != height {
+			return Err(BlockError::InvalidNumber(Mismatch { expected: height as BlockNumber, found: first.number() }).into());
 		}
-		let proposal = ConsensusMessage::new_proposal(header).expect("block went through full verification; this Engine verifies new_proposal creation; qed");
-		let proposer = proposal.verify().expect("block went through full verification; this Engine tries verify; qed");
-		debug!(target: "engine", "Received a new proposal {:?} from {}.", proposal.vote_step, proposer);
-		if self.is_view(&proposal) {
-			*self.proposal.write() = proposal.block_hash.clone();
-			*self.proposal_parent.write() = header.parent_hash().clone();
+		if let Some((committed, hash)) = *self.last_commit.read() {
+			if committed + 1 == height && *first.parent_hash() != hash {
+				return Err(BlockError::InvalidParentHash(Mismatch { expected: hash, found: first.parent_hash().clone() }).into());
+			}
 		}
-		self.votes.vote(proposal, &proposer);
-		true
+		let mut parent: Option<&Header> = None;
+		for header in headers {
+			if let Some(parent) = parent {
+				if header.number() != parent.number() + 1 {
+					return Err(BlockError::InvalidNumber(Mismatch { expected: parent.number() + 1, found: header.number() }).into());
+				}
+				if *header.parent_hash() != parent.hash() {
+					return Err(BlockError::InvalidParentHash(Mismatch { expected: parent.hash(), found: header.parent_hash().clone() }).into());
+				}
+			}
+			verify_commit(header, &*self.validators, self.epoch_at(header.number() as Height), &self.commit_rules)?;
+			parent = Some(header);
+		}
+		phase_log!(self, Phase::Commit, debug, "Fast forwarding over {} committed blocks.", headers.len());
+		let last_view = headers.last().map_or(Ok(0), consensus_view)?;
+		self.last_commit_view.store(last_view, AtomicOrdering::SeqCst);
+		*self.last_commit.write() = headers.last().map(|header| (last, header.hash()));
+		self.note_finalized(last);
+		self.to_next_height(last);
+		self.to_step(Step::Commit);
+		Ok(())
 	}
 
-	/// Equivalent to a timeout: to be used for tests.
-	fn step(&self) {
-		let next_step = match *self.step.read() {
+	/// Whether the last step transition armed a timeout.
+	/// When false the engine only moves on when messages arrive.
+	pub fn step_timer_healthy(&self) -> bool {
+		self.step_timer_healthy.load(AtomicOrdering::SeqCst)
+	}
+
+	/// Deliver a step transition with `send`, retrying a few times.
+	/// The handler stays registered, a failed transition is recovered by the next message or timeout.
+	fn send_step<F>(&self, send: F) -> Result<(), IoError> where F: FnMut() -> Result<(), IoError> {
+		let result = retry_io(send);
+		self.step_timer_healthy.store(result.is_ok(), AtomicOrdering::SeqCst);
+		result
+	}
+
+	/// Use via step_service to transition steps.
+	fn to_step(&self, step: Step) {
+		if let Err(io_err) = self.send_step(|| self.step_service.send_message(step)) {
+			warn!(target: "engine", "Could not proceed to step {}, step timer is broken.", io_err)
+		}
+		*self.step.write() = step;
+		match step {
 			Step::Propose => {
-				trace!(target: "engine", "Propose timeout.");
-				if self.proposal.read().is_none() {
-					// Report the proposer if no proposal was received.
-					let height = self.height.load(AtomicOrdering::SeqCst);
-					let current_proposer = self.view_proposer(&*self.proposal_parent.read(), height, self.view.load(AtomicOrdering::SeqCst));
-					self.validators.report_benign(&current_proposer, height as BlockNumber);
-				}
-				Step::Prevote
-			},
-			Step::Prevote if self.has_enough_any_votes() => {
-				trace!(target: "engine", "Prevote timeout.");
-				Step::Precommit
+				self.update_sealing()
 			},
 			Step::Prevote => {
-				trace!(target: "engine", "Prevote timeout without enough votes.");
-				self.broadcast_old_messages();
-				Step::Prevote
-			},
-			Step::Precommit if self.has_enough_any_votes() => {
-				trace!(target: "engine", "Precommit timeout.");
-				self.increment_view(1);
-				Step::Propose
+				let block_hash = match *self.lock_change.read() {
+					Some(ref m) if !self.should_unlock(m.vote_step.view) => m.block_hash,
+					_ => self.proposal.read().clone(),
+				};
+				self.generate_and_broadcast_message(block_hash);
 			},
 			Step::Precommit => {
-				trace!(target: "engine", "Precommit timeout without enough votes.");
-				self.broadcast_old_messages();
-				Step::Precommit
+				phase_log!(self, Phase::Precommit, trace, "to_step: Precommit.");
+				let block_hash = match *self.lock_change.read() {
+					Some(ref m) if self.is_view(m) && m.block_hash.is_some() => {
+						phase_log!(self, Phase::Precommit, trace, "Setting last lock: {}", m.vote_step.view);
+						self.last_lock.store(m.vote_step.view, AtomicOrdering::SeqCst);
+						m.block_hash
+					},
+					_ => None,
+				};
+				self.generate_and_broadcast_message(block_hash);
 			},
 			Step::Commit => {
-				trace!(target: "engine", "Commit timeout.");
-				Step::Propose
+				phase_log!(self, Phase::Commit, trace, "to_step: Commit.");
 			},
+		}
+	}
+
+	fn is_authority(&self, address: &Address) -> bool {
+		self.validators.contains(&*self.proposal_parent.read(), address)
+	}
+
+	/// Check if `address` proposed or voted in the current view.
+	fn has_voted_in_view(&self, address: &Address) -> bool {
+		let height = self.height.load(AtomicOrdering::SeqCst);
+		let view = self.view.load(AtomicOrdering::SeqCst);
+		[Step::Propose, Step::Prevote, Step::Precommit].iter()
+			.any(|step| self.votes.round_voters(&VoteStep::new(height, view, *step)).iter().any(|&(ref voter, _)| voter == address))
+	}
+
+	/// Check if address is a validator for the block at the given height.
+	/// Heights which are not imported yet use the current validators.
+	fn is_height_authority(&self, height: Height, address: &Address) -> bool {
+		use client::BlockChainClient;
+		let parent = if height < self.height.load(AtomicOrdering::SeqCst) {
+			self.client.read().as_ref()
+				.and_then(Weak::upgrade)
+				.and_then(|c| c.block_hash(BlockId::Number((height as BlockNumber).saturating_sub(1))))
+		} else {
+			None
 		};
-		self.to_step(next_step);
+		match parent {
+			Some(parent) => self.validators.contains(&parent, address),
+			None => self.is_authority(address),
+		}
 	}
 
-	fn register_client(&self, client: Weak<Client>) {
+	/// Epoch in which votes at `height` are cast.
+	fn epoch_at(&self, height: Height) -> Epoch {
+		self.epochs.read().iter().rev().find(|&(first, _)| *first <= height).map_or(0, |(_, epoch)| *epoch)
+	}
+
+	/// Reload the vote epochs from the epoch transitions the client stored for the canonical chain.
+	/// Transitions are stored on import, once the block and receipts are known, and survive restarts.
+	/// Votes after a transition block are cast in its epoch.
+	fn reload_epochs(&self) {
+		let transitions = match self.client.read().as_ref().and_then(Weak::upgrade) {
+			Some(client) => client.epoch_transitions(),
+			None => return,
+		};
+		let epochs: BTreeMap<Height, Epoch> = transitions.into_iter()
+			.map(|(epoch, number)| (number as Height + 1, epoch))
+			.collect();
+		if *self.epochs.read() != epochs {
+			phase_log!(self, Phase::Commit, debug, "Vote epochs by first height: {:?}.", epochs);
+			*self.epochs.write() = epochs;
+		}
+	}
+
+	fn check_above_threshold(&self, weight: usize) -> Result<(), EngineError> {
+		check_above_ratio(weight, self.validators.total_weight(&*self.proposal_parent.read()) as usize, &self.commit_rules.quorum_ratio)
+	}
+
+	/// Vote weight of `voter` as a validator for the child of `parent`.
+	/// Zero if `voter` is not in the set at `parent`, so votes collected before a removal do not count.
+	fn vote_weight(&self, parent: &H256, voter: &Address) -> usize {
+		self.validators.weight(parent, voter) as usize
+	}
+
+	/// Find the designated for the given view.
+	fn view_proposer(&self, bh: &H256, height: Height, view: View) -> Address {
+		// Later views rotate, in case the emergency proposer is offline as well.
+		if view == 0 {
+			if let Some(proposer) = self.emergency_proposers.get(&height) {
+				trace!(target: "engine", "Emergency proposer {} overrides the proposer at height {}.", proposer, height);
+				return *proposer;
+			}
+		}
+		// Contract based sets may call into the client every lookup.
+		// The parent hash determines the validator set, so set changes miss the cache.
+		if let Some((ref c_bh, c_height, c_view, proposer)) = *self.proposer_cache.read() {
+			if c_bh == bh && c_height == height && c_view == view {
+				return proposer;
+			}
+		}
+		let proposer = self.lookup_view_proposer(bh, height, view);
+		*self.proposer_cache.write() = Some((bh.clone(), height, view, proposer));
+		proposer
+	}
+
+	/// Proposer of `height` and `view` in the validator set as of the parent block `bh`.
+	/// Sets reduce the nonce by their own size at `bh`, so the proposer does not depend on later set changes.
+	fn lookup_view_proposer(&self, bh: &H256, height: Height, view: View) -> Address {
+		let proposer_nonce = match self.primary_strategy {
+			PrimaryStrategy::HashBased => seeded_proposer_nonce(bh, view),
+			PrimaryStrategy::RoundRobin => height + view,
+		};
+		trace!(target: "engine", "Proposer nonce: {}", proposer_nonce);
+		if self.weighted_proposer {
+			self.weighted_validator(bh, proposer_nonce)
+		} else {
+			self.validators.get(bh, proposer_nonce)
+		}
+	}
+
+	/// Validator whose range of cumulative weights contains `nonce` modulo the total weight.
+	fn weighted_validator(&self, bh: &H256, nonce: usize) -> Address {
+		let total = self.validators.total_weight(bh);
+		if total == 0 {
+			return self.validators.get(bh, nonce);
+		}
+		let mut position = nonce as u64 % total;
+		for i in 0..self.validators.count(bh) {
+			let validator = self.validators.get(bh, i);
+			let weight = self.validators.weight(bh, &validator);
+			if position < weight {
+				return validator;
+			}
+			position -= weight;
+		}
+		self.validators.get(bh, nonce)
+	}
+
+	/// Check if address is a proposer for given view.
+	fn check_view_proposer(&self, bh: &H256, height: Height, view: View, address: &Address) -> Result<(), EngineError> {
+		let proposer = self.view_proposer(bh, height, view);
+		if proposer == *address {
+			Ok(())
+		} else {
+			Err(EngineError::NotProposer(Mismatch { expected: proposer, found: address.clone() }))
+		}
+	}
+
+	/// Check if a block with the same bare hash is already committed at its height.
+	fn is_committed(&self, header: &Header) -> bool {
 		use client::BlockChainClient;
-		if let Some(c) = client.upgrade() {
-			self.height.store(c.chain_info().best_block_number as usize + 1, AtomicOrdering::SeqCst);
+		self.client.read().as_ref()
+			.and_then(Weak::upgrade)
+			.and_then(|c| c.block_header(BlockId::Number(header.number())))
+			.map_or(false, |committed| committed.decode().bare_hash() == header.bare_hash())
+	}
+
+	/// Check that a proposal does not reorganise more than `max_proposal_depth` blocks.
+	fn check_proposal_depth(&self, header: &Header) -> Result<(), BlockError> {
+		use client::BlockChainClient;
+		let depth = match self.max_proposal_depth {
+			Some(depth) => depth,
+			None => return Ok(()),
+		};
+		let best = match self.client.read().as_ref().and_then(Weak::upgrade) {
+			Some(c) => c.chain_info().best_block_number,
+			None => return Ok(()),
+		};
+		let min = (best + 1).saturating_sub(depth);
+		if header.number() < min {
+			warn!(target: "engine", "verify_block_family: Proposal {} is built {} blocks below the best block.", header.bare_hash(), best + 1 - header.number());
+			return Err(BlockError::RidiculousNumber(OutOfBounds { min: Some(min), max: None, found: header.number() }));
 		}
-		*self.client.write() = Some(client.clone());
-		self.validators.register_contract(client);
+		Ok(())
 	}
-}
 
-#[cfg(test)]
-mod tests {
-	use util::*;
-	use block::*;
-	use error::{Error, BlockError};
-	use header::Header;
-	use client::chain_notify::ChainNotify;
-	use miner::MinerService;
-	use tests::helpers::*;
-	use account_provider::AccountProvider;
-	use spec::Spec;
-	use engines::{Engine, EngineError, Seal};
-	use super::*;
+	/// Validator address which a recovered signing `key` stands for.
+	fn validator_of(&self, key: Address) -> Address {
+		self.commit_rules.validator_of(key)
+	}
 
-	/// Accounts inserted with "0" and "1" are validators. First proposer is "0".
-	fn setup() -> (Spec, Arc<AccountProvider>) {
-		let tap = Arc::new(AccountProvider::transient_provider());
-		let spec = Spec::new_test_tendermint();
-		(spec, tap)
+	/// Add a key of the signer account provider, to sign with when the signer account is unavailable.
+	/// The key has to be listed in `signerKeys` for other validators to accept its votes.
+	pub fn add_backup_signer(&self, address: Address, password: String) {
+		if self.commit_rules.signer_keys.get(&address) != Some(&self.signer.address()) {
+			warn!(target: "engine", "Backup signer {} is not a signer key of {}.", address, self.signer.address());
+		}
+		self.signer.add_backup(address, password);
 	}
 
-	fn propose_default(spec: &Spec, proposer: Address) -> (ClosedBlock, Vec<Bytes>) {
-		let db = get_temp_state_db();
-		let db = spec.ensure_db_good(db, &Default::default()).unwrap();
-		let genesis_header = spec.genesis_header();
-		let last_hashes = Arc::new(vec![genesis_header.hash()]);
-		let b = OpenBlock::new(spec.engine.as_ref(), Default::default(), false, db.boxed_clone(), &genesis_header, last_hashes, proposer, (3141562.into(), 31415620.into()), vec![]).unwrap();
-		let b = b.close();
-		if let Seal::Proposal(seal) = spec.engine.generate_seal(b.block()) {
-			(b, seal)
+	/// Sign with the engine signer, provided it is still `expected`.
+	fn sign_as(&self, expected: &Address, hash: H256) -> Option<H520> {
+		let signature = match self.signer.sign(hash) {
+			Ok(signature) => signature,
+			Err(e) => {
+				warn!(target: "engine", "generate_seal: FAIL: accounts secret key unavailable: {:?}", e);
+				return None;
+			},
+		};
+		match recover(&signature, &hash).map(|public| public_to_address(&public)) {
+			Ok(address) if self.validator_of(address) == *expected => Some(signature.into()),
+			_ => {
+				warn!(target: "engine", "generate_seal: FAIL: signer changed from {} while sealing.", expected);
+				None
+			},
+		}
+	}
+
+	/// Check if current signer is the current proposer.
+	fn is_signer_proposer(&self, bh: &H256) -> bool {
+		let proposer = self.view_proposer(bh, self.height.load(AtomicOrdering::SeqCst), self.view.load(AtomicOrdering::SeqCst));
+		self.signer.is_address(&proposer)
+	}
+
+	/// Check that a proposal for the height after the last known commit builds on that commit.
+	fn builds_on_last_commit(&self, header: &Header) -> bool {
+		match *self.last_commit.read() {
+			Some((height, ref hash)) if header.number() as Height == height + 1 => header.parent_hash() == hash,
+			_ => true,
+		}
+	}
+
+	fn is_height(&self, message: &ConsensusMessage) -> bool {
+		message.vote_step.is_height(self.height.load(AtomicOrdering::SeqCst))
+	}
+
+	fn is_view(&self, message: &ConsensusMessage) -> bool {
+		message.vote_step.is_view(self.height.load(AtomicOrdering::SeqCst), self.view.load(AtomicOrdering::SeqCst))
+	}
+
+	/// Random delay of the rebroadcast on a `step` timeout, at most `broadcast_jitter`.
+	/// The delay is kept under half of the timeout, so the rebroadcast goes out before the next one.
+	fn broadcast_delay<R: Rng>(&self, rng: &mut R, step: &Step) -> StdDuration {
+		let timeout = self.timeouts.view_timeout(step, self.view.load(AtomicOrdering::SeqCst));
+		let max_ms = min(self.broadcast_jitter, timeout / 2).num_milliseconds();
+		if max_ms <= 0 {
+			return StdDuration::from_millis(0);
+		}
+		StdDuration::from_millis(rng.gen_range(0, max_ms as u64 + 1))
+	}
+
+	/// Rebroadcast on a timeout, spread out from the validators timing out at the same time.
+	fn rebroadcast_after_jitter(&self, step: &Step) {
+		let delay = self.broadcast_delay(&mut thread_rng(), step);
+		self.schedule_rebroadcast(delay);
+	}
+
+	/// Rebroadcast after `delay`, without blocking the step transition.
+	fn schedule_rebroadcast(&self, delay: StdDuration) {
+		if delay > StdDuration::from_millis(0) {
+			*self.rebroadcast_delay.lock() = Some(delay);
 		} else {
-			panic!()
+			self.broadcast_old_messages();
 		}
 	}
 
-	fn vote<F>(engine: &Engine, signer: F, height: usize, view: usize, step: Step, block_hash: Option<H256>) -> Bytes where F: FnOnce(H256) -> Result<H520, ::account_provider::SignError> {
-		let mi = message_info_rlp(&VoteStep::new(height, view, step), block_hash);
-		let m = message_full_rlp(&signer(mi.sha3()).unwrap().into(), &mi);
-		engine.handle_message(&m).unwrap();
-		m
+	/// Whether a proposal of the current view should replace the one already received.
+	/// Of conflicting proposals the lowest block hash wins, so that nodes receiving them in any order agree.
+	/// Once the view left the propose step the proposal has been prevoted and stays.
+	fn prefers_proposal(&self, proposal: &ConsensusMessage) -> bool {
+		match *self.proposal.read() {
+			None => true,
+			Some(ref current) if Some(*current) == proposal.block_hash => false,
+			Some(ref current) => {
+				let replace = *self.step.read() == Step::Propose && proposal.block_hash.map_or(false, |hash| hash < *current);
+				warn!(target: "engine", "Conflicting proposals {} and {:?} at {:?}, {} the latter.", current, proposal.block_hash, proposal.vote_step, if replace { "choosing" } else { "ignoring" });
+				replace
+			},
+		}
 	}
 
-	fn proposal_seal(tap: &Arc<AccountProvider>, header: &Header, view: View) -> Vec<Bytes> {
-		let author = header.author();
-		let vote_info = message_info_rlp(&VoteStep::new(header.number() as Height, view, Step::Propose), Some(header.bare_hash()));
-		let signature = tap.sign(*author, None, vote_info.sha3()).unwrap();
-		vec![
-			::rlp::encode(&view).to_vec(),
-			::rlp::encode(&H520::from(signature)).to_vec(),
-			::rlp::EMPTY_LIST_RLP.to_vec()
-		]
+	fn increment_view(&self, n: View) {
+		phase_log!(self, Phase::NewView, trace, "increment_view: New view.");
+		self.view_latency.end_view();
+		self.view.fetch_add(n, AtomicOrdering::SeqCst);
+		self.throw_out_past_views();
 	}
 
-	fn insert_and_unlock(tap: &Arc<AccountProvider>, acc: &str) -> Address {
-		let addr = tap.insert_account(acc.sha3().into(), acc).unwrap();
-		tap.unlock_account_permanently(addr, acc.into()).unwrap();
-		addr
+	/// Drop votes of views which can no longer commit, never those of the current view or later.
+	fn throw_out_past_views(&self) {
+		let height = self.height.load(AtomicOrdering::SeqCst);
+		let view = self.view.load(AtomicOrdering::SeqCst);
+		if let Some(retained) = self.max_retained_views {
+			if view > retained {
+				// Commit messages are not gossiped, so the marker round holds no votes.
+				self.votes.throw_out_up_to(&VoteStep::new(height, view - retained - 1, Step::Commit));
+			}
+		}
+		self.throw_out_over_cap();
 	}
 
-	fn insert_and_register(tap: &Arc<AccountProvider>, engine: &Engine, acc: &str) -> Address {
-		let addr = insert_and_unlock(tap, acc);
-		engine.set_signer(tap.clone(), addr.clone(), acc.into());
-		addr
+	/// Drop the oldest votes of past views while more than `max_retained_messages` are kept.
+	/// Checked on every received vote, whether or not `max_retained_views` is set.
+	fn throw_out_over_cap(&self) {
+		let height = self.height.load(AtomicOrdering::SeqCst);
+		let view = self.view.load(AtomicOrdering::SeqCst);
+		let removed = self.votes.throw_out_oldest(&VoteStep::new(height, view, Step::Propose), self.max_retained_messages);
+		if removed > 0 {
+			debug!(target: "engine", "Dropped {} votes of past views at height {}.", removed, height);
+		}
+	}
+
+	fn should_unlock(&self, lock_change_view: View) -> bool {
+		self.last_lock.load(AtomicOrdering::SeqCst) < lock_change_view
+			&& lock_change_view < self.view.load(AtomicOrdering::SeqCst)
+	}
+
+
+	fn has_enough_any_votes(&self) -> bool {
+		let parent = self.proposal_parent.read().clone();
+		let step_votes = self.votes.round_votes_weight(&VoteStep::new(self.height.load(AtomicOrdering::SeqCst), self.view.load(AtomicOrdering::SeqCst), *self.step.read()), |voter| self.vote_weight(&parent, voter));
+		self.check_above_threshold(step_votes).is_ok()
+	}
+
+	fn has_enough_future_step_votes(&self, vote_step: &VoteStep) -> bool {
+		if vote_step.view > self.view.load(AtomicOrdering::SeqCst) {
+			let parent = self.proposal_parent.read().clone();
+			let future_views = self.future_views.read();
+			// Votes of validators which already voted in a higher view do not count.
+			let step_votes: usize = self.votes.round_voters(vote_step)
+				.into_iter()
+				.filter(|&(ref voter, _)| future_views.get(voter).map_or(true, |&(h, v)| h != vote_step.height || v <= vote_step.view))
+				.map(|(voter, _)| self.vote_weight(&parent, &voter))
+				.sum();
+			self.check_above_threshold(step_votes).is_ok()
+		} else {
+			false
+		}
+	}
+
+	/// Weight of prevotes for the block of `message` implied by proposals of proposers which did not prevote themselves.
+	fn implied_prevotes(&self, parent: &H256, message: &ConsensusMessage) -> usize {
+		if !self.proposer_vote_counts || message.vote_step.step != Step::Prevote || message.block_hash.is_none() {
+			return 0;
+		}
+		let prevoters = self.votes.round_voters(&message.vote_step);
+		let propose_step = VoteStep::new(message.vote_step.height, message.vote_step.view, Step::Propose);
+		self.votes.round_voters(&propose_step)
+			.into_iter()
+			// A proposer which prevoted, for any block, is counted by its prevote only.
+			.filter(|&(ref proposer, ref bh)| *bh == message.block_hash && prevoters.iter().all(|&(ref voter, _)| voter != proposer))
+			.map(|(proposer, _)| self.vote_weight(parent, &proposer))
+			.sum()
+	}
+
+	fn has_enough_aligned_votes(&self, message: &ConsensusMessage) -> bool {
+		let parent = self.proposal_parent.read().clone();
+		let aligned_count = self.votes.aligned_votes_weight(&message, |voter| self.vote_weight(&parent, voter)) + self.implied_prevotes(&parent, message);
+		self.check_above_threshold(aligned_count).is_ok()
+	}
+
+	fn handle_valid_message(&self, message: &ConsensusMessage) {
+		let _span = RoundSpan::enter(message.vote_step.height, message.vote_step.view);
+		self.persist_vote(message);
+		let ref vote_step = message.vote_step;
+		let is_newer_than_lock = match *self.lock_change.read() {
+			Some(ref lock) => vote_step > &lock.vote_step,
+			None => true,
+		};
+		let lock_change = is_newer_than_lock
+			&& vote_step.step == Step::Prevote
+			&& message.block_hash.is_some()
+			&& self.has_enough_aligned_votes(message);
+		if lock_change {
+			phase_log!(self, Phase::Prevote, trace, "handle_valid_message: Lock change.");
+			*self.lock_change.write() = Some(message.clone());
+			self.vote_store.set_lock(Some(&::rlp::encode(message)));
+		}
+		// Check if it can affect the step transition.
+		if self.is_height(message) {
+			let next_step = match *self.step.read() {
+				Step::Precommit if message.block_hash.is_none() && self.has_enough_aligned_votes(message) => {
+					self.increment_view(1);
+					Some(Step::Propose)
+				},
+				Step::Precommit if self.has_enough_aligned_votes(message) => {
+					let bh = message.block_hash.expect("previous guard ensures is_some; qed");
+					if *self.last_proposed.read() == bh {
+						// Commit the block using a complete signature set.
+						match self.commit_grace_window {
+							Some(window) => {
+								phase_log!(self, Phase::Commit, trace, "Waiting {:?} for more precommits.", window);
+								*self.pending_seal.lock() = Some(PendingSeal {
+									vote_step: vote_step.clone(),
+									block_hash: bh,
+									parent: self.proposal_parent.read().clone(),
+									deadline: Instant::now() + window,
+									armed: false,
+								});
+							},
+							None => self.submit_commit(vote_step, bh, &self.proposal_parent.read().clone()),
+						}
+					}
+					self.notify_commit(vote_step, bh);
+					self.last_commit_view.store(vote_step.view, AtomicOrdering::SeqCst);
+					// The hash of the sealed block is not known until it is imported.
+					*self.last_commit.write() = None;
+					self.note_finalized(vote_step.height);
+					self.runtime.note_commit();
+					self.to_next_height(self.height.load(AtomicOrdering::SeqCst));
+					Some(Step::Commit)
+				},
+				Step::Precommit if self.has_enough_future_step_votes(&vote_step) => {
+					self.increment_view(vote_step.view - self.view.load(AtomicOrdering::SeqCst));
+					Some(Step::Precommit)
+				},
+				// Avoid counting votes twice.
+				Step::Prevote if lock_change => Some(Step::Precommit),
+				Step::Prevote if self.has_enough_aligned_votes(message) => Some(Step::Precommit),
+				Step::Prevote if self.has_enough_future_step_votes(&vote_step) => {
+					self.increment_view(vote_step.view - self.view.load(AtomicOrdering::SeqCst));
+					Some(Step::Prevote)
+				},
+				_ => None,
+			};
+
+			if let Some(step) = next_step {
+				trace!(target: "engine", "Transition to {:?} triggered.", step);
+				self.to_step(step);
+			}
+		}
+	}
+}
+
+impl ConsensusIntrospection for Tendermint {
+	fn consensus_state(&self) -> ConsensusState {
+		Tendermint::consensus_state(self)
+	}
+
+	fn set_log_phases(&self, phases: &[Phase]) {
+		Tendermint::set_log_phases(self, phases)
+	}
+
+	fn view_latency_stats(&self) -> ViewLatencyStats {
+		Tendermint::view_latency_stats(self)
+	}
+
+	fn validator_signed_blocks(&self, address: &Address, from: BlockNumber, to: BlockNumber) -> Vec<BlockNumber> {
+		Tendermint::validator_signed_blocks(self, address, from, to)
+	}
+
+	fn check_block(&self, block: &[u8]) -> Result<(), (VerificationStage, Error)> {
+		Tendermint::check_block(self, block)
+	}
+
+	fn equivocating_validators(&self) -> Vec<Address> {
+		Tendermint::equivocating_validators(self)
+	}
+
+	fn last_commit_view_changes(&self) -> usize {
+		Tendermint::last_commit_view_changes(self)
+	}
+
+	fn seal_messages(&self, header: &Header) -> Option<Vec<Bytes>> {
+		commit_messages(header).ok()
+	}
+
+	fn runtime_stats(&self) -> RuntimeStats {
+		Tendermint::runtime_stats(self)
+	}
+
+	fn effective_config(&self) -> Json {
+		Tendermint::effective_config(self)
+	}
+}
+
+impl Engine for Tendermint {
+	fn name(&self) -> &str { "Tendermint" }
+
+	fn version(&self) -> SemanticVersion { SemanticVersion::new(1, 0, 0) }
+
+	/// (consensus view, proposal signature, authority signatures)
+	fn seal_fields(&self) -> usize { 3 }
+
+	fn params(&self) -> &CommonParams { &self.params }
+
+	fn additional_params(&self) -> HashMap<String, String> { hash_map!["registrar".to_owned() => self.registrar.hex()] }
+
+	fn builtins(&self) -> &BTreeMap<Address, Builtin> { &self.builtins }
+
+	fn maximum_uncle_count(&self) -> usize { 0 }
+
+	fn maximum_uncle_age(&self) -> usize { 0 }
+
+	/// Additional engine-specific information for the user/developer concerning `header`.
+	fn extra_info(&self, header: &Header) -> BTreeMap<String, String> {
+		let seal_length = header.seal().len();
+		if seal_length != self.seal_fields() {
+			return map!["error".into() => format!("Seal has {} fields, expected {}.", seal_length, self.seal_fields())];
+		}
+		let message = match ConsensusMessage::new_proposal(header) {
+			Ok(message) => message,
+			Err(e) => return map!["error".into() => format!("Seal is not a valid proposal: {}", e)],
+		};
+		let mut info = map![
+			"signature".into() => message.signature.to_string(),
+			"height".into() => message.vote_step.height.to_string(),
+			"view".into() => message.vote_step.view.to_string(),
+			"block_hash".into() => message.block_hash.as_ref().map(ToString::to_string).unwrap_or("".into())
+		];
+		match message.verify(self.epoch_at(message.vote_step.height)) {
+			Ok(proposer) => info.insert("proposer".into(), format!("0x{}", self.validator_of(proposer).hex())),
+			Err(e) => info.insert("error".into(), format!("Proposal signer can not be recovered: {}", e)),
+		};
+		info
+	}
+
+	fn consensus(&self) -> Option<&ConsensusIntrospection> {
+		Some(self)
+	}
+
+	fn allowed_future_block_time(&self) -> u64 {
+		self.allowed_future_time
+	}
+
+	fn schedule(&self, block_number: BlockNumber) -> Schedule {
+		Schedule::new_post_eip150(
+			self.max_code_size as usize,
+			block_number >= self.eip160_transition,
+			block_number >= self.eip161abc_transition,
+			block_number >= self.eip161d_transition,
+			block_number >= self.params.eip86_transition
+		)
+	}
+
+	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, _gas_ceil_target: U256) {
+		// Chain scoring: total weight is sqrt(U256::max_value())*height - view
+		let new_difficulty = U256::from(U128::max_value()) + consensus_view(parent).expect("Header has been verified; qed").into() - self.view.load(AtomicOrdering::SeqCst).into();
+		header.set_difficulty(new_difficulty);
+		header.set_gas_limit({
+			let gas_limit = parent.gas_limit().clone();
+			let bound_divisor = self.gas_limit_bound_divisor;
+			let target = if gas_limit < gas_floor_target {
+				min(gas_floor_target, gas_limit + gas_limit / bound_divisor - 1.into())
+			} else {
+				max(gas_floor_target, gas_limit - gas_limit / bound_divisor + 1.into())
+			};
+			// Never propose a block the family verification would reject.
+			max(self.params.min_gas_limit, target)
+		});
+	}
+
+	/// Should this node participate.
+	fn seals_internally(&self) -> Option<bool> {
+		Some(!self.observer_only && self.signer.address() != Address::default())
+	}
+
+	/// Attempt to seal generate a proposal seal.
+	fn generate_seal(&self, block: &ExecutedBlock) -> Seal {
+		if self.observer_only || self.stopped.load(AtomicOrdering::SeqCst) {
+			return Seal::None;
+		}
+		let header = block.header();
+		let _span = RoundSpan::enter(header.number() as Height, self.view.load(AtomicOrdering::SeqCst));
+		let author = header.author();
+		// Signer may be replaced concurrently, stick to one identity.
+		let signer = self.signer.address();
+		// The author is paid the block reward, other validators reject proposals authored by someone else.
+		if *author != signer {
+			phase_log!(self, Phase::Propose, debug, "Skipping proposal at height {}, author {} is not the signer {}.", header.number(), author, signer);
+			return Seal::None;
+		}
+		// Only proposer can generate seal if None was generated.
+		if !self.is_signer_proposer(header.parent_hash()) || self.proposal.read().is_some() {
+			return Seal::None;
+		}
+		if !self.proposing_enabled.load(AtomicOrdering::SeqCst) {
+			phase_log!(self, Phase::Propose, debug, "Skipping own proposal at height {}, proposing is disabled.", header.number());
+			return Seal::None;
+		}
+		if !self.is_synced() {
+			phase_log!(self, Phase::Propose, debug, "Skipping own proposal at height {}, still syncing.", header.number());
+			return Seal::None;
+		}
+		if self.is_behind_chain_head() {
+			phase_log!(self, Phase::Propose, debug, "Skipping own proposal at height {}, the chain is already past it.", header.number());
+			return Seal::None;
+		}
+
+		let height = header.number() as Height;
+		let view = self.view.load(AtomicOrdering::SeqCst);
+		let bh = Some(header.bare_hash());
+		let vote_info = message_info_rlp(&VoteStep::new(height, view, Step::Propose), bh.clone());
+		if let Some(signature) = self.sign_as(&signer, vote_hash(self.epoch_at(height), &vote_info)) {
+			// Insert Propose vote.
+			phase_log!(self, Phase::Propose, debug, "Submitting proposal {} at height {} view {}.", header.bare_hash(), height, view);
+			self.metrics.messages_out.increment(Step::Propose);
+			self.votes.vote(ConsensusMessage::new(signature, height, view, Step::Propose, bh), &signer);
+			// Remember the owned block.
+			*self.last_proposed.write() = header.bare_hash();
+			// Remember proposal for later seal submission.
+			*self.proposal.write() = bh;
+			*self.proposal_parent.write() = header.parent_hash().clone();
+			Seal::Proposal(vec![
+				::rlp::encode(&view).to_vec(),
+				::rlp::encode(&signature).to_vec(),
+				::rlp::EMPTY_LIST_RLP.to_vec()
+			])
+		} else {
+			Seal::None
+		}
+	}
+
+	fn handle_message(&self, rlp: &[u8]) -> Result<(), Error> {
+		if self.stopped.load(AtomicOrdering::SeqCst) {
+			return Ok(());
+		}
+		let rlp = UntrustedRlp::new(rlp);
+		let message: ConsensusMessage = rlp.as_val()?;
+		let _span = RoundSpan::enter(message.vote_step.height, message.vote_step.view);
+		if !self.votes.is_old_or_known(&message) {
+			let epoch = self.epoch_at(message.vote_step.height);
+			let sender = self.validator_of(public_to_address(&recover(&message.signature.into(), &vote_hash(epoch, rlp.at(1)?.as_raw()))?));
+			// Validators can change between epochs, check against the set of the message height.
+			if !self.is_height_authority(message.vote_step.height, &sender) {
+				return Err(EngineError::NotAuthorized(sender).into());
+			}
+			// Only validators use up the slice and the queue.
+			if !self.take_slice_slot() {
+				self.defer_message(message, rlp.as_raw(), sender);
+				return Ok(());
+			}
+			self.apply_message(&message, rlp.as_raw(), &sender)?;
+		}
+		Ok(())
+	}
+
+	/// Apply the block reward on finalisation of the block.
+	fn on_close_block(&self, block: &mut ExecutedBlock) -> Result<(), Error>{
+		let fields = block.fields_mut();
+		// Bestow block reward
+		let number = fields.header.number();
+		let res = match self.reward_maturity {
+			// Without issuance there is nothing to hold, keep the state untouched.
+			Some(_) if self.issues_no_rewards() => return Ok(()),
+			Some(maturity) => credit_matured_reward(fields.state, fields.header.author(), number, maturity, &self.block_reward_at(number.saturating_sub(maturity))),
+			None => {
+				let reward = self.block_reward_at(number);
+				if reward.is_zero() {
+					return Ok(());
+				}
+				fields.state.add_balance(fields.header.author(), &reward, CleanupMode::NoEmpty)
+			},
+		}
+			.map_err(::error::Error::from)
+			.and_then(|_| fields.state.commit());
+		// Commit state so that we can actually figure out the state root.
+		if let Err(ref e) = res {
+			warn!("Encountered error on closing block: {}", e);
+		}
+		res
+	}
+
+	fn verify_block_basic(&self, header: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
+		let seal_length = header.seal().len();
+		if seal_length == self.seal_fields() {
+			let seal = header.seal();
+			UntrustedRlp::new(&seal[0]).as_val::<View>()
+				.map_err(|e| EngineError::BadSealView(format!("{}", e)))?;
+			if seal[1] != ::rlp::NULL_RLP.to_vec() {
+				UntrustedRlp::new(&seal[1]).as_val::<H520>()
+					.map_err(|e| EngineError::BadProposalSignature(format!("{}", e)))?;
+			}
+			let precommits = UntrustedRlp::new(&seal[2]);
+			precommits.item_count().and_then(|_| precommits.as_list::<H520>())
+				.map_err(|e| EngineError::BadPrecommits(format!("{}", e)))?;
+			// Either proposal or commit.
+			if (seal[1] == ::rlp::NULL_RLP.to_vec())
+				!= (seal[2] == ::rlp::EMPTY_LIST_RLP.to_vec()) {
+				Ok(())
+			} else {
+				warn!(target: "engine", "verify_block_basic: Block is neither a Commit nor Proposal.");
+				Err(BlockError::InvalidSeal.into())
+			}
+		} else {
+			Err(BlockError::InvalidSealArity(
+				Mismatch { expected: self.seal_fields(), found: seal_length }
+			).into())
+		}
+	}
+
+	/// Check that no transaction in the block comes from a blacklisted sender
+	/// and that transactions are ordered if required.
+	fn verify_block_unordered(&self, header: &Header, block: Option<&[u8]>) -> Result<(), Error> {
+		if let (true, Some(block)) = (self.canonical_transaction_order, block) {
+			let transactions = BlockView::new(block).transactions();
+			for pair in transactions.windows(2) {
+				if canonical_order_key(&pair[0]) >= canonical_order_key(&pair[1]) {
+					warn!(target: "engine", "verify_block_unordered: Block {} has transaction {} out of order.", header.number(), pair[1].hash());
+					return Err(BlockError::TransactionOutOfOrder(pair[1].hash()).into());
+				}
+			}
+		}
+		if let (false, Some(block)) = (self.sender_blacklist.is_empty(), block) {
+			for t in BlockView::new(block).transactions() {
+				let sender = SignedTransaction::new(t)?.sender();
+				if self.sender_blacklist.contains(&sender) {
+					warn!(target: "engine", "verify_block_unordered: Block {} contains a transaction from blacklisted {}.", header.number(), sender);
+					return Err(TransactionError::SenderBanned.into());
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Verify validators, gas limit and required transactions.
+	fn verify_block_family(&self, header: &Header, parent: &Header, block: Option<&[u8]>) -> Result<(), Error> {
+		if header.number() == 0 {
+			return Err(BlockError::RidiculousNumber(OutOfBounds { min: Some(1), max: None, found: header.number() }).into());
+		}
+
+		// Also checked by the client, repeated before any signature is recovered.
+		if header.gas_used() > header.gas_limit() {
+			return Err(BlockError::TooMuchGasUsed(OutOfBounds { min: None, max: Some(header.gas_limit().clone()), found: header.gas_used().clone() }).into());
+		}
+
+		if let (false, Some(block)) = (self.required_transactions.is_empty(), block) {
+			let transactions = BlockView::new(block).transactions();
+			if let Some(missing) = self.required_transactions.iter().find(|r| !transactions.iter().any(|t| r.is_satisfied_by(t))) {
+				warn!(target: "engine", "verify_block_family: Block {} lacks a required call to {}.", header.number(), missing.to);
+				return Err(BlockError::MissingRequiredTransaction(missing.to).into());
+			}
+		}
+
+		if let Ok(proposal) = ConsensusMessage::new_proposal(header) {
+			let proposer = self.validator_of(proposal.verify(self.epoch_at(proposal.vote_step.height))?);
+			if !self.is_authority(&proposer) {
+				return Err(EngineError::NotAuthorized(proposer).into());
+			}
+			// The author is paid the block reward.
+			if *header.author() != proposer {
+				warn!(target: "engine", "verify_block_family: Proposal {} signed by {} names {} as the author.", header.bare_hash(), proposer, header.author());
+				return Err(EngineError::AuthorMismatch(Mismatch { expected: proposer, found: header.author().clone() }).into());
+			}
+			self.check_view_proposer(header.parent_hash(), proposal.vote_step.height, proposal.vote_step.view, &proposer)?;
+			if self.is_committed(header) {
+				warn!(target: "engine", "verify_block_family: {} re-proposed committed block {}.", proposer, header.bare_hash());
+				return Err(BlockError::InvalidSeal.into());
+			}
+			self.check_proposal_depth(header)?;
+		} else {
+			let epoch = self.epoch_at(header.number() as Height);
+			let weight = verify_precommits_with_rules(header, &*self.validators, epoch, &self.commit_rules, |precommit| self.votes.get(precommit))?;
+			self.check_above_threshold(weight)?;
+		}
+
+		let gas_limit_divisor = self.gas_limit_bound_divisor;
+		let min_gas = parent.gas_limit().clone() - parent.gas_limit().clone() / gas_limit_divisor;
+		let max_gas = parent.gas_limit().clone() + parent.gas_limit().clone() / gas_limit_divisor;
+		if header.gas_limit() <= &min_gas || header.gas_limit() >= &max_gas {
+			self.validators.report_malicious(header.author(), header.number(), Default::default());
+			return Err(BlockError::InvalidGasLimit(OutOfBounds { min: Some(min_gas), max: Some(max_gas), found: header.gas_limit().clone() }).into());
+		}
+		if header.gas_limit() < &self.params.min_gas_limit {
+			return Err(BlockError::InvalidGasLimit(OutOfBounds { min: Some(self.params.min_gas_limit), max: None, found: header.gas_limit().clone() }).into());
+		}
+
+		Ok(())
+	}
+
+	fn set_signer(&self, ap: Arc<AccountProvider>, address: Address, password: String) {
+		let previous = self.signer.address();
+		{
+			self.signer.set(ap, address, password);
+		}
+		if previous != address && self.has_voted_in_view(&previous) {
+			// Votes of both keys in one round could be taken for equivocation.
+			let view = self.view.load(AtomicOrdering::SeqCst);
+			trace!(target: "engine", "Signer changed from {} to {} after voting, skipping view {}.", previous, address, view);
+			*self.proposal.write() = None;
+			self.increment_view(1);
+		}
+		if self.is_authority(&address) {
+			self.notify_lifecycle(LifecycleEvent::Participating(address));
+		}
+		self.to_step(Step::Propose);
+	}
+
+	fn sign(&self, hash: H256) -> Result<Signature, Error> {
+		self.signer.sign(hash).map_err(Into::into)
+	}
+
+	/// Send the votes of the current view once more, then stop proposing, voting and handling messages.
+	fn stop(&self) {
+		if !self.stopped.load(AtomicOrdering::SeqCst) {
+			// Peers get the latest state before the node leaves.
+			self.broadcast_old_messages();
+		}
+		self.stopped.store(true, AtomicOrdering::SeqCst);
+		self.step_service.stop();
+		self.notify_lifecycle(LifecycleEvent::Stopped);
+	}
+
+	fn is_proposal(&self, header: &Header) -> bool {
+		let signatures_len = header.seal()[2].len();
+		// Signatures have to be an empty list rlp.
+		if signatures_len != 1 {
+			match commit_messages(header) {
+				Ok(proof) => self.finality_gadget.read().on_finalized(header, &proof),
+				Err(e) => warn!(target: "engine", "is_proposal: Could not decode precommits of {}: {}", header.number(), e),
+			}
+			let height = header.number() as Height;
+			if height < self.height.load(AtomicOrdering::SeqCst) {
+				// Already committed locally or adopted from another peer.
+				phase_log!(self, Phase::Commit, trace, "Received an old commit: {:?}.", height);
+				// Commits sealed by this node become known once they are imported.
+				if height + 1 == self.height.load(AtomicOrdering::SeqCst) {
+					*self.last_commit.write() = Some((height, header.hash()));
+				}
+				return false;
+			}
+			if !self.adopt_peer_commits {
+				phase_log!(self, Phase::Commit, trace, "Leaving the commit of current height {:?} to local votes.", height);
+				return false;
+			}
+			// New Commit received, skip to next height.
+			// Local votes for this height no longer count, so no redundant seal is produced.
+			phase_log!(self, Phase::Commit, trace, "Received a commit: {:?}.", height);
+			if let Ok(view) = consensus_view(header) {
+				self.last_commit_view.store(view, AtomicOrdering::SeqCst);
+			}
+			*self.last_commit.write() = Some((height, header.hash()));
+			self.note_finalized(height);
+			self.runtime.note_commit();
+			self.to_next_height(height);
+			self.to_step(Step::Commit);
+			return false;
+		}
+		let proposal = ConsensusMessage::new_proposal(header).expect("block went through full verification; this Engine verifies new_proposal creation; qed");
+		let proposer = self.validator_of(proposal.verify(self.epoch_at(proposal.vote_step.height)).expect("block went through full verification; this Engine tries verify; qed"));
+		phase_log!(self, Phase::Propose, debug, "Received a new proposal {:?} from {}.", proposal.vote_step, proposer);
+		self.metrics.messages_in.increment(Step::Propose);
+		self.runtime.note_proposal();
+		if !self.builds_on_last_commit(header) {
+			warn!(target: "engine", "is_proposal: Ignoring proposal {} from {} which does not build on the last commit.", header.bare_hash(), proposer);
+			return true;
+		}
+		if self.is_view(&proposal) && self.prefers_proposal(&proposal) {
+			*self.proposal.write() = proposal.block_hash.clone();
+			*self.proposal_parent.write() = header.parent_hash().clone();
+		}
+		self.note_height_proposal(&proposer, proposal.vote_step.height, header);
+		self.persist_vote(&proposal);
+		self.votes.vote(proposal, &proposer);
+		self.throw_out_over_cap();
+		true
+	}
+
+	/// Equivalent to a timeout: to be used for tests.
+	fn step(&self) {
+		let _span = RoundSpan::enter(self.height.load(AtomicOrdering::SeqCst), self.view.load(AtomicOrdering::SeqCst));
+		// Seals whose grace window has passed, in case their timer was not set.
+		self.submit_pending_seal(false);
+		let next_step = match *self.step.read() {
+			Step::Propose => {
+				phase_log!(self, Phase::Propose, trace, "Propose timeout.");
+				if self.proposal.read().is_none() {
+					// Report the proposer if no proposal was received.
+					let height = self.height.load(AtomicOrdering::SeqCst);
+					let view = self.view.load(AtomicOrdering::SeqCst);
+					let current_proposer = self.view_proposer(&*self.proposal_parent.read(), height, view);
+					// Own proposals are recorded as the proposal too, so the slot was not used.
+					if self.signer.is_address(&current_proposer) {
+						self.missed_primary_slots.fetch_add(1, AtomicOrdering::SeqCst);
+						warn!(target: "engine", "Missed own proposer slot at height {} view {}, no block was sealed before the timeout.", height, view);
+					}
+					self.validators.report_benign(&current_proposer, height as BlockNumber);
+				}
+				Step::Prevote
+			},
+			Step::Prevote if self.has_enough_any_votes() => {
+				phase_log!(self, Phase::Prevote, trace, "Prevote timeout.");
+				Step::Precommit
+			},
+			Step::Prevote => {
+				phase_log!(self, Phase::Prevote, trace, "Prevote timeout without enough votes.");
+				self.rebroadcast_after_jitter(&Step::Prevote);
+				Step::Prevote
+			},
+			Step::Precommit if self.has_enough_any_votes() => {
+				phase_log!(self, Phase::Precommit, trace, "Precommit timeout.");
+				match self.view_limit_status() {
+					Err(e) => {
+						// Stay in the view and keep the timer running, votes of peers or a new block can still move the engine.
+						error!(target: "engine", "Not changing the view at height {}: {}", self.height.load(AtomicOrdering::SeqCst), e);
+						self.rebroadcast_after_jitter(&Step::Precommit);
+						Step::Precommit
+					},
+					Ok(()) => {
+						self.increment_view(1);
+						Step::Propose
+					},
+				}
+			},
+			Step::Precommit => {
+				phase_log!(self, Phase::Precommit, trace, "Precommit timeout without enough votes.");
+				self.rebroadcast_after_jitter(&Step::Precommit);
+				Step::Precommit
+			},
+			Step::Commit => {
+				phase_log!(self, Phase::Commit, trace, "Commit timeout.");
+				Step::Propose
+			},
+		};
+		self.to_step(next_step);
+	}
+
+	fn order_transactions(&self, transactions: &mut [SignedTransaction]) -> bool {
+		if self.canonical_transaction_order {
+			transactions.sort_by_key(|t| canonical_order_key(t));
+		}
+		self.canonical_transaction_order
+	}
+
+	/// Highest block with a verified commit, no higher than the best block.
+	fn finalized_block(&self, best_block: BlockNumber) -> Option<BlockNumber> {
+		Some(::std::cmp::min(self.finalized_height() as BlockNumber, best_block))
+	}
+
+	/// Votes reference the bare hash of a block, drop those for blocks which left the canonical chain.
+	fn on_reorg(&self, retracted: &[Header]) {
+		let stale: HashSet<H256> = retracted.iter().map(Header::bare_hash).collect();
+		// Voters stay recorded, so retracted votes remain evidence of double votes.
+		let removed = self.votes.throw_out_blocks(|hash| stale.contains(hash));
+		if removed > 0 {
+			debug!(target: "engine", "No longer counting {} votes for {} retracted blocks.", removed, stale.len());
+		}
+	}
+
+	fn on_canonical_import(&self, _enacted: &[H256]) {
+		self.reload_epochs();
+	}
+
+	fn register_client(&self, client: Weak<Client>) {
+		use client::BlockChainClient;
+		if let Some(c) = client.upgrade() {
+			let chain_info = c.chain_info();
+			self.height.store(chain_info.best_block_number as usize + 1, AtomicOrdering::SeqCst);
+			*self.last_commit.write() = Some((chain_info.best_block_number as Height, chain_info.best_block_hash));
+			// Every imported block is committed.
+			self.note_finalized(chain_info.best_block_number as Height);
+			// Until a proposal is received the validators are those after the best block, genesis for block 1.
+			*self.proposal_parent.write() = chain_info.best_block_hash;
+		}
+		*self.client.write() = Some(client.clone());
+		// Epochs are needed to sign and verify votes from the start.
+		self.reload_epochs();
+		self.validators.register_contract(client);
+		// Stored votes are checked against the epochs and validators of the chain head.
+		self.restore_votes();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use block::*;
+	use error::{Error, BlockError};
+	use header::Header;
+	use client::chain_notify::ChainNotify;
+	use client::TestBlockChainClient;
+	use miner::MinerService;
+	use tests::helpers::*;
+	use account_provider::AccountProvider;
+	use spec::Spec;
+	use engines::{Engine, EngineError, Seal};
+	use engines::validator_set::{SimpleList, WeightedList, TestSet};
+	use devtools::RandomTempPath;
+	use super::store::{VoteStore, DatabaseStore};
+	use super::verify::QuorumRatio;
+	use super::*;
+
+	/// Accounts inserted with "0" and "1" are validators. First proposer is "0".
+	fn setup() -> (Spec, Arc<AccountProvider>) {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let spec = Spec::new_test_tendermint();
+		(spec, tap)
+	}
+
+	fn propose_with(spec: &Spec, engine: &Engine, proposer: Address) -> (ClosedBlock, Seal) {
+		let db = get_temp_state_db();
+		let db = spec.ensure_db_good(db, &Default::default()).unwrap();
+		let genesis_header = spec.genesis_header();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+		let b = OpenBlock::new(engine, Default::default(), false, db.boxed_clone(), &genesis_header, last_hashes, proposer, (3141562.into(), 31415620.into()), vec![]).unwrap();
+		let b = b.close();
+		let seal = engine.generate_seal(b.block());
+		(b, seal)
+	}
+
+	fn propose_default(spec: &Spec, proposer: Address) -> (ClosedBlock, Vec<Bytes>) {
+		if let (b, Seal::Proposal(seal)) = propose_with(spec, spec.engine.as_ref(), proposer) {
+			(b, seal)
+		} else {
+			panic!()
+		}
+	}
+
+	fn vote<F>(engine: &Engine, signer: F, height: usize, view: usize, step: Step, block_hash: Option<H256>) -> Bytes where F: FnOnce(H256) -> Result<H520, ::account_provider::SignError> {
+		let mi = message_info_rlp(&VoteStep::new(height, view, step), block_hash);
+		let m = message_full_rlp(&signer(mi.sha3()).unwrap().into(), &mi);
+		engine.handle_message(&m).unwrap();
+		m
+	}
+
+	fn vote_as(engine: &Engine, tap: &Arc<AccountProvider>, signer: Address, height: usize, view: usize, step: Step, block_hash: Option<H256>) -> Bytes {
+		vote(engine, |mh| tap.sign(signer, None, mh).map(H520::from), height, view, step, block_hash)
+	}
+
+	fn commit_seal(tap: &Arc<AccountProvider>, header: &Header, view: View, signers: &[Address]) -> Vec<Bytes> {
+		let vote_info = message_info_rlp(&VoteStep::new(header.number() as Height, view, Step::Precommit), Some(header.bare_hash()));
+		let precommits: Vec<_> = signers.iter().map(|s| H520::from(tap.sign(*s, None, vote_info.sha3()).unwrap())).collect();
+		vec![
+			::rlp::encode(&view).to_vec(),
+			::rlp::NULL_RLP.to_vec(),
+			::rlp::encode_list(&precommits).to_vec()
+		]
+	}
+
+	fn proposal_seal(tap: &Arc<AccountProvider>, header: &Header, view: View) -> Vec<Bytes> {
+		let author = header.author();
+		let vote_info = message_info_rlp(&VoteStep::new(header.number() as Height, view, Step::Propose), Some(header.bare_hash()));
+		let signature = tap.sign(*author, None, vote_info.sha3()).unwrap();
+		vec![
+			::rlp::encode(&view).to_vec(),
+			::rlp::encode(&H520::from(signature)).to_vec(),
+			::rlp::EMPTY_LIST_RLP.to_vec()
+		]
+	}
+
+	fn insert_and_unlock(tap: &Arc<AccountProvider>, acc: &str) -> Address {
+		let addr = tap.insert_account(acc.sha3().into(), acc).unwrap();
+		tap.unlock_account_permanently(addr, acc.into()).unwrap();
+		addr
+	}
+
+	fn insert_and_register(tap: &Arc<AccountProvider>, engine: &Engine, acc: &str) -> Address {
+		let addr = insert_and_unlock(tap, acc);
+		engine.set_signer(tap.clone(), addr.clone(), acc.into());
+		addr
+	}
+
+	/// Parameters of the test spec.
+	fn test_params() -> TendermintParams {
+		let spec = ::ethjson::spec::Spec::load(include_bytes!("../../../res/tendermint.json") as &[u8]).unwrap();
+		match spec.engine {
+			::ethjson::spec::Engine::Tendermint(tendermint) => tendermint.params.into(),
+			_ => panic!("Test spec uses Tendermint."),
+		}
+	}
+
+	/// Engine which can be inspected directly.
+	fn new_engine(params: TendermintParams) -> Arc<Tendermint> {
+		Tendermint::new(Spec::new_test_tendermint().params().clone(), params, BTreeMap::new()).unwrap()
+	}
+
+	#[derive(Default)]
+	struct TestNotify {
+		messages: RwLock<Vec<Bytes>>,
+	}
+
+	impl ChainNotify for TestNotify {
+		fn broadcast(&self, data: Vec<u8>) {
+			self.messages.write().push(data);
+		}
+	}
+
+	/// Dummy client with `blocks` blocks registered with the engine, along with the messages it broadcasts.
+	fn notified_client(engine: &Engine, blocks: u32) -> (Arc<Client>, Arc<TestNotify>) {
+		let client = generate_dummy_client(blocks);
+		let notify = Arc::new(TestNotify::default());
+		client.add_notify(notify.clone());
+		engine.register_client(Arc::downgrade(&client));
+		(client, notify)
+	}
+
+	/// Client of the test spec sealing as "1", with a transaction pending for the first proposal.
+	/// Returned along with the messages it broadcasts.
+	fn sealing_client(tap: &Arc<AccountProvider>) -> (Arc<Client>, Arc<TestNotify>) {
+		use ethkey::{Generator, Random};
+		use types::transaction::{Transaction, Action};
+
+		let signer = insert_and_unlock(tap, "1");
+		let client = generate_dummy_client_with_spec_and_accounts(Spec::new_test_tendermint, Some(tap.clone()));
+		client.miner().set_engine_signer(signer, "1".into()).unwrap();
+		let notify = Arc::new(TestNotify::default());
+		client.add_notify(notify.clone());
+		client.engine().register_client(Arc::downgrade(&client));
+
+		let keypair = Random.generate().unwrap();
+		let transaction = Transaction {
+			action: Action::Create,
+			value: U256::zero(),
+			data: "3331600055".from_hex().unwrap(),
+			gas: U256::from(100_000),
+			gas_price: U256::zero(),
+			nonce: U256::zero(),
+		}.sign(keypair.secret(), None);
+		client.miner().import_own_transaction(client.as_ref(), transaction.into()).unwrap();
+		(client, notify)
+	}
+
+	#[test]
+	fn has_valid_metadata() {
+		let engine = Spec::new_test_tendermint().engine;
+		assert!(!engine.name().is_empty());
+		assert!(engine.version().major >= 1);
+	}
+
+	#[test]
+	fn can_return_schedule() {
+		let engine = Spec::new_test_tendermint().engine;
+		let schedule = engine.schedule(10000000);
+
+		assert!(schedule.stack_limit > 0);
+	}
+
+	#[test]
+	fn verification_fails_on_short_seal() {
+		let engine = Spec::new_test_tendermint().engine;
+		let header = Header::default();
+
+		let verify_result = engine.verify_block_basic(&header, None);
+
+		match verify_result {
+			Err(Error::Block(BlockError::InvalidSealArity(_))) => {},
+			Err(_) => { panic!("should be block seal-arity mismatch error (got {:?})", verify_result); },
+			_ => { panic!("Should be error, got Ok"); },
+		}
+	}
+
+	#[test]
+	fn verification_fails_on_malformed_seal_field() {
+		let engine = Spec::new_test_tendermint().engine;
+		let mut header = Header::default();
+
+		// Proposal signature is too short to be a signature.
+		header.set_seal(vec![::rlp::encode(&0usize).to_vec(), ::rlp::encode(&vec![1u8; 10]).to_vec(), ::rlp::EMPTY_LIST_RLP.to_vec()]);
+		match engine.verify_block_basic(&header, None) {
+			Err(Error::Engine(EngineError::BadProposalSignature(_))) => {},
+			r => panic!("should be proposal signature error (got {:?})", r),
+		}
+
+		// View is not an RLP value.
+		header.set_seal(vec![vec![0xc5], ::rlp::NULL_RLP.to_vec(), ::rlp::EMPTY_LIST_RLP.to_vec()]);
+		match engine.verify_block_basic(&header, None) {
+			Err(Error::Engine(EngineError::BadSealView(_))) => {},
+			r => panic!("should be view error (got {:?})", r),
+		}
+
+		// Precommits are not a list.
+		header.set_seal(vec![::rlp::encode(&0usize).to_vec(), ::rlp::NULL_RLP.to_vec(), ::rlp::encode(&H520::default()).to_vec()]);
+		match engine.verify_block_basic(&header, None) {
+			Err(Error::Engine(EngineError::BadPrecommits(_))) => {},
+			r => panic!("should be precommits error (got {:?})", r),
+		}
+
+		// Well formed proposal seal passes.
+		header.set_seal(vec![::rlp::encode(&0usize).to_vec(), ::rlp::encode(&H520::default()).to_vec(), ::rlp::EMPTY_LIST_RLP.to_vec()]);
+		assert!(engine.verify_block_basic(&header, None).is_ok());
+	}
+
+	#[test]
+	fn extra_info_names_proposer() {
+		let (spec, tap) = setup();
+		let engine = spec.engine;
+
+		let mut header = Header::default();
+		header.set_number(1);
+		let validator = insert_and_unlock(&tap, "1");
+		header.set_author(validator);
+		let seal = proposal_seal(&tap, &header, 0);
+		header.set_seal(seal);
+		let info = engine.extra_info(&header);
+		assert_eq!(info.get("proposer"), Some(&format!("0x{}", validator.hex())));
+		assert_eq!(info.get("view"), Some(&"0".to_owned()));
+		assert!(info.get("error").is_none());
+
+		// Signature which does not decode.
+		header.set_seal(vec![::rlp::encode(&0usize).to_vec(), ::rlp::encode(&vec![1u8; 10]).to_vec(), ::rlp::EMPTY_LIST_RLP.to_vec()]);
+		let info = engine.extra_info(&header);
+		assert!(info.get("error").is_some());
+		assert!(info.get("proposer").is_none());
+
+		// Missing seal fields.
+		let info = engine.extra_info(&Header::default());
+		assert!(info.get("error").is_some());
+	}
+
+	#[test]
+	fn broadcast_jitter_is_bounded() {
+		use rand::{XorShiftRng, SeedableRng};
+		let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+		let engine = new_engine(test_params());
+		for _ in 0..100 {
+			assert_eq!(engine.broadcast_delay(&mut rng, &Step::Prevote), StdDuration::from_millis(0));
+		}
+		engine.stop();
+
+		let mut params = test_params();
+		params.broadcast_jitter = ::time::Duration::milliseconds(50);
+		params.timeouts.precommit = ::time::Duration::milliseconds(60);
+		let engine = new_engine(params);
+		let delays: HashSet<_> = (0..100).map(|_| engine.broadcast_delay(&mut rng, &Step::Prevote)).collect();
+		assert!(delays.len() > 1);
+		assert!(delays.iter().all(|d| *d <= StdDuration::from_millis(50)));
+		// Half of the short precommit timeout.
+		assert!((0..100).all(|_| engine.broadcast_delay(&mut rng, &Step::Precommit) <= StdDuration::from_millis(30)));
+		engine.stop();
+	}
+
+	#[test]
+	fn delays_rebroadcast_without_blocking() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		// Without jitter no rebroadcast handler takes the delay.
+		let engine = new_engine(test_params());
+		vote_as(engine.as_ref(), &tap, v0, 1, 0, Step::Prevote, Some(H256::from(1)));
+
+		// Left to the rebroadcast timer.
+		engine.schedule_rebroadcast(StdDuration::from_millis(20));
+		assert!(engine.rebroadcast.lock().is_empty());
+		assert_eq!(*engine.rebroadcast_delay.lock(), Some(StdDuration::from_millis(20)));
+
+		*engine.rebroadcast_delay.lock() = None;
+		engine.schedule_rebroadcast(StdDuration::from_millis(0));
+		assert!(!engine.rebroadcast.lock().is_empty());
+		engine.stop();
+	}
+
+	#[test]
+	fn finalized_only_with_enough_precommits() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+
+		let mut header = Header::default();
+		header.set_number(1);
+		header.set_author(v1);
+		let seal = proposal_seal(&tap, &header, 0);
+		header.set_seal(seal);
+		assert!(!engine.is_finalized(&header));
+
+		let seal = commit_seal(&tap, &header, 0, &[v0]);
+		header.set_seal(seal);
+		assert!(!engine.is_finalized(&header));
+
+		let seal = commit_seal(&tap, &header, 0, &[v1, v0]);
+		header.set_seal(seal);
+		assert!(engine.is_finalized(&header));
+		engine.stop();
+	}
+
+	#[test]
+	fn conflicting_proposals_resolve_to_lowest_hash() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v1 = insert_and_unlock(&tap, "1");
+		// "1" proposes twice at height 1 view 0.
+		let proposals: Vec<_> = (1..3).map(|timestamp| {
+			let mut header = Header::default();
+			header.set_number(1);
+			header.set_timestamp(timestamp);
+			header.set_author(v1);
+			let seal = proposal_seal(&tap, &header, 0);
+			header.set_seal(seal);
+			header
+		}).collect();
+		let lowest = min(proposals[0].bare_hash(), proposals[1].bare_hash());
+
+		let first = new_engine(test_params());
+		assert!(first.is_proposal(&proposals[0]));
+		assert!(first.is_proposal(&proposals[1]));
+		let second = new_engine(test_params());
+		assert!(second.is_proposal(&proposals[1]));
+		assert!(second.is_proposal(&proposals[0]));
+		assert_eq!(*first.proposal.read(), Some(lowest));
+		assert_eq!(*second.proposal.read(), Some(lowest));
+		first.stop();
+		second.stop();
+	}
+
+	#[test]
+	fn accepts_votes_of_backup_key() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		// Primary key is locked, so signing falls back to the backup key.
+		let v1 = tap.insert_account("1".sha3().into(), "1").unwrap();
+		let backup = insert_and_unlock(&tap, "2");
+		let mut params = test_params();
+		params.signer_keys.insert(backup, v1);
+		let signing = new_engine(params);
+		signing.set_signer(tap.clone(), v1, "locked".into());
+		signing.add_backup_signer(backup, "2".into());
+		assert!(signing.signer.is_address(&backup));
+
+		let mut params = test_params();
+		params.signer_keys.insert(backup, v1);
+		let engine = new_engine(params);
+		let block_hash = H256::random();
+		let prevote = vote(&*engine, |mh| signing.signer.sign(mh).map(H520::from), 1, 0, Step::Prevote, Some(block_hash));
+		assert!(engine.handle_message(&prevote).is_ok());
+		assert_eq!(engine.votes.round_voters(&VoteStep::new(1, 0, Step::Prevote)), vec![(v1, Some(block_hash))]);
+
+		// Without the mapping the backup key is no validator.
+		let other = new_engine(test_params());
+		match other.handle_message(&prevote) {
+			Err(Error::Engine(EngineError::NotAuthorized(address))) => assert_eq!(address, backup),
+			_ => panic!(),
+		}
+		signing.stop();
+		engine.stop();
+		other.stop();
+	}
+
+	#[test]
+	fn retries_step_transition() {
+		let engine = new_engine(test_params());
+		let unreachable = || IoError::StdIo(::std::io::Error::new(::std::io::ErrorKind::Other, "unreachable"));
+
+		let mut attempts = 0;
+		assert!(engine.send_step(|| {
+			attempts += 1;
+			if attempts == 1 { Err(unreachable()) } else { engine.step_service.send_message(Step::Propose) }
+		}).is_ok());
+		assert_eq!(attempts, 2);
+		assert!(engine.step_timer_healthy());
+
+		let mut attempts = 0;
+		assert!(engine.send_step(|| { attempts += 1; Err(unreachable()) }).is_err());
+		assert_eq!(attempts, ::engines::transition::IO_SEND_ATTEMPTS);
+		assert!(!engine.step_timer_healthy());
+		engine.stop();
+	}
+
+	#[test]
+	fn enforces_min_gas_limit() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let mut common = Spec::new_test_tendermint().params().clone();
+		common.min_gas_limit = U256::from_str("222000").unwrap();
+		let engine = Tendermint::new(common, test_params(), BTreeMap::new()).unwrap();
+
+		let mut parent_header: Header = Header::default();
+		parent_header.set_gas_limit(U256::from_str("222222").unwrap());
+
+		let mut header = Header::default();
+		header.set_number(1);
+		header.set_gas_limit(U256::from_str("221fff").unwrap());
+		let validator = insert_and_unlock(&tap, "1");
+		header.set_author(validator);
+		let seal = proposal_seal(&tap, &header, 0);
+		header.set_seal(seal);
+		// Within the parent bound, but under the floor.
+		match engine.verify_block_family(&header, &parent_header, None) {
+			Err(Error::Block(BlockError::InvalidGasLimit(ref bounds))) => assert_eq!(bounds.min, Some(U256::from_str("222000").unwrap())),
+			_ => panic!(),
+		}
+
+		header.set_gas_limit(U256::from_str("222000").unwrap());
+		let seal = proposal_seal(&tap, &header, 0);
+		header.set_seal(seal);
+		assert!(engine.verify_block_family(&header, &parent_header, None).is_ok());
+
+		// Lowering the gas limit stops at the floor.
+		parent_header.set_seal(vec![::rlp::encode(&0usize).to_vec()]);
+		let mut proposed = Header::default();
+		engine.populate_from_parent(&mut proposed, &parent_header, 0.into(), 0.into());
+		assert_eq!(proposed.gas_limit(), &U256::from_str("222000").unwrap());
+		engine.stop();
+	}
+
+	#[test]
+	fn rejects_author_other_than_proposer() {
+		let (spec, tap) = setup();
+		let engine = spec.engine;
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+
+		let mut parent_header: Header = Header::default();
+		parent_header.set_gas_limit(U256::from_str("222222").unwrap());
+
+		let mut header = Header::default();
+		header.set_number(1);
+		header.set_gas_limit(U256::from_str("222222").unwrap());
+		header.set_author(v1);
+		let seal = proposal_seal(&tap, &header, 0);
+		header.set_seal(seal);
+		assert!(engine.verify_block_family(&header, &parent_header, None).is_ok());
+
+		// "1" is the proposer, but names "0" as the author.
+		header.set_author(v0);
+		let vote_info = message_info_rlp(&VoteStep::new(1, 0, Step::Propose), Some(header.bare_hash()));
+		let signature = tap.sign(v1, None, vote_info.sha3()).unwrap();
+		header.set_seal(vec![
+			::rlp::encode(&0usize).to_vec(),
+			::rlp::encode(&H520::from(signature)).to_vec(),
+			::rlp::EMPTY_LIST_RLP.to_vec()
+		]);
+		match engine.verify_block_family(&header, &parent_header, None) {
+			Err(Error::Engine(EngineError::AuthorMismatch(mis))) => {
+				assert_eq!(mis.expected, v1);
+				assert_eq!(mis.found, v0);
+			},
+			_ => panic!(),
+		}
+	}
+
+	#[test]
+	fn allows_correct_proposer() {
+		let (spec, tap) = setup();
+		let engine = spec.engine;
+
+		let mut parent_header: Header = Header::default();
+		parent_header.set_gas_limit(U256::from_str("222222").unwrap());
+
+		let mut header = Header::default();
+		header.set_number(1);
+		header.set_gas_limit(U256::from_str("222222").unwrap());
+		let validator = insert_and_unlock(&tap, "1");
+		header.set_author(validator);
+		let seal = proposal_seal(&tap, &header, 0);
+		header.set_seal(seal);
+		// Good proposer.
+		assert!(engine.verify_block_family(&header, &parent_header, None).is_ok());
+
+		let validator = insert_and_unlock(&tap, "0");
+		header.set_author(validator);
+		let seal = proposal_seal(&tap, &header, 0);
+		header.set_seal(seal);
+		// Bad proposer.
+		match engine.verify_block_family(&header, &parent_header, None) {
+			Err(Error::Engine(EngineError::NotProposer(_))) => {},
+			_ => panic!(),
+		}
+
+		let random = insert_and_unlock(&tap, "101");
+		header.set_author(random);
+		let seal = proposal_seal(&tap, &header, 0);
+		header.set_seal(seal);
+		// Not authority.
+		match engine.verify_block_family(&header, &parent_header, None) {
+			Err(Error::Engine(EngineError::NotAuthorized(_))) => {},
+			_ => panic!(),
+		};
+		engine.stop();
+	}
+
+	#[test]
+	fn checks_timestamp() {
+		use verification::verify_header_params;
+
+		let default_engine = new_engine(test_params());
+		let mut params = test_params();
+		params.allowed_future_time = 60;
+		let engine = new_engine(params);
+		assert_eq!(engine.allowed_future_block_time(), 60);
+
+		let mut header = Header::default();
+		header.set_number(1);
+		header.set_gas_limit(engine.params().min_gas_limit);
+		let now = ::time::get_time().sec as u64;
+		header.set_timestamp(now);
+		assert!(verify_header_params(&header, &*default_engine, true).is_ok());
+		// Basic verification applies the bound of the engine.
+		header.set_timestamp(now + 45);
+		assert!(verify_header_params(&header, &*engine, true).is_ok());
+		match verify_header_params(&header, &*default_engine, true) {
+			Err(Error::Block(BlockError::InvalidTimestamp(oob))) => assert!(oob.max.is_some()),
+			_ => panic!("Timestamp too far ahead should be rejected."),
+		}
+		header.set_timestamp(now + 3600);
+		assert!(verify_header_params(&header, &*engine, true).is_err());
+		default_engine.stop();
+		engine.stop();
+	}
+
+	#[test]
+	fn seal_signatures_checking() {
+		let (spec, tap) = setup();
+		let engine = spec.engine;
+
+		let mut parent_header: Header = Header::default();
+		parent_header.set_gas_limit(U256::from_str("222222").unwrap());
+
+		let mut header = Header::default();
+		header.set_number(2);
+		header.set_gas_limit(U256::from_str("222222").unwrap());
+		let proposer = insert_and_unlock(&tap, "1");
+		header.set_author(proposer);
+		let mut seal = proposal_seal(&tap, &header, 0);
+
+		let vote_info = message_info_rlp(&VoteStep::new(2, 0, Step::Precommit), Some(header.bare_hash()));
+		let signature1 = tap.sign(proposer, None, vote_info.sha3()).unwrap();
+
+		seal[1] = ::rlp::NULL_RLP.to_vec();
+		seal[2] = ::rlp::encode_list(&vec![H520::from(signature1.clone())]).to_vec();
+		header.set_seal(seal.clone());
+
+		// One good signature is not enough.
+		match engine.verify_block_family(&header, &parent_header, None) {
+			Err(Error::Engine(EngineError::BadSealFieldSize(_))) => {},
+			_ => panic!(),
+		}
+
+		let voter = insert_and_unlock(&tap, "0");
+		let signature0 = tap.sign(voter, None, vote_info.sha3()).unwrap();
+
+		seal[2] = ::rlp::encode_list(&vec![H520::from(signature1.clone()), H520::from(signature0.clone())]).to_vec();
+		header.set_seal(seal.clone());
+
+		assert!(engine.verify_block_family(&header, &parent_header, None).is_ok());
+
+		let bad_voter = insert_and_unlock(&tap, "101");
+		let bad_signature = tap.sign(bad_voter, None, vote_info.sha3()).unwrap();
+
+		seal[2] = ::rlp::encode_list(&vec![H520::from(signature1), H520::from(bad_signature)]).to_vec();
+		header.set_seal(seal);
+
+		// One good and one bad signature.
+		match engine.verify_block_family(&header, &parent_header, None) {
+			Err(Error::Engine(EngineError::NotAuthorized(_))) => {},
+			_ => panic!(),
+		};
+		engine.stop();
+	}
+
+	#[test]
+	fn can_generate_seal() {
+		let (spec, tap) = setup();
+
+		let proposer = insert_and_register(&tap, spec.engine.as_ref(), "1");
+
+		let (b, seal) = propose_default(&spec, proposer);
+		assert!(b.lock().try_seal(spec.engine.as_ref(), seal).is_ok());
+	}
+
+	#[test]
+	fn does_not_seal_for_other_author() {
+		let (spec, tap) = setup();
+		let proposer = insert_and_register(&tap, spec.engine.as_ref(), "1");
+		let other = insert_and_unlock(&tap, "0");
+
+		match propose_with(&spec, spec.engine.as_ref(), other) {
+			(_, Seal::None) => {},
+			_ => panic!("Proposal authored by another validator should not be sealed."),
+		}
+		let (b, seal) = propose_default(&spec, proposer);
+		assert!(b.lock().try_seal(spec.engine.as_ref(), seal).is_ok());
+	}
+
+	/// Engine persisting votes to `path`, with the client and chain head set up like `register_client` does.
+	fn restart_with_store(path: &RandomTempPath, height: Height) -> Arc<Tendermint> {
+		let mut params = test_params();
+		params.persist_votes = true;
+		params.vote_store_path = Some(path.as_str().to_owned());
+		let engine = new_engine(params);
+		let client = Arc::new(TestBlockChainClient::default());
+		*engine.client.write() = Some(Arc::downgrade(&(client.clone() as Arc<EngineClient>)));
+		engine.height.store(height, AtomicOrdering::SeqCst);
+		engine.reload_epochs();
+		engine.restore_votes();
+		engine
+	}
+
+	#[test]
+	fn restores_stored_votes() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let path = RandomTempPath::new();
+		let engine = restart_with_store(&path, 1);
+
+		let mut header = Header::default();
+		header.set_number(1);
+		header.set_author(v1);
+		let seal = proposal_seal(&tap, &header, 0);
+		header.set_seal(seal);
+		assert!(engine.is_proposal(&header));
+		let proposal = ConsensusMessage::new_proposal(&header).unwrap();
+		*engine.step.write() = Step::Prevote;
+		vote_as(engine.as_ref(), &tap, v0, 1, 0, Step::Prevote, Some(header.bare_hash()));
+		vote_as(engine.as_ref(), &tap, v1, 1, 0, Step::Prevote, Some(header.bare_hash()));
+		let lock = engine.lock_change.read().clone().expect("prevotes of both validators lock the engine");
+		engine.stop();
+		drop(engine);
+
+		// A conflicting vote in the store is not restored.
+		{
+			let store = DatabaseStore::open(path.as_str()).unwrap();
+			assert_eq!(store.load().votes.len(), 3);
+			let mi = message_info_rlp(&VoteStep::new(1, 0, Step::Prevote), None);
+			store.insert(1, &message_full_rlp(&tap.sign(v0, None, mi.sha3()).unwrap().into(), &mi));
+		}
+
+		// Restart.
+		let engine = restart_with_store(&path, 1);
+		assert!(engine.votes.is_old_or_known(&proposal));
+		let prevote_step = VoteStep::new(1, 0, Step::Prevote);
+		let voters = engine.votes.round_voters(&prevote_step);
+		assert_eq!(voters.len(), 2);
+		assert!(voters.contains(&(v1, Some(header.bare_hash()))));
+		assert!(voters.iter().any(|&(ref voter, _)| *voter == v0));
+		assert_eq!(*engine.lock_change.read(), Some(lock));
+		engine.stop();
+		drop(engine);
+
+		// Votes below the chain head are discarded, and so is the lock.
+		let engine = restart_with_store(&path, 2);
+		assert!(engine.votes.round_voters(&prevote_step).is_empty());
+		assert!(engine.lock_change.read().is_none());
+		// Moving to the next height prunes the store.
+		engine.to_next_height(2);
+		engine.stop();
+		drop(engine);
+		assert_eq!(DatabaseStore::open(path.as_str()).unwrap().load(), Default::default());
+	}
+
+	#[test]
+	fn verifies_first_block_on_genesis() {
+		let (spec, tap) = setup();
+		let engine = spec.engine.clone();
+		let genesis = spec.genesis_header();
+		// The first validator in the rotation, height 1 view 0.
+		let proposer = insert_and_register(&tap, engine.as_ref(), "1");
+		let (b, seal) = propose_default(&spec, proposer);
+		let sealed = b.lock().seal(engine.as_ref(), seal).unwrap();
+		let header = sealed.header().clone();
+		assert_eq!(header.number(), 1);
+		assert_eq!(*header.parent_hash(), genesis.hash());
+		assert!(engine.verify_block_basic(&header, None).is_ok());
+		assert!(engine.verify_block_family(&header, &genesis, None).is_ok());
+
+		// Gas limit bounds come from the genesis block.
+		let mut header = header;
+		header.set_gas_limit(*genesis.gas_limit() * U256::from(2));
+		let seal = proposal_seal(&tap, &header, 0);
+		header.set_seal(seal);
+		match engine.verify_block_family(&header, &genesis, None) {
+			Err(Error::Block(BlockError::InvalidGasLimit(_))) => {},
+			e => panic!("Unexpected result: {:?}", e),
+		}
+		engine.stop();
+	}
+
+	#[test]
+	fn can_recognize_proposal() {
+		let (spec, tap) = setup();
+
+		let proposer = insert_and_register(&tap, spec.engine.as_ref(), "1");
+
+		let (b, seal) = propose_default(&spec, proposer);
+		let sealed = b.lock().seal(spec.engine.as_ref(), seal).unwrap();
+		assert!(spec.engine.is_proposal(sealed.header()));
+	}
+
+	#[test]
+	fn relays_messages() {
+		let (spec, tap) = setup();
+		let engine = spec.engine.clone();
+
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_register(&tap, engine.as_ref(), "1");
+
+		let h = 1;
+		let r = 0;
+
+		// Propose
+		let (b, _) = propose_default(&spec, v1.clone());
+		let proposal = Some(b.header().bare_hash());
+
+		let (_client, notify) = notified_client(engine.as_ref(), 0);
+
+		let prevote_current = vote_as(engine.as_ref(), &tap, v0, h, r, Step::Prevote, proposal);
+
+		let precommit_current = vote_as(engine.as_ref(), &tap, v0, h, r, Step::Precommit, proposal);
+
+		let prevote_future = vote_as(engine.as_ref(), &tap, v0, h + 1, r, Step::Prevote, proposal);
+
+		// Relays all valid present and future messages.
+		assert!(notify.messages.read().contains(&prevote_current));
+		assert!(notify.messages.read().contains(&precommit_current));
+		assert!(notify.messages.read().contains(&prevote_future));
+	}
+
+	#[test]
+	fn counts_messages_by_type() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+
+		let (_client, _) = notified_client(engine.as_ref(), 0);
+
+		let mut header = Header::default();
+		header.set_number(1);
+		header.set_author(v1);
+		let seal = proposal_seal(&tap, &header, 0);
+		header.set_seal(seal);
+		assert!(engine.is_proposal(&header));
+		let proposal = Some(header.bare_hash());
+
+		vote_as(engine.as_ref(), &tap, v0, 1, 0, Step::Prevote, proposal);
+		vote_as(engine.as_ref(), &tap, v0, 1, 0, Step::Precommit, proposal);
+
+		let metrics = engine.message_metrics();
+		assert_eq!(metrics.messages_in.get(Step::Propose), 1);
+		assert_eq!(metrics.messages_in.get(Step::Prevote), 1);
+		assert_eq!(metrics.messages_in.get(Step::Precommit), 1);
+		// Valid votes are relayed.
+		assert_eq!(metrics.messages_out.get(Step::Propose), 0);
+		assert_eq!(metrics.messages_out.get(Step::Prevote), 1);
+		assert_eq!(metrics.messages_out.get(Step::Precommit), 1);
+		engine.stop();
+	}
+
+	#[test]
+	fn fast_forwards_committed_blocks() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+
+		let mut parent_hash = H256::default();
+		let mut headers = Vec::new();
+		for number in 1..4 {
+			let mut header = Header::default();
+			header.set_number(number);
+			header.set_parent_hash(parent_hash);
+			let seal = commit_seal(&tap, &header, 0, &[v0, v1]);
+			header.set_seal(seal);
+			parent_hash = header.hash();
+			headers.push(header);
+		}
+
+		// Under quorum seal is rejected and nothing is applied.
+		let mut bad = headers.clone();
+		let seal = commit_seal(&tap, &bad[1], 0, &[v0]);
+		bad[1].set_seal(seal);
+		assert!(engine.fast_forward(&bad).is_err());
+		assert_eq!(engine.height.load(AtomicOrdering::SeqCst), 1);
+
+		// Validly sealed blocks which do not link up are rejected.
+		let mut unlinked = headers.clone();
+		unlinked[2].set_parent_hash(H256::default());
+		let seal = commit_seal(&tap, &unlinked[2], 0, &[v0, v1]);
+		unlinked[2].set_seal(seal);
+		assert!(engine.fast_forward(&unlinked).is_err());
+		// So is a run which does not start at the current height.
+		assert!(engine.fast_forward(&headers[1..]).is_err());
+		assert_eq!(engine.height.load(AtomicOrdering::SeqCst), 1);
+
+		assert!(engine.fast_forward(&headers).is_ok());
+		assert_eq!(engine.height.load(AtomicOrdering::SeqCst), 4);
+		assert_eq!(engine.view.load(AtomicOrdering::SeqCst), 0);
+		engine.stop();
+	}
+
+	#[test]
+	fn tracks_view_latency() {
+		let engine = new_engine(test_params());
+		assert_eq!(engine.view_latency_stats().views, 0);
+
+		engine.increment_view(1);
+		engine.to_next_height(1);
+		let stats = engine.view_latency_stats();
+		assert_eq!(stats.views, 2);
+		assert!(stats.max >= stats.average);
+		assert!(stats.max < ::std::time::Duration::from_secs(10));
+		assert_eq!(engine.consensus().unwrap().view_latency_stats().views, 2);
+		engine.stop();
+	}
+
+	#[test]
+	fn rejects_block_with_blacklisted_sender() {
+		use ethkey::{Generator, Random};
+		use types::transaction::{Transaction, Action};
+
+		let banned = Random.generate().unwrap();
+		let allowed = Random.generate().unwrap();
+		let mut params = test_params();
+		params.sender_blacklist.insert(banned.address());
+		let engine = new_engine(params);
+
+		let transaction = |keypair: &::ethkey::KeyPair| Transaction {
+			action: Action::Create,
+			value: U256::zero(),
+			data: Vec::new(),
+			gas: U256::from(100_000),
+			gas_price: U256::zero(),
+			nonce: U256::zero(),
+		}.sign(keypair.secret(), None);
+
+		let header = Header::default();
+		let good = create_test_block_with_data(&header, &[transaction(&allowed)], &[]);
+		assert!(engine.verify_block_unordered(&header, Some(&good)).is_ok());
+
+		let bad = create_test_block_with_data(&header, &[transaction(&allowed), transaction(&banned)], &[]);
+		match engine.verify_block_unordered(&header, Some(&bad)) {
+			Err(Error::Transaction(TransactionError::SenderBanned)) => {},
+			_ => panic!(),
+		}
+		engine.stop();
+	}
+
+	#[test]
+	fn requires_canonical_transaction_order() {
+		use ethkey::{Generator, Random};
+		use types::transaction::{Transaction, Action};
+
+		let mut params = test_params();
+		params.canonical_transaction_order = true;
+		let engine = new_engine(params);
+
+		let transaction = |nonce: u64| Transaction {
+			action: Action::Create,
+			value: U256::zero(),
+			data: Vec::new(),
+			gas: U256::from(100_000),
+			gas_price: U256::zero(),
+			nonce: U256::from(nonce),
+		}.sign(Random.generate().unwrap().secret(), None);
+
+		let header = Header::default();
+		let mut transactions = vec![transaction(1), transaction(0), transaction(0)];
+		let unordered = create_test_block_with_data(&header, &transactions, &[]);
+		match engine.verify_block_unordered(&header, Some(&unordered)) {
+			Err(Error::Block(BlockError::TransactionOutOfOrder(_))) => {},
+			_ => panic!("Block with transactions out of order should be rejected."),
+		}
+
+		assert!(engine.order_transactions(&mut transactions));
+		let ordered = create_test_block_with_data(&header, &transactions, &[]);
+		assert!(engine.verify_block_unordered(&header, Some(&ordered)).is_ok());
+		engine.stop();
+	}
+
+	#[test]
+	fn requires_system_transaction() {
+		use client::BlockChainClient;
+		use ethkey::{Generator, Random};
+		use types::transaction::{Transaction, Action};
+		use super::params::RequiredTransaction;
+
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let client = generate_dummy_client(1);
+		let system = Address::from(0x1000);
+		let mut params = test_params();
+		params.required_transactions = vec![RequiredTransaction { to: system, data_prefix: vec![0xab] }];
+		let engine = new_engine(params);
+		engine.register_client(Arc::downgrade(&client));
+
+		// "0" proposes at even heights.
+		let parent = client.block_header(BlockId::Number(1)).unwrap().decode();
+		let mut header = Header::default();
+		header.set_number(2);
+		header.set_parent_hash(parent.hash());
+		header.set_gas_limit(parent.gas_limit().clone());
+		header.set_author(v0);
+		let seal = proposal_seal(&tap, &header, 0);
+		header.set_seal(seal);
+
+		let call = |data: Bytes| Transaction {
+			action: Action::Call(system),
+			value: U256::zero(),
+			data: data,
+			gas: U256::from(100_000),
+			gas_price: U256::zero(),
+			nonce: U256::zero(),
+		}.sign(Random.generate().unwrap().secret(), None);
+
+		let missing = create_test_block_with_data(&header, &[call(vec![0xcd])], &[]);
+		match engine.verify_block_family(&header, &parent, Some(&missing)) {
+			Err(Error::Block(BlockError::MissingRequiredTransaction(a))) => assert_eq!(a, system),
+			_ => panic!("Block without the required call should be rejected."),
+		}
+		let included = create_test_block_with_data(&header, &[call(vec![0xcd]), call(vec![0xab, 0x01])], &[]);
+		assert!(engine.verify_block_family(&header, &parent, Some(&included)).is_ok());
+		engine.stop();
+	}
+
+	#[test]
+	fn stores_double_vote_proof_once() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+
+		let messages: Vec<_> = (0..3).map(|n| {
+			let mi = message_info_rlp(&VoteStep::new(1, 0, Step::Prevote), Some(H256::from(n as u64)));
+			message_full_rlp(&tap.sign(v0, None, mi.sha3()).unwrap().into(), &mi)
+		}).collect();
+		assert!(engine.handle_message(&messages[0]).is_ok());
+		assert!(engine.double_vote_proofs(&v0).is_empty());
+		for m in &messages[1..] {
+			assert!(engine.handle_message(m).is_err());
+		}
+
+		let proofs = engine.double_vote_proofs(&v0);
+		assert_eq!(proofs.len(), 1);
+		let proof = UntrustedRlp::new(&proofs[0]);
+		assert_eq!(proof.at(0).unwrap().as_raw(), &messages[0][..]);
+		assert_eq!(proof.at(1).unwrap().as_raw(), &messages[1][..]);
+		engine.stop();
+	}
+
+	#[test]
+	fn counts_double_votes() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+
+		for n in 0..(DOUBLE_VOTE_ALERT_THRESHOLD + 1) {
+			let mi = message_info_rlp(&VoteStep::new(1, 0, Step::Prevote), Some(H256::from(n as u64)));
+			let m = message_full_rlp(&tap.sign(v0, None, mi.sha3()).unwrap().into(), &mi);
+			match engine.handle_message(&m) {
+				Ok(_) if n == 0 => {},
+				Err(Error::Engine(EngineError::DoubleVote(a))) => assert_eq!(a, v0),
+				_ => panic!("Conflicting votes should be reported."),
+			}
+			if n + 1 < DOUBLE_VOTE_ALERT_THRESHOLD {
+				assert!(engine.equivocating_validators().is_empty());
+			}
+		}
+		assert_eq!(engine.double_votes(&v0), DOUBLE_VOTE_ALERT_THRESHOLD);
+		assert_eq!(engine.equivocating_validators(), vec![v0]);
+		assert_eq!(engine.consensus().unwrap().equivocating_validators(), vec![v0]);
+		engine.stop();
+	}
+
+	#[test]
+	fn silences_log_phases() {
+		let engine = new_engine(test_params());
+		assert!(engine.log_phases.is_enabled(Phase::NewView));
+		assert!(engine.log_phases.is_enabled(Phase::Commit));
+
+		engine.set_log_phases(&[Phase::Commit, Phase::Prevote]);
+		assert!(!engine.log_phases.is_enabled(Phase::NewView));
+		assert!(!engine.log_phases.is_enabled(Phase::Propose));
+		assert!(engine.log_phases.is_enabled(Phase::Commit));
+		assert!(engine.log_phases.is_enabled(Phase::Prevote));
+
+		// Silenced phases still transition.
+		engine.increment_view(1);
+		assert_eq!(engine.view.load(AtomicOrdering::SeqCst), 1);
+
+		// Phases set through the introspection of the engine.
+		engine.consensus().unwrap().set_log_phases(&[Phase::NewView]);
+		assert!(engine.log_phases.is_enabled(Phase::NewView));
+		assert!(!engine.log_phases.is_enabled(Phase::Commit));
+		engine.stop();
+	}
+
+	#[test]
+	fn tags_message_handling_with_round() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+
+		let prevote = vote_as(&*engine, &tap, v0, 1, 2, Step::Prevote, Some(H256::random()));
+		assert!(engine.handle_message(&prevote).is_ok());
+		assert_eq!(RoundSpan::last_entered(), Some(RoundSpan { height: 1, view: 2 }));
+		// Left once the message is handled.
+		assert_eq!(RoundSpan::current(), None);
+		engine.stop();
+	}
+
+	#[test]
+	fn rejects_sub_half_quorum_ratio() {
+		let mut params = test_params();
+		params.quorum_ratio = QuorumRatio { numerator: 2, denominator: 5 };
+		match Tendermint::new(Spec::new_test_tendermint().params().clone(), params, BTreeMap::new()) {
+			Err(Error::Engine(EngineError::InvalidParams(_))) => {},
+			_ => panic!("Ratio below one half should be rejected."),
+		}
+
+		// Two validators, both needed under either ratio.
+		let mut params = test_params();
+		params.quorum_ratio = QuorumRatio { numerator: 3, denominator: 4 };
+		let engine = new_engine(params);
+		assert!(engine.check_above_threshold(1).is_err());
+		assert!(engine.check_above_threshold(2).is_ok());
+		engine.stop();
+	}
+
+	#[test]
+	fn rejects_zero_gas_limit_bound_divisor() {
+		let mut params = test_params();
+		params.gas_limit_bound_divisor = U256::zero();
+		match Tendermint::new(Spec::new_test_tendermint().params().clone(), params, BTreeMap::new()) {
+			Err(Error::Engine(EngineError::InvalidParams(_))) => {},
+			_ => panic!("Zero divisor should be rejected."),
+		}
+
+		let engine = Tendermint::new(Spec::new_test_tendermint().params().clone(), test_params(), BTreeMap::new()).unwrap();
+		assert_eq!(engine.gas_limit_bound_divisor, U256::from(0x400));
+		engine.stop();
+	}
+
+	#[test]
+	fn reports_effective_config() {
+		let engine = new_engine(test_params());
+		let config = engine.effective_config();
+		// Set in the spec.
+		assert_eq!(config.find("timeoutPropose"), Some(&Json::U64(10000)));
+		assert_eq!(config.find("gasLimitBoundDivisor"), Some(&Json::String("0x400".into())));
+		// Filled in by defaults.
+		assert_eq!(config.find("blockReward"), Some(&Json::String("0x0".into())));
+		assert_eq!(config.find("viewChangeBackoff"), Some(&Json::U64(1)));
+		assert_eq!(config.find("maxTimeout"), Some(&Json::Null));
+		assert_eq!(config.find("syncDistance"), Some(&Json::Null));
+		assert_eq!(config.find("minimalCommitSeal"), Some(&Json::Boolean(false)));
+		assert_eq!(config.find("persistVotes"), Some(&Json::Boolean(false)));
+		assert_eq!(config.find("senderBlacklist"), Some(&Json::Array(vec![])));
+		assert_eq!(engine.consensus().unwrap().effective_config(), config);
+		engine.stop();
+	}
+
+	#[test]
+	fn dumps_state_json() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+		let proposal = Some("0".sha3());
+		vote_as(engine.as_ref(), &tap, v0, 1, 0, Step::Prevote, proposal);
+
+		let state = Json::from_str(&engine.dump_state_json()).unwrap();
+		assert_eq!(state.find("height").and_then(Json::as_u64), Some(1));
+		assert_eq!(state.find("view").and_then(Json::as_u64), Some(0));
+		assert_eq!(state.find("step").and_then(Json::as_string), Some("propose"));
+		let voter = format!("0x{}", v0.hex());
+		let prevote = state.find_path(&["votes", "prevote", &*voter]).and_then(Json::as_string);
+		assert_eq!(prevote, Some(&*format!("0x{}", proposal.unwrap().hex())));
+		engine.stop();
+	}
+
+	#[derive(Default)]
+	struct TestSink(Mutex<Vec<(H256, Height, View, usize)>>);
+
+	impl CommitSink for Arc<TestSink> {
+		fn on_commit(&self, block_hash: &H256, height: Height, view: View, signatures: &[H520]) {
+			self.0.lock().push((block_hash.clone(), height, view, signatures.len()));
+		}
+	}
+
+	#[derive(Default)]
+	struct TestGadget(Mutex<Vec<(H256, usize)>>);
+
+	impl FinalityGadget for Arc<TestGadget> {
+		fn on_finalized(&self, header: &Header, proof: &[Bytes]) {
+			self.0.lock().push((header.hash(), proof.len()));
+		}
+	}
+
+	#[test]
+	fn passes_commits_to_finality_gadget() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let gadget = Arc::new(TestGadget::default());
+		engine.set_finality_gadget(Box::new(gadget.clone()));
+
+		let mut proposal = Header::default();
+		proposal.set_number(1);
+		proposal.set_author(v1);
+		let seal = proposal_seal(&tap, &proposal, 0);
+		proposal.set_seal(seal);
+		assert!(engine.is_proposal(&proposal));
+		assert!(gadget.0.lock().is_empty());
+
+		let mut commit = Header::default();
+		commit.set_number(1);
+		commit.set_author(v1);
+		let seal = commit_seal(&tap, &commit, 0, &[v1, v0]);
+		commit.set_seal(seal);
+		assert!(!engine.is_proposal(&commit));
+		assert_eq!(*gadget.0.lock(), vec![(commit.hash(), 2)]);
+		engine.stop();
+	}
+
+	#[test]
+	fn pushes_commit_to_sink() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let validators: Vec<_> = ["0", "1", "2", "3"].iter().map(|acc| insert_and_unlock(&tap, acc)).collect();
+		let mut params = test_params();
+		params.validators = Box::new(SimpleList::new(validators.clone()));
+		let engine = new_engine(params);
+		let sink = Arc::new(TestSink::default());
+		engine.set_commit_sink(Box::new(sink.clone()));
+
+		let proposal = H256::from(1);
+		*engine.last_proposed.write() = proposal;
+		*engine.step.write() = Step::Precommit;
+		for v in &validators[..3] {
+			vote_as(engine.as_ref(), &tap, *v, 1, 0, Step::Precommit, Some(proposal));
+		}
+		assert_eq!(*sink.0.lock(), vec![(proposal, 1, 0, 3)]);
+		engine.stop();
+	}
+
+	#[test]
+	fn commit_uses_message_view() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let validators: Vec<_> = ["0", "1", "2", "3"].iter().map(|acc| insert_and_unlock(&tap, acc)).collect();
+		let mut params = test_params();
+		params.validators = Box::new(SimpleList::new(validators.clone()));
+		let engine = new_engine(params);
+		let sink = Arc::new(TestSink::default());
+		engine.set_commit_sink(Box::new(sink.clone()));
+
+		let proposal = H256::from(1);
+		*engine.last_proposed.write() = proposal;
+		*engine.step.write() = Step::Precommit;
+		for v in &validators[..2] {
+			vote_as(engine.as_ref(), &tap, *v, 1, 2, Step::Precommit, Some(proposal));
+		}
+		// View moves on while the seal is being assembled.
+		engine.view.store(5, AtomicOrdering::SeqCst);
+		vote_as(engine.as_ref(), &tap, validators[2], 1, 2, Step::Precommit, Some(proposal));
+		// Seal view matches the view of the collected signatures.
+		assert_eq!(*sink.0.lock(), vec![(proposal, 1, 2, 3)]);
+		engine.stop();
+	}
+
+	#[test]
+	fn reports_last_commit_view() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		assert_eq!(engine.last_commit_view_changes(), 0);
+
+		*engine.step.write() = Step::Precommit;
+		let proposal = Some(H256::from(1));
+		vote_as(engine.as_ref(), &tap, v1, 1, 2, Step::Precommit, proposal);
+		vote_as(engine.as_ref(), &tap, v0, 1, 2, Step::Precommit, proposal);
+		assert_eq!(engine.height.load(AtomicOrdering::SeqCst), 2);
+		assert_eq!(engine.last_commit_view_changes(), 2);
+		assert_eq!(engine.consensus().unwrap().last_commit_view_changes(), 2);
+		engine.stop();
+	}
+
+	#[test]
+	fn commit_seal_gathers_votes_in_grace_window() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let validators: Vec<_> = ["0", "1", "2", "3"].iter().map(|acc| insert_and_unlock(&tap, acc)).collect();
+		let mut params = test_params();
+		params.validators = Box::new(SimpleList::new(validators.clone()));
+		params.commit_grace_window = Some(::time::Duration::seconds(100));
+		let engine = new_engine(params);
+
+		let proposal = Some(H256::from(1));
+		let parent = H256::from(7);
+		*engine.proposal_parent.write() = parent;
+		*engine.last_proposed.write() = proposal.unwrap();
+		*engine.step.write() = Step::Precommit;
+		// Three out of four is a quorum.
+		for v in &validators[..3] {
+			vote_as(engine.as_ref(), &tap, *v, 1, 0, Step::Precommit, proposal);
+		}
+		assert_eq!(engine.height.load(AtomicOrdering::SeqCst), 2);
+		assert!(engine.pending_seal.lock().is_some());
+		assert!(engine.take_pending_seal(false).is_none());
+		// The deadline timer is set once.
+		assert!(engine.arm_pending_seal().is_some());
+		assert!(engine.arm_pending_seal().is_none());
+		vote_as(engine.as_ref(), &tap, validators[3], 1, 0, Step::Precommit, proposal);
+		*engine.proposal_parent.write() = H256::from(8);
+
+		let pending = engine.take_pending_seal(true).unwrap();
+		assert_eq!(pending.block_hash, proposal.unwrap());
+		assert_eq!(pending.parent, parent);
+		assert_eq!(engine.votes.round_signatures(&pending.vote_step, &pending.block_hash).len(), 4);
+		engine.stop();
+	}
+
+	#[test]
+	fn flags_different_proposals_at_height() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v1 = insert_and_unlock(&tap, "1");
+		let proposal = |view: View, timestamp: u64, transactions_root: H256| {
+			let mut header = Header::default();
+			header.set_number(1);
+			header.set_author(v1);
+			header.set_timestamp(timestamp);
+			header.set_transactions_root(transactions_root);
+			let seal = proposal_seal(&tap, &header, view);
+			header.set_seal(seal);
+			header
+		};
+
+		// "1" proposes at height 1 in even views, rebuilding the block with a new timestamp is honest.
+		assert!(engine.is_proposal(&proposal(0, 10, H256::from(1))));
+		assert!(engine.is_proposal(&proposal(2, 20, H256::from(1))));
+		assert_eq!(engine.height_equivocations(&v1), 0);
+		assert!(engine.is_proposal(&proposal(4, 30, H256::from(2))));
+		assert_eq!(engine.height_equivocations(&v1), 1);
+		engine.stop();
+	}
+
+	/// Engine where "1" alone outweighs "0" and "2".
+	fn weighted_engine(weighted_proposer: bool) -> (Arc<Tendermint>, Arc<AccountProvider>, Vec<Address>) {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let v2 = insert_and_unlock(&tap, "2");
+		let mut params = test_params();
+		params.validators = Box::new(WeightedList::new(vec![(v0, 1), (v1, 5), (v2, 1)].into_iter().collect()));
+		params.weighted_proposer = weighted_proposer;
+		(new_engine(params), tap, vec![v0, v1, v2])
+	}
+
+	#[test]
+	fn weighted_quorum_commits_with_minority() {
+		let (engine, tap, v) = weighted_engine(false);
+		let (v0, v1, v2) = (v[0], v[1], v[2]);
+		let proposal = Some(H256::from(1));
+		*engine.step.write() = Step::Precommit;
+
+		// Two of three validators, but only 2 of the total weight 7.
+		vote_as(engine.as_ref(), &tap, v0, 1, 0, Step::Precommit, proposal);
+		vote_as(engine.as_ref(), &tap, v2, 1, 0, Step::Precommit, proposal);
+		assert_eq!(engine.height.load(AtomicOrdering::SeqCst), 1);
+
+		vote_as(engine.as_ref(), &tap, v1, 1, 0, Step::Precommit, proposal);
+		assert_eq!(engine.height.load(AtomicOrdering::SeqCst), 2);
+		engine.stop();
+
+		// A single validator above two thirds of the weight.
+		let (engine, tap, v) = weighted_engine(false);
+		*engine.step.write() = Step::Precommit;
+		vote_as(engine.as_ref(), &tap, v[1], 1, 0, Step::Precommit, proposal);
+		assert_eq!(engine.height.load(AtomicOrdering::SeqCst), 2);
+		engine.stop();
+	}
+
+	#[test]
+	fn excludes_votes_of_removed_validators() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v: Vec<_> = ["0", "1", "2", "3"].iter().map(|name| insert_and_unlock(&tap, name)).collect();
+		let set = Arc::new(RwLock::new(SimpleList::new(v.clone())));
+		let mut params = test_params();
+		params.validators = Box::new(TestSet::shared(set.clone()));
+		let engine = new_engine(params);
+		let sink = Arc::new(TestSink::default());
+		engine.set_commit_sink(Box::new(sink.clone()));
+
+		let proposal = H256::from(1);
+		let vote_step = VoteStep::new(1, 0, Step::Precommit);
+		let vote_info = message_info_rlp(&vote_step, Some(proposal));
+		for voter in &[v[0], v[2], v[3]] {
+			let signature = H520::from(tap.sign(*voter, None, vote_info.sha3()).unwrap());
+			engine.votes.vote(ConsensusMessage::new(signature, 1, 0, Step::Precommit, Some(proposal)), voter);
+		}
+		let message = ConsensusMessage::new(Default::default(), 1, 0, Step::Precommit, Some(proposal));
+		// Three of four validators.
+		assert!(engine.has_enough_aligned_votes(&message));
+
+		// "3" is removed during the view, two of the remaining three are not enough.
+		*set.write() = SimpleList::new(vec![v[0], v[1], v[2]]);
+		assert!(!engine.has_enough_aligned_votes(&message));
+
+		engine.submit_commit(&vote_step, proposal);
+		assert_eq!(sink.0.lock()[0].3, 2);
+		engine.stop();
+	}
+
+	#[test]
+	fn weighted_proposer_rotation() {
+		let (engine, _, v) = weighted_engine(true);
+		let (v0, v1, v2) = (v[0], v[1], v[2]);
+		// Validators are ordered by address: "1", "0", "2".
+		let proposers: Vec<_> = (0..7).map(|view| engine.view_proposer(&H256::default(), 1, view)).collect();
+		assert_eq!(proposers, vec![v1, v1, v1, v1, v0, v2, v1]);
+		engine.stop();
+	}
+
+	#[test]
+	fn withholds_messages_while_syncing() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let mut params = test_params();
+		params.sync_distance = Some(2);
+		let engine = new_engine(params);
+		insert_and_register(&tap, engine.as_ref(), "1");
+		let v0 = insert_and_unlock(&tap, "0");
+		assert!(engine.generate_message(None).is_some());
+
+		// Far behind the network.
+		vote_as(engine.as_ref(), &tap, v0, 10, 0, Step::Prevote, Some(H256::from(1)));
+		assert_eq!(engine.network_height(), Some(10));
+		let rounds = engine.votes.len();
+		assert!(engine.generate_message(None).is_none());
+		assert_eq!(engine.votes.len(), rounds);
+
+		// Caught up to within the distance.
+		engine.height.store(8, AtomicOrdering::SeqCst);
+		assert!(engine.generate_message(None).is_some());
+		engine.stop();
+	}
+
+	#[test]
+	fn counts_only_highest_future_view_vote() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		*engine.step.write() = Step::Prevote;
+
+		// Different blocks, so that only the view change can be triggered.
+		for view in 2..5 {
+			vote_as(engine.as_ref(), &tap, v0, 1, view, Step::Prevote, Some(H256::from(1)));
+		}
+		vote_as(engine.as_ref(), &tap, v1, 1, 2, Step::Prevote, Some(H256::from(2)));
+		// The vote of "0" counts towards view 4 only.
+		assert_eq!(engine.view.load(AtomicOrdering::SeqCst), 0);
+
+		vote_as(engine.as_ref(), &tap, v1, 1, 4, Step::Prevote, Some(H256::from(2)));
+		assert_eq!(engine.view.load(AtomicOrdering::SeqCst), 4);
+		engine.stop();
+	}
+
+	#[test]
+	fn backs_off_timeouts_with_view() {
+		let timeouts = TendermintTimeouts {
+			view_change_backoff: 2,
+			max_timeout: Some(::time::Duration::milliseconds(5000)),
+			..Default::default()
+		};
+		assert_eq!(timeouts.view_timeout(&Step::Prevote, 0), ::time::Duration::milliseconds(1000));
+		assert_eq!(timeouts.view_timeout(&Step::Prevote, 1), ::time::Duration::milliseconds(2000));
+		assert_eq!(timeouts.view_timeout(&Step::Prevote, 2), ::time::Duration::milliseconds(4000));
+		assert_eq!(timeouts.view_timeout(&Step::Prevote, 3), ::time::Duration::milliseconds(5000));
+		assert_eq!(timeouts.view_timeout(&Step::Prevote, 100), ::time::Duration::milliseconds(5000));
+		// Without backoff timeouts stay the same.
+		assert_eq!(TendermintTimeouts::default().view_timeout(&Step::Propose, 5), ::time::Duration::milliseconds(1000));
+	}
+
+	#[test]
+	fn ignores_proposal_not_on_last_commit() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+
+		let commit = |number: BlockNumber, parent_hash: H256| {
+			let mut header = Header::default();
+			header.set_number(number);
+			header.set_author(v1);
+			header.set_parent_hash(parent_hash);
+			let seal = commit_seal(&tap, &header, 0, &[v1, v0]);
+			header.set_seal(seal);
+			header
+		};
+		// Validators take turns, "0" proposes at height 2 view 0 and "1" at height 3.
+		let proposal = |number: BlockNumber, parent_hash: H256| {
+			let mut header = Header::default();
+			header.set_number(number);
+			header.set_author(if number % 2 == 0 { v0 } else { v1 });
+			header.set_parent_hash(parent_hash);
+			let seal = proposal_seal(&tap, &header, 0);
+			header.set_seal(seal);
+			header
+		};
+
+		let first = commit(1, H256::default());
+		assert!(!engine.is_proposal(&first));
+		assert!(engine.is_proposal(&proposal(2, H256::from(1))));
+		assert!(engine.proposal.read().is_none());
+		let valid = proposal(2, first.hash());
+		assert!(engine.is_proposal(&valid));
+		assert_eq!(*engine.proposal.read(), Some(valid.bare_hash()));
+
+		// Height 2 is sealed locally, its commit is imported afterwards.
+		*engine.last_commit.write() = None;
+		engine.to_next_height(2);
+		let second = commit(2, first.hash());
+		assert!(!engine.is_proposal(&second));
+		assert_eq!(*engine.last_commit.read(), Some((2, second.hash())));
+		assert!(engine.is_proposal(&proposal(3, first.hash())));
+		assert!(engine.proposal.read().is_none());
+		let valid = proposal(3, second.hash());
+		assert!(engine.is_proposal(&valid));
+		assert_eq!(*engine.proposal.read(), Some(valid.bare_hash()));
+		engine.stop();
+	}
+
+	#[test]
+	fn reports_consensus_state() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_register(&tap, engine.as_ref(), "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let state = engine.consensus_state();
+		assert_eq!((state.height, state.view, state.step), (1, 0, Step::Propose));
+		// "1" proposes at height 1 view 0.
+		assert!(!state.is_proposer);
+		assert_eq!(state.proposal_votes, 0);
+
+		let proposal = H256::from(1);
+		*engine.proposal.write() = Some(proposal);
+		*engine.step.write() = Step::Prevote;
+		vote_as(engine.as_ref(), &tap, v1, 1, 0, Step::Prevote, Some(proposal));
+		assert_eq!(engine.consensus_state().proposal_votes, 1);
+
+		// View change on nil precommits.
+		*engine.step.write() = Step::Precommit;
+		vote_as(engine.as_ref(), &tap, v0, 1, 0, Step::Precommit, None);
+		vote_as(engine.as_ref(), &tap, v1, 1, 0, Step::Precommit, None);
+		let state = engine.consensus_state();
+		assert_eq!((state.height, state.view), (1, 1));
+		assert!(state.is_proposer);
+		// Same state is served through the consensus introspection.
+		assert_eq!(engine.consensus().unwrap().consensus_state(), state);
+		engine.stop();
+	}
+
+	#[test]
+	fn counts_runtime_activity() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		assert_eq!(engine.runtime_stats().messages_processed, 0);
+
+		let proposal = Some(H256::from(1));
+		*engine.step.write() = Step::Precommit;
+		vote_as(engine.as_ref(), &tap, v0, 1, 0, Step::Precommit, proposal);
+		assert_eq!(engine.runtime_stats().messages_processed, 1);
+		assert_eq!(engine.runtime_stats().commits, 0);
+		vote_as(engine.as_ref(), &tap, v1, 1, 0, Step::Precommit, proposal);
+		let stats = engine.runtime_stats();
+		assert_eq!(stats.messages_processed, 2);
+		assert_eq!(stats.commits, 1);
+		assert_eq!(stats.proposals_seen, 0);
+		assert_eq!(engine.consensus().unwrap().runtime_stats().messages_processed, 2);
+		engine.stop();
+	}
+
+	#[test]
+	fn seals_minimal_quorum() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let validators: Vec<_> = ["0", "1", "2", "3"].iter().map(|acc| insert_and_unlock(&tap, acc)).collect();
+		let mut params = test_params();
+		params.validators = Box::new(SimpleList::new(validators.clone()));
+		params.commit_grace_window = Some(::time::Duration::seconds(100));
+		params.seal_signatures = SealSignatures::Minimal;
+		match Tendermint::new(Spec::new_test_tendermint().params().clone(), params, BTreeMap::new()) {
+			Err(Error::Engine(EngineError::InvalidParams(_))) => {},
+			_ => panic!("Minimal seals leave out the precommits of the grace window."),
+		}
+
+		let mut params = test_params();
+		params.validators = Box::new(SimpleList::new(validators.clone()));
+		params.seal_signatures = SealSignatures::Minimal;
+		let engine = new_engine(params);
+		let sink = Arc::new(TestSink::default());
+		engine.set_commit_sink(Box::new(sink.clone()));
+
+		let proposal = H256::from(1);
+		*engine.last_proposed.write() = proposal;
+		// Precommits received in the prevote step are all counted once the node precommits.
+		*engine.step.write() = Step::Prevote;
+		for v in &validators {
+			vote_as(engine.as_ref(), &tap, *v, 1, 0, Step::Precommit, Some(proposal));
+		}
+		// Three out of four is a quorum.
+		assert_eq!(*sink.0.lock(), vec![(proposal, 1, 0, 3)]);
+		engine.stop();
+	}
+
+	#[test]
+	fn seeded_proposer_is_agreed() {
+		let mut params = test_params();
+		params.primary_strategy = PrimaryStrategy::HashBased;
+		let engine = new_engine(params);
+		let mut params = test_params();
+		params.primary_strategy = PrimaryStrategy::HashBased;
+		let other = new_engine(params);
+
+		let mut proposers = HashSet::new();
+		for n in 0..8 {
+			let parent_hash = H256::from(n);
+			let proposer = engine.view_proposer(&parent_hash, 1, 0);
+			assert_eq!(proposer, other.view_proposer(&parent_hash, 1, 0));
+			// Does not depend on height, only on the parent.
+			assert_eq!(proposer, engine.view_proposer(&parent_hash, 5, 0));
+			proposers.insert(proposer);
+		}
+		// Rotation still covers all validators.
+		assert_eq!(proposers.len(), 2);
+		engine.stop();
+		other.stop();
+	}
+
+	#[test]
+	fn round_robin_proposer_is_agreed() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let engine = new_engine(test_params());
+		let other = new_engine(test_params());
+		assert_eq!(engine.primary_strategy, PrimaryStrategy::RoundRobin);
+
+		for height in 1..5 {
+			for view in 0..3 {
+				let parent_hash = H256::from((height * 10 + view) as u64);
+				let proposer = engine.view_proposer(&parent_hash, height, view);
+				assert_eq!(proposer, other.view_proposer(&parent_hash, height, view));
+				// Does not depend on the parent.
+				assert_eq!(proposer, engine.view_proposer(&H256::default(), height, view));
+				assert_eq!(proposer, if (height + view) % 2 == 0 { v0 } else { v1 });
+			}
+		}
+		engine.stop();
+		other.stop();
+	}
+
+	/// Propose as "1" and prevote for the proposal as "0", optionally after a nil prevote from "1".
+	fn locks_on_single_prevote(proposer_vote_counts: bool, proposer_prevotes_nil: bool) -> bool {
+		let (spec, tap) = setup();
+		let mut params = test_params();
+		params.proposer_vote_counts = proposer_vote_counts;
+		let engine = new_engine(params);
+		let v1 = insert_and_register(&tap, engine.as_ref(), "1");
+		let (b, seal) = propose_with(&spec, engine.as_ref(), v1);
+		assert!(seal != Seal::None);
+		let proposal = Some(b.header().bare_hash());
+		// Do not prevote as the proposer.
+		let v0 = insert_and_register(&tap, engine.as_ref(), "0");
+		if proposer_prevotes_nil {
+			vote_as(engine.as_ref(), &tap, v1, 1, 0, Step::Prevote, None);
+		}
+		vote_as(engine.as_ref(), &tap, v0, 1, 0, Step::Prevote, proposal);
+		let locked = engine.lock_change.read().is_some();
+		engine.stop();
+		locked
+	}
+
+	#[test]
+	fn proposal_counts_as_prevote() {
+		// Two validators: a single prevote is not enough on its own.
+		assert!(!locks_on_single_prevote(false, false));
+		assert!(locks_on_single_prevote(true, false));
+		// Proposer which prevoted nil is not counted for its proposal.
+		assert!(!locks_on_single_prevote(true, true));
+	}
+
+	#[test]
+	fn loads_epochs_from_canonical_transitions() {
+		let client = Arc::new(TestBlockChainClient::new());
+		client.epoch_transitions.write().push((1, 5));
+		let engine = new_engine(test_params());
+		*engine.client.write() = Some(Arc::downgrade(&(client.clone() as Arc<EngineClient>)));
+		// Nothing is recorded before the client provides the transitions.
+		assert_eq!(engine.epoch_at(6), 0);
+		engine.on_canonical_import(&[H256::from(5)]);
+		assert_eq!(engine.epoch_at(5), 0);
+		assert_eq!(engine.epoch_at(6), 1);
+
+		// A restarted engine loads the same epochs.
+		let restarted = new_engine(test_params());
+		*restarted.client.write() = Some(Arc::downgrade(&(client.clone() as Arc<EngineClient>)));
+		restarted.reload_epochs();
+		assert_eq!(restarted.epoch_at(6), 1);
+
+		// Transition retracted by a reorg.
+		client.epoch_transitions.write().pop();
+		engine.on_canonical_import(&[H256::from(6)]);
+		assert_eq!(engine.epoch_at(6), 0);
+		engine.stop();
+		restarted.stop();
+	}
+
+	#[test]
+	fn rejects_vote_from_previous_epoch() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+		engine.epochs.write().insert(1, 1);
+		assert_eq!(engine.epoch_at(0), 0);
+		assert_eq!(engine.epoch_at(5), 1);
+
+		let mi = message_info_rlp(&VoteStep::new(1, 0, Step::Prevote), None);
+		let replayed = message_full_rlp(&tap.sign(v0, None, mi.sha3()).unwrap().into(), &mi);
+		match engine.handle_message(&replayed) {
+			Err(Error::Engine(EngineError::NotAuthorized(_))) => {},
+			_ => panic!("Vote from the previous epoch should be rejected."),
+		}
+		let signed = message_full_rlp(&tap.sign(v0, None, vote_hash(1, &mi)).unwrap().into(), &mi);
+		assert!(engine.handle_message(&signed).is_ok());
+		engine.stop();
+	}
+
+	#[test]
+	fn reports_consensus_metrics() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+
+		let mut header = Header::default();
+		header.set_number(1);
+		header.set_author(v1);
+		let seal = proposal_seal(&tap, &header, 0);
+		header.set_seal(seal);
+		assert!(engine.is_proposal(&header));
+		let proposal = Some(header.bare_hash());
+		vote_as(engine.as_ref(), &tap, v0, 1, 0, Step::Prevote, proposal);
+		vote_as(engine.as_ref(), &tap, v1, 1, 0, Step::Prevote, None);
+		vote_as(engine.as_ref(), &tap, v0, 1, 0, Step::Precommit, proposal);
+		// Different view.
+		vote_as(engine.as_ref(), &tap, v1, 1, 1, Step::Prevote, None);
+
+		assert_eq!(engine.consensus_metrics(), ConsensusMetrics {
+			height: 1,
+			view: 0,
+			proposals: 1,
+			prevotes: 2,
+			precommits: 1,
+			known_messages: 5,
+		});
+		engine.stop();
+	}
+
+	#[test]
+	fn no_seal_behind_chain_head() {
+		use client::EachBlockWith;
+
+		let spec = Spec::new_test_tendermint();
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_register(&tap, engine.as_ref(), "0");
+		let client = Arc::new(TestBlockChainClient::new());
+		client.add_blocks(1, EachBlockWith::Nothing);
+		*engine.client.write() = Some(Arc::downgrade(&(client.clone() as Arc<EngineClient>)));
+
+		// Proposer of height 1 view 1, but block 1 is already in the chain.
+		engine.view.store(1, AtomicOrdering::SeqCst);
+		let (_, seal) = propose_with(&spec, engine.as_ref(), v0);
+		assert!(seal == Seal::None);
+
+		// Proposer of height 2 view 0 after catching up.
+		engine.to_next_height(1);
+		let (_, seal) = propose_with(&spec, engine.as_ref(), v0);
+		match seal {
+			Seal::Proposal(_) => {},
+			_ => panic!("should propose once the engine caught up with the chain"),
+		}
+		engine.stop();
+	}
+
+	#[test]
+	fn defers_messages_over_rate_limit() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		assert_eq!(test_params().messages_per_second, None);
+		let mut params = test_params();
+		params.messages_per_second = Some(3);
+		// Slices are only started by the test.
+		params.message_slice_interval = ::time::Duration::hours(1);
+		let engine = new_engine(params);
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+
+		for view in 0..5 {
+			vote_as(engine.as_ref(), &tap, v0, 1, view, Step::Prevote, Some(H256::from(1)));
+		}
+		assert_eq!(engine.message_metrics().messages_in.get(Step::Prevote), 3);
+		assert_eq!(engine.votes.message_count(), 3);
+		assert_eq!(engine.deferred_messages.lock().len(), 2);
+
+		// Other validators keep their own rate.
+		vote_as(engine.as_ref(), &tap, v1, 1, 0, Step::Prevote, Some(H256::from(1)));
+		assert_eq!(engine.message_metrics().messages_in.get(Step::Prevote), 4);
+
+		// Still over the rate within the same second.
+		engine.next_message_slice();
+		assert_eq!(engine.votes.message_count(), 4);
+		assert_eq!(engine.deferred_messages.lock().len(), 2);
+
+		// Applied once the next second starts.
+		engine.message_rates.lock().clear();
+		engine.next_message_slice();
+		assert_eq!(engine.votes.message_count(), 6);
+		assert!(engine.deferred_messages.lock().is_empty());
+		engine.stop();
+	}
+
+	#[test]
+	fn drops_votes_on_reorg() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+		let mut retracted = Header::default();
+		retracted.set_number(1);
+		let mut canonical = retracted.clone();
+		canonical.set_timestamp(1);
+		assert!(retracted.bare_hash() != canonical.bare_hash());
+
+		vote_as(engine.as_ref(), &tap, v0, 1, 0, Step::Prevote, Some(retracted.bare_hash()));
+		let round = VoteStep::new(1, 0, Step::Prevote);
+		assert_eq!(engine.votes.round_signatures(&round, &retracted.bare_hash()).len(), 1);
+
+		engine.on_reorg(&[retracted.clone()]);
+		assert!(engine.votes.round_signatures(&round, &retracted.bare_hash()).is_empty());
+		// Voting again in the same round is still a double vote.
+		let mi = message_info_rlp(&round, Some(canonical.bare_hash()));
+		let revote = message_full_rlp(&tap.sign(v0, None, mi.sha3()).unwrap().into(), &mi);
+		match engine.handle_message(&revote) {
+			Err(Error::Engine(EngineError::DoubleVote(address))) => assert_eq!(address, v0),
+			_ => panic!("Re-vote after a reorg should be a double vote."),
+		}
+		assert!(engine.votes.round_signatures(&round, &canonical.bare_hash()).is_empty());
+		engine.stop();
+	}
+
+	#[test]
+	fn counts_missed_primary_slots() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let primary = new_engine(test_params());
+		let other = new_engine(test_params());
+		// Proposer of height 1 view 0.
+		insert_and_register(&tap, primary.as_ref(), "1");
+		insert_and_register(&tap, other.as_ref(), "0");
+		assert_eq!(*primary.step.read(), Step::Propose);
+
+		primary.step();
+		other.step();
+		assert_eq!(primary.missed_primary_slots(), 1);
+		assert_eq!(other.missed_primary_slots(), 0);
+
+		// A proposal fills the slot.
+		*primary.step.write() = Step::Propose;
+		*primary.proposal.write() = Some(H256::from(1));
+		primary.step();
+		assert_eq!(primary.missed_primary_slots(), 1);
+		primary.stop();
+		other.stop();
+	}
+
+	#[test]
+	fn drops_votes_of_past_views() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let mut params = test_params();
+		params.max_retained_views = Some(2);
+		let engine = new_engine(params);
+
+		let message = |view| ConsensusMessage::new(H520::random(), 1, view, Step::Prevote, Some(H256::from(1)));
+		for view in 0..11 {
+			engine.votes.vote(message(view), &v0);
+		}
+		assert_eq!(engine.votes.message_count(), 11);
+
+		engine.view.store(9, AtomicOrdering::SeqCst);
+		engine.increment_view(1);
+		// Views 8 and 9 are retained along with the current one.
+		assert_eq!(engine.votes.message_count(), 3);
+		for view in 8..11 {
+			assert_eq!(engine.votes.count_round_votes(&VoteStep::new(1, view, Step::Prevote)), 1);
+		}
+		assert!(engine.votes.is_old_or_known(&message(7)));
+		assert!(!engine.votes.is_old_or_known(&message(8)));
+		engine.stop();
+	}
+
+	#[test]
+	fn caps_votes_of_past_views_on_arrival() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let mut params = test_params();
+		assert_eq!(params.max_retained_views, None);
+		params.max_retained_messages = 4;
+		let engine = new_engine(params);
+		engine.view.store(5, AtomicOrdering::SeqCst);
+
+		for view in 0..6 {
+			vote_as(engine.as_ref(), &tap, v0, 1, view, Step::Prevote, Some(H256::from(1)));
+		}
+		// The oldest views are dropped as votes arrive, the current view is kept.
+		assert_eq!(engine.votes.message_count(), 4);
+		for view in 2..6 {
+			assert_eq!(engine.votes.count_round_votes(&VoteStep::new(1, view, Step::Prevote)), 1);
+		}
+		let mi = message_info_rlp(&VoteStep::new(1, 1, Step::Prevote), Some(H256::from(1)));
+		let old: ConsensusMessage = UntrustedRlp::new(&message_full_rlp(&tap.sign(v0, None, mi.sha3()).unwrap().into(), &mi)).as_val().unwrap();
+		assert!(engine.votes.is_old_or_known(&old));
+		engine.stop();
+	}
+
+	#[test]
+	fn goes_quiet_after_stop() {
+		let spec = Spec::new_test_tendermint();
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		// Proposer of height 1 view 0.
+		let v1 = insert_and_register(&tap, engine.as_ref(), "1");
+		let v0 = insert_and_unlock(&tap, "0");
+		let client = Arc::new(TestBlockChainClient::new());
+		*engine.client.write() = Some(Arc::downgrade(&(client.clone() as Arc<EngineClient>)));
+
+		vote_as(engine.as_ref(), &tap, v0, 1, 0, Step::Prevote, Some(H256::from(1)));
+		let relayed = engine.message_metrics().messages_out.get(Step::Prevote);
+		engine.stop();
+		// Votes are flushed once on the way out.
+		assert_eq!(engine.message_metrics().messages_out.get(Step::Prevote), relayed + 1);
+		engine.stop();
+		assert_eq!(engine.message_metrics().messages_out.get(Step::Prevote), relayed + 1);
+
+		let (_, seal) = propose_with(&spec, engine.as_ref(), v1);
+		assert!(seal == Seal::None);
+		vote_as(engine.as_ref(), &tap, v0, 1, 0, Step::Precommit, Some(H256::from(1)));
+		assert_eq!(engine.message_metrics().messages_in.get(Step::Precommit), 0);
+		assert_eq!(engine.message_metrics().messages_out.get(Step::Precommit), 0);
+		assert_eq!(engine.message_metrics().messages_out.get(Step::Prevote), relayed + 1);
+	}
+
+	#[test]
+	fn rebroadcasts_messages_once_per_height() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+		let client = Arc::new(TestBlockChainClient::new());
+		*engine.client.write() = Some(Arc::downgrade(&(client.clone() as Arc<EngineClient>)));
+
+		vote_as(engine.as_ref(), &tap, v0, 1, 0, Step::Prevote, Some(H256::from(1)));
+		let relayed = engine.message_metrics().messages_out.get(Step::Prevote);
+		engine.broadcast_old_messages();
+		assert_eq!(engine.message_metrics().messages_out.get(Step::Prevote), relayed + 1);
+		// Nothing new to send.
+		engine.broadcast_old_messages();
+		assert_eq!(engine.message_metrics().messages_out.get(Step::Prevote), relayed + 1);
+
+		// Cache resets on a height change.
+		engine.to_next_height(0);
+		engine.broadcast_old_messages();
+		assert_eq!(engine.message_metrics().messages_out.get(Step::Prevote), relayed + 2);
+		engine.stop();
+	}
+
+	#[test]
+	fn stops_view_changes_at_limit() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let mut params = test_params();
+		params.max_view = Some(2);
+		let engine = new_engine(params);
+
+		assert!(engine.view_limit_status().is_ok());
+		for view in 0..3 {
+			*engine.step.write() = Step::Precommit;
+			// Enough precommits, but for different blocks.
+			vote_as(engine.as_ref(), &tap, v0, 1, view, Step::Precommit, Some(H256::from(1)));
+			vote_as(engine.as_ref(), &tap, v1, 1, view, Step::Precommit, Some(H256::from(2)));
+			engine.step();
+		}
+		assert_eq!(engine.view.load(AtomicOrdering::SeqCst), 2);
+		assert_eq!(*engine.step.read(), Step::Precommit);
+		// The timeout keeps firing and the votes are sent again.
+		assert!(!engine.rebroadcast.lock().is_empty());
+		engine.rebroadcast.lock().clear();
+		engine.step();
+		assert_eq!(engine.view.load(AtomicOrdering::SeqCst), 2);
+		assert_eq!(*engine.step.read(), Step::Precommit);
+		assert!(!engine.rebroadcast.lock().is_empty());
+		match engine.view_limit_status() {
+			Err(EngineError::ViewLimitReached(2)) => {},
+			_ => panic!("View limit should be reported."),
+		}
+
+		// Recovers on the next height.
+		engine.to_next_height(1);
+		assert!(engine.view_limit_status().is_ok());
+		engine.stop();
+	}
+
+	#[test]
+	fn spreads_message_bursts_over_slices() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let mut params = test_params();
+		params.max_messages_per_slice = Some(2);
+		// Slices are only started by the test.
+		params.message_slice_interval = ::time::Duration::hours(1);
+		let engine = new_engine(params);
+
+		let messages: Vec<_> = (0..5).map(|view| {
+			let mi = message_info_rlp(&VoteStep::new(1, view, Step::Prevote), None);
+			message_full_rlp(&tap.sign(v0, None, mi.sha3()).unwrap().into(), &mi)
+		}).collect();
+		for m in &messages {
+			assert!(engine.handle_message(m).is_ok());
+		}
+		// Messages of others are rejected right away, without using up the slice.
+		let v2 = insert_and_unlock(&tap, "2");
+		let mi = message_info_rlp(&VoteStep::new(1, 0, Step::Prevote), None);
+		let outsider = message_full_rlp(&tap.sign(v2, None, mi.sha3()).unwrap().into(), &mi);
+		match engine.handle_message(&outsider) {
+			Err(Error::Engine(EngineError::NotAuthorized(address))) => assert_eq!(address, v2),
+			_ => panic!(),
+		}
+		let handled = || messages.iter()
+			.filter(|m| engine.votes.is_old_or_known(&UntrustedRlp::new(m).as_val().unwrap()))
+			.count();
+		assert_eq!(handled(), 2);
+		assert_eq!(engine.deferred_messages.lock().len(), 3);
+
+		engine.next_message_slice();
+		assert_eq!(handled(), 4);
+		engine.next_message_slice();
+		assert_eq!(handled(), 5);
+		assert!(engine.deferred_messages.lock().is_empty());
+		engine.stop();
+	}
+
+	#[test]
+	fn notifies_commits() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let commits = engine.subscribe_commits();
+		let proposal = H256::from(1);
+		*engine.step.write() = Step::Precommit;
+
+		vote_as(engine.as_ref(), &tap, v0, 1, 0, Step::Precommit, Some(proposal));
+		assert!(commits.try_recv().is_err());
+		vote_as(engine.as_ref(), &tap, v1, 1, 0, Step::Precommit, Some(proposal));
+		assert_eq!(commits.try_recv(), Ok(CommittedBlock { height: 1, view: 0, block_hash: proposal, signers: vec![v1, v0] }));
+		assert!(commits.try_recv().is_err());
+		engine.stop();
+	}
+
+	#[test]
+	fn proves_finality_of_commit() {
+		use super::verify::verify_finality_proof;
+
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let mut header = Header::default();
+		header.set_number(1);
+		*engine.step.write() = Step::Precommit;
+		assert_eq!(engine.finalized_height(), 0);
+
+		vote_as(engine.as_ref(), &tap, v0, 1, 0, Step::Precommit, Some(header.bare_hash()));
+		assert_eq!(engine.finalized_height(), 0);
+		vote_as(engine.as_ref(), &tap, v1, 1, 0, Step::Precommit, Some(header.bare_hash()));
+		assert_eq!(engine.finalized_height(), 1);
+
+		let validators = SimpleList::new(vec![v0, v1]);
+		header.set_seal(commit_seal(&tap, &header, 0, &[v1, v0]));
+		let proof = finality_proof(&header).unwrap();
+		// The proof stands on its own, without the seal.
+		let mut bare = header.clone();
+		bare.set_seal(Vec::new());
+		assert!(verify_finality_proof(&bare, &proof, &validators, 0, &CommitRules::default()).is_ok());
+
+		// Not enough signatures.
+		header.set_seal(commit_seal(&tap, &header, 0, &[v1]));
+		let proof = finality_proof(&header).unwrap();
+		assert!(verify_finality_proof(&bare, &proof, &validators, 0, &CommitRules::default()).is_err());
+		// Proposals are not final.
+		header.set_seal(proposal_seal(&tap, &header, 0));
+		assert!(finality_proof(&header).is_err());
+		engine.stop();
+	}
+
+	#[test]
+	fn observer_follows_commit_without_voting() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v: Vec<_> = ["0", "1", "2", "3"].iter().map(|name| insert_and_unlock(&tap, name)).collect();
+		let mut params = test_params();
+		params.validators = Box::new(SimpleList::new(v.clone()));
+		params.observer_only = true;
+		let engine = new_engine(params);
+		// Observer uses a validator address.
+		engine.set_signer(tap.clone(), v[3], "3".into());
+		assert_eq!(engine.seals_internally(), Some(false));
+
+		let proposal = Some(H256::from(1));
+		engine.step();
+		for step in &[Step::Prevote, Step::Precommit] {
+			for peer in &v[0..3] {
+				vote_as(engine.as_ref(), &tap, *peer, 1, 0, *step, proposal);
+			}
+			assert!(engine.votes.round_voters(&VoteStep::new(1, 0, *step)).iter().all(|&(ref voter, _)| *voter != v[3]));
+		}
+		assert_eq!(engine.height.load(AtomicOrdering::SeqCst), 2);
+		assert!(engine.votes.round_voters(&VoteStep::new(2, 0, Step::Prevote)).is_empty());
+		engine.stop();
+	}
+
+	#[test]
+	fn skips_view_on_signer_rotation() {
+		let (spec, tap) = setup();
+		let engine = new_engine(test_params());
+		// Rotating before voting keeps the view.
+		insert_and_register(&tap, engine.as_ref(), "2");
+		let v1 = insert_and_register(&tap, engine.as_ref(), "1");
+		assert_eq!(engine.view.load(AtomicOrdering::SeqCst), 0);
+		let (_, seal) = propose_with(&spec, engine.as_ref(), v1);
+		assert!(seal != Seal::None);
+		assert!(engine.proposal.read().is_some());
+
+		let v0 = insert_and_register(&tap, engine.as_ref(), "0");
+		assert_eq!(engine.view.load(AtomicOrdering::SeqCst), 1);
+		assert!(engine.proposal.read().is_none());
+		engine.to_step(Step::Prevote);
+		// The new key votes only in the next view.
+		assert!(engine.votes.round_voters(&VoteStep::new(1, 0, Step::Prevote)).is_empty());
+		assert_eq!(engine.votes.round_voters(&VoteStep::new(1, 1, Step::Prevote)), vec![(v0, None)]);
+		engine.stop();
+	}
+
+	#[test]
+	fn reports_lifecycle() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let events = engine.subscribe_lifecycle();
+		assert_eq!(events.try_recv(), Ok(LifecycleEvent::Started));
+
+		// Not a validator.
+		insert_and_register(&tap, engine.as_ref(), "2");
+		assert!(events.try_recv().is_err());
+		let v1 = insert_and_register(&tap, engine.as_ref(), "1");
+		assert_eq!(events.try_recv(), Ok(LifecycleEvent::Participating(v1)));
+		engine.stop();
+		assert_eq!(events.try_recv(), Ok(LifecycleEvent::Stopped));
+
+		// Late subscribers receive the current state.
+		let late: Vec<_> = engine.subscribe_lifecycle().try_iter().collect();
+		assert_eq!(late, vec![LifecycleEvent::Started, LifecycleEvent::Participating(v1), LifecycleEvent::Stopped]);
+	}
+
+	#[test]
+	fn votes_with_proposing_disabled() {
+		let (spec, tap) = setup();
+		let engine = new_engine(test_params());
+		let v1 = insert_and_register(&tap, engine.as_ref(), "1");
+		engine.set_proposing_enabled(false);
+		let (_, seal) = propose_with(&spec, engine.as_ref(), v1);
+		assert_eq!(seal, Seal::None);
+
+		engine.to_step(Step::Prevote);
+		let voters = engine.votes.round_voters(&VoteStep::new(1, 0, Step::Prevote));
+		assert_eq!(voters, vec![(v1, None)]);
+
+		engine.set_proposing_enabled(true);
+		engine.to_step(Step::Propose);
+		let (_, seal) = propose_with(&spec, engine.as_ref(), v1);
+		assert!(seal != Seal::None);
+		engine.stop();
 	}
 
-	#[derive(Default)]
-	struct TestNotify {
-		messages: RwLock<Vec<Bytes>>,
+	#[test]
+	fn does_not_seal_with_changed_signer() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_register(&tap, engine.as_ref(), "1");
+		let hash = H256::from(1);
+		assert!(engine.sign_as(&v1, hash).is_some());
+		// Signer replaced after v1 was checked to be the proposer.
+		assert!(engine.sign_as(&v0, hash).is_none());
+		engine.stop();
 	}
 
-	impl ChainNotify for TestNotify {
-		fn broadcast(&self, data: Vec<u8>) {
-			self.messages.write().push(data);
+	#[test]
+	fn emergency_proposer_can_propose() {
+		let spec = Spec::new_test_tendermint();
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let engine = new_engine(test_params());
+		// "1" is the proposer at height 1 view 0.
+		let v0 = insert_and_register(&tap, engine.as_ref(), "0");
+		match propose_with(&spec, engine.as_ref(), v0) {
+			(_, Seal::None) => {},
+			_ => panic!("Only the proposer can seal."),
+		}
+		engine.stop();
+
+		let mut params = test_params();
+		params.emergency_proposers.insert(1, v0);
+		let engine = new_engine(params);
+		engine.set_signer(tap.clone(), v0, "0".into());
+		match propose_with(&spec, engine.as_ref(), v0) {
+			(_, Seal::Proposal(_)) => {},
+			_ => panic!("Emergency proposer should be able to seal."),
 		}
+		// Other heights and later views keep the rotation.
+		assert_eq!(engine.view_proposer(&Default::default(), 1, 1), v0);
+		assert_eq!(engine.view_proposer(&Default::default(), 1, 2), insert_and_unlock(&tap, "1"));
+		assert_eq!(engine.view_proposer(&Default::default(), 2, 0), v0);
+		assert_eq!(engine.view_proposer(&Default::default(), 3, 0), insert_and_unlock(&tap, "1"));
+		engine.stop();
 	}
 
 	#[test]
-	fn has_valid_metadata() {
-		let engine = Spec::new_test_tendermint().engine;
-		assert!(!engine.name().is_empty());
-		assert!(engine.version().major >= 1);
+	fn seal_submission() {
+		use client::BlockChainClient;
+
+		let tap = Arc::new(AccountProvider::transient_provider());
+		// Accounts for signing votes.
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let (client, _) = sealing_client(&tap);
+		let engine = client.engine();
+
+		// Propose
+		let proposal = Some(client.miner().pending_block().unwrap().header.bare_hash());
+		// Propose timeout
+		engine.step();
+
+		let h = 1;
+		let r = 0;
+
+		// Prevote.
+		vote_as(engine, &tap, v1, h, r, Step::Prevote, proposal);
+		vote_as(engine, &tap, v0, h, r, Step::Prevote, proposal);
+		vote_as(engine, &tap, v1, h, r, Step::Precommit, proposal);
+
+		assert_eq!(client.chain_info().best_block_number, 0);
+		// Last precommit.
+		vote_as(engine, &tap, v0, h, r, Step::Precommit, proposal);
+		assert_eq!(client.chain_info().best_block_number, 1);
 	}
 
 	#[test]
-	fn can_return_schedule() {
-		let engine = Spec::new_test_tendermint().engine;
-		let schedule = engine.schedule(10000000);
+	fn caches_view_proposer() {
+		let gets = Arc::new(AtomicUsize::new(0));
+		let mut params = test_params();
+		params.validators = Box::new(TestSet::from_list(vec![Address::from(1), Address::from(2)]).with_lookup_counter(gets.clone()));
+		let engine = new_engine(params);
 
-		assert!(schedule.stack_limit > 0);
+		let bh = H256::from(1);
+		let proposer = engine.view_proposer(&bh, 1, 0);
+		assert_eq!(engine.view_proposer(&bh, 1, 0), proposer);
+		assert_eq!(gets.load(AtomicOrdering::SeqCst), 1);
+
+		// Different round or different parent (possibly another validator set) is looked up again.
+		assert!(engine.view_proposer(&bh, 1, 1) != proposer);
+		engine.view_proposer(&H256::from(2), 1, 1);
+		assert_eq!(gets.load(AtomicOrdering::SeqCst), 3);
+		engine.stop();
 	}
 
 	#[test]
-	fn verification_fails_on_short_seal() {
-		let engine = Spec::new_test_tendermint().engine;
-		let header = Header::default();
+	fn checks_sender_against_message_epoch() {
+		use client::BlockChainClient;
 
-		let verify_result = engine.verify_block_basic(&header, None);
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let v2 = insert_and_unlock(&tap, "2");
+		let client = generate_dummy_client(3);
+		let mut params = test_params();
+		let epoch_parent = client.block_hash(BlockId::Number(1)).unwrap();
+		params.validators = Box::new(TestSet::from_list(vec![v0, v1]).with_parent_set(epoch_parent, vec![v2]));
+		let engine = new_engine(params);
+		engine.register_client(Arc::downgrade(&client));
+		assert_eq!(engine.height.load(AtomicOrdering::SeqCst), 4);
 
-		match verify_result {
-			Err(Error::Block(BlockError::InvalidSealArity(_))) => {},
-			Err(_) => { panic!("should be block seal-arity mismatch error (got {:?})", verify_result); },
-			_ => { panic!("Should be error, got Ok"); },
+		let message = |signer: Address, height: Height| {
+			let mi = message_info_rlp(&VoteStep::new(height, 0, Step::Prevote), Some(H256::from(height)));
+			message_full_rlp(&tap.sign(signer, None, mi.sha3()).unwrap().into(), &mi)
+		};
+
+		// Block 2 is validated by the epoch set.
+		assert!(engine.handle_message(&message(v2, 2)).is_ok());
+		match engine.handle_message(&message(v0, 2)) {
+			Err(Error::Engine(EngineError::NotAuthorized(a))) => assert_eq!(a, v0),
+			_ => panic!("v0 is not a validator at height 2."),
 		}
+		// Current height uses the current set.
+		assert!(engine.handle_message(&message(v0, 4)).is_ok());
+		match engine.handle_message(&message(v2, 4)) {
+			Err(Error::Engine(EngineError::NotAuthorized(a))) => assert_eq!(a, v2),
+			_ => panic!("v2 is not a validator at height 4."),
+		}
+		engine.stop();
 	}
 
 	#[test]
-	fn allows_correct_proposer() {
-		let (spec, tap) = setup();
-		let engine = spec.engine;
-
-		let mut parent_header: Header = Header::default();
-		parent_header.set_gas_limit(U256::from_str("222222").unwrap());
+	fn serves_seal_messages() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let engine = new_engine(test_params());
 
 		let mut header = Header::default();
 		header.set_number(1);
-		header.set_gas_limit(U256::from_str("222222").unwrap());
-		let validator = insert_and_unlock(&tap, "1");
-		header.set_author(validator);
-		let seal = proposal_seal(&tap, &header, 0);
-		header.set_seal(seal);
-		// Good proposer.
-		assert!(engine.verify_block_family(&header, &parent_header, None).is_ok());
-
-		let validator = insert_and_unlock(&tap, "0");
-		header.set_author(validator);
-		let seal = proposal_seal(&tap, &header, 0);
-		header.set_seal(seal);
-		// Bad proposer.
-		match engine.verify_block_family(&header, &parent_header, None) {
-			Err(Error::Engine(EngineError::NotProposer(_))) => {},
-			_ => panic!(),
-		}
-
-		let random = insert_and_unlock(&tap, "101");
-		header.set_author(random);
-		let seal = proposal_seal(&tap, &header, 0);
+		let seal = commit_seal(&tap, &header, 0, &[v0, v1]);
 		header.set_seal(seal);
-		// Not authority.
-		match engine.verify_block_family(&header, &parent_header, None) {
-			Err(Error::Engine(EngineError::NotAuthorized(_))) => {},
-			_ => panic!(),
-		};
+		assert_eq!(engine.consensus().unwrap().seal_messages(&header).map(|messages| messages.len()), Some(2));
 		engine.stop();
 	}
 
 	#[test]
-	fn seal_signatures_checking() {
-		let (spec, tap) = setup();
-		let engine = spec.engine;
-
-		let mut parent_header: Header = Header::default();
-		parent_header.set_gas_limit(U256::from_str("222222").unwrap());
-
-		let mut header = Header::default();
-		header.set_number(2);
-		header.set_gas_limit(U256::from_str("222222").unwrap());
-		let proposer = insert_and_unlock(&tap, "1");
-		header.set_author(proposer);
-		let mut seal = proposal_seal(&tap, &header, 0);
+	fn checks_block_stages() {
+		use block::Block;
+		use basic_types::Seal as BlockSeal;
 
-		let vote_info = message_info_rlp(&VoteStep::new(2, 0, Step::Precommit), Some(header.bare_hash()));
-		let signature1 = tap.sign(proposer, None, vote_info.sha3()).unwrap();
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let client = generate_dummy_client_with_spec_and_accounts(Spec::new_test_tendermint, None);
+		let engine = new_engine(test_params());
+		engine.register_client(Arc::downgrade(&client));
 
-		seal[1] = ::rlp::NULL_RLP.to_vec();
-		seal[2] = ::rlp::encode_list(&vec![H520::from(signature1.clone())]).to_vec();
-		header.set_seal(seal.clone());
+		let genesis = Spec::new_test_tendermint().genesis_header();
+		let block = |signers: &[Address]| {
+			let mut header = Header::default();
+			header.set_number(1);
+			header.set_parent_hash(genesis.hash());
+			header.set_gas_limit(genesis.gas_limit().clone());
+			let seal = commit_seal(&tap, &header, 0, signers);
+			header.set_seal(seal);
+			Block { header: header, transactions: Vec::new(), uncles: Vec::new() }.rlp_bytes(BlockSeal::With)
+		};
 
-		// One good signature is not enough.
-		match engine.verify_block_family(&header, &parent_header, None) {
-			Err(Error::Engine(EngineError::BadSealFieldSize(_))) => {},
-			_ => panic!(),
+		assert!(engine.check_block(&block(&[v1, v0])).is_ok());
+		match engine.check_block(&block(&[v0])) {
+			Err((VerificationStage::Family, Error::Engine(EngineError::BadSealFieldSize(_)))) => {},
+			_ => panic!("Under quorum commit should fail the family check."),
 		}
+		match engine.consensus().unwrap().check_block(&block(&[v0])) {
+			Err((VerificationStage::Family, _)) => {},
+			_ => panic!("Introspection should run the same checks."),
+		}
+		engine.stop();
+	}
 
-		let voter = insert_and_unlock(&tap, "0");
-		let signature0 = tap.sign(voter, None, vote_info.sha3()).unwrap();
+	#[test]
+	fn rejects_proposal_on_stale_ancestor() {
+		use client::BlockChainClient;
 
-		seal[2] = ::rlp::encode_list(&vec![H520::from(signature1.clone()), H520::from(signature0.clone())]).to_vec();
-		header.set_seal(seal.clone());
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let client = generate_dummy_client(5);
+		let mut params = test_params();
+		params.max_proposal_depth = Some(2);
+		let engine = new_engine(params);
+		engine.register_client(Arc::downgrade(&client));
 
-		assert!(engine.verify_block_family(&header, &parent_header, None).is_ok());
+		// "0" proposes at even heights.
+		let proposal = |number: BlockNumber| {
+			let parent = client.block_header(BlockId::Number(number - 1)).unwrap().decode();
+			let mut header = Header::default();
+			header.set_number(number);
+			header.set_parent_hash(parent.hash());
+			header.set_gas_limit(parent.gas_limit().clone());
+			header.set_author(v0);
+			let seal = proposal_seal(&tap, &header, 0);
+			header.set_seal(seal);
+			(header, parent)
+		};
 
-		let bad_voter = insert_and_unlock(&tap, "101");
-		let bad_signature = tap.sign(bad_voter, None, vote_info.sha3()).unwrap();
+		let (header, parent) = proposal(4);
+		assert!(engine.verify_block_family(&header, &parent, None).is_ok());
+		let (header, parent) = proposal(2);
+		match engine.verify_block_family(&header, &parent, None) {
+			Err(Error::Block(BlockError::RidiculousNumber(_))) => {},
+			_ => panic!("Proposal 4 blocks below the best block should be rejected."),
+		}
+		engine.stop();
+	}
 
-		seal[2] = ::rlp::encode_list(&vec![H520::from(signature1), H520::from(bad_signature)]).to_vec();
-		header.set_seal(seal);
+	#[test]
+	fn follows_block_reward_schedule() {
+		let mut params = test_params();
+		params.block_reward = U256::from(100);
+		let flat = new_engine(params);
+		assert_eq!(flat.block_reward_at(0), U256::from(100));
+		assert_eq!(flat.block_reward_at(1_000_000), U256::from(100));
+		flat.stop();
 
-		// One good and one bad signature.
-		match engine.verify_block_family(&header, &parent_header, None) {
-			Err(Error::Engine(EngineError::NotAuthorized(_))) => {},
-			_ => panic!(),
-		};
+		let mut params = test_params();
+		params.block_reward = U256::from(100);
+		params.block_reward_schedule = vec![(10, U256::from(50)), (20, U256::from(25))];
+		let engine = new_engine(params);
+		// Before the first step.
+		assert_eq!(engine.block_reward_at(0), U256::from(100));
+		assert_eq!(engine.block_reward_at(9), U256::from(100));
+		// Step boundaries.
+		assert_eq!(engine.block_reward_at(10), U256::from(50));
+		assert_eq!(engine.block_reward_at(19), U256::from(50));
+		assert_eq!(engine.block_reward_at(20), U256::from(25));
+		assert_eq!(engine.block_reward_at(u64::max_value()), U256::from(25));
 		engine.stop();
 	}
 
 	#[test]
-	fn can_generate_seal() {
-		let (spec, tap) = setup();
+	fn zero_reward_keeps_state() {
+		let spec = Spec::new_test_tendermint();
+		let author = Address::from(5);
+		let close = |block_reward: U256| {
+			let mut params = test_params();
+			params.block_reward = block_reward;
+			let engine = new_engine(params);
+			let db = spec.ensure_db_good(get_temp_state_db(), &Default::default()).unwrap();
+			let genesis_header = spec.genesis_header();
+			let last_hashes = Arc::new(vec![genesis_header.hash()]);
+			let b = OpenBlock::new(engine.as_ref(), Default::default(), false, db, &genesis_header, last_hashes, author, (3141562.into(), 31415620.into()), vec![]).unwrap();
+			let mut state = b.block().state().clone();
+			state.commit().unwrap();
+			let unclosed_root = state.root().clone();
+			let b = b.close_and_lock();
+			engine.stop();
+			(unclosed_root, b.header().state_root().clone(), b.block().state().balance(&author).unwrap())
+		};
 
-		let proposer = insert_and_register(&tap, spec.engine.as_ref(), "1");
+		let (unclosed_root, root, balance) = close(U256::zero());
+		assert_eq!(root, unclosed_root);
+		assert_eq!(balance, U256::zero());
 
-		let (b, seal) = propose_default(&spec, proposer);
-		assert!(b.lock().try_seal(spec.engine.as_ref(), seal).is_ok());
+		let (unclosed_root, root, balance) = close(U256::from(100));
+		assert!(root != unclosed_root);
+		assert_eq!(balance, U256::from(100));
 	}
 
 	#[test]
-	fn can_recognize_proposal() {
-		let (spec, tap) = setup();
+	fn schedule_follows_transitions() {
+		let engine = new_engine(test_params());
+		let schedule = engine.schedule(0);
+		assert_eq!(schedule.create_data_limit, usize::max_value());
+		assert_eq!(schedule.exp_byte_gas, 50);
+		assert!(schedule.no_empty && schedule.kill_empty);
+		engine.stop();
 
-		let proposer = insert_and_register(&tap, spec.engine.as_ref(), "1");
+		let mut params = test_params();
+		params.eip160_transition = 10;
+		params.eip161abc_transition = 20;
+		params.eip161d_transition = 20;
+		params.max_code_size = 24576;
+		let engine = new_engine(params);
+		let schedule = engine.schedule(9);
+		assert_eq!(schedule.create_data_limit, 24576);
+		assert_eq!(schedule.exp_byte_gas, 10);
+		assert!(!schedule.no_empty && !schedule.kill_empty);
+		assert_eq!(engine.schedule(10).exp_byte_gas, 50);
+		assert!(!engine.schedule(19).no_empty);
+		let schedule = engine.schedule(20);
+		assert!(schedule.no_empty && schedule.kill_empty);
+		engine.stop();
+	}
 
-		let (b, seal) = propose_default(&spec, proposer);
-		let sealed = b.lock().seal(spec.engine.as_ref(), seal).unwrap();
-		assert!(spec.engine.is_proposal(sealed.header()));
+	#[test]
+	fn holds_reward_until_maturity() {
+		use super::credit_matured_reward;
+
+		let mut state = get_temp_state();
+		let author = Address::from(5);
+		let reward = U256::from(100);
+		for number in 1..4 {
+			credit_matured_reward(&mut state, &author, number, 3, &reward).unwrap();
+			assert_eq!(state.balance(&author).unwrap(), U256::zero());
+		}
+		// Reward of block 1.
+		credit_matured_reward(&mut state, &author, 4, 3, &reward).unwrap();
+		assert_eq!(state.balance(&author).unwrap(), reward);
+		// Reward of block 2, while the one of block 5 goes to another author.
+		credit_matured_reward(&mut state, &Address::from(6), 5, 3, &reward).unwrap();
+		assert_eq!(state.balance(&author).unwrap(), U256::from(200));
+		assert_eq!(state.balance(&Address::from(6)).unwrap(), U256::zero());
 	}
 
 	#[test]
-	fn relays_messages() {
-		let (spec, tap) = setup();
-		let engine = spec.engine.clone();
+	fn keeps_held_reward_when_holder_is_touched() {
+		use super::{credit_matured_reward, pending_rewards_address};
+		use env_info::EnvInfo;
+		use types::transaction::{Transaction, Action};
 
-		let v0 = insert_and_unlock(&tap, "0");
-		let v1 = insert_and_register(&tap, engine.as_ref(), "1");
+		let engine = new_engine(test_params());
+		let mut state = get_temp_state();
+		let author = Address::from(5);
+		let reward = U256::from(100);
+		credit_matured_reward(&mut state, &author, 1, 3, &reward).unwrap();
+		state.commit().unwrap();
 
-		let h = 1;
-		let r = 0;
+		// Zero value transfers touch the receiver, which kills it if it is empty.
+		let mut info = EnvInfo::default();
+		info.gas_limit = 1_000_000.into();
+		let touch = Transaction {
+			nonce: 0.into(),
+			gas_price: 0.into(),
+			gas: 100_000.into(),
+			action: Action::Call(pending_rewards_address()),
+			value: 0.into(),
+			data: Vec::new(),
+		}.sign(&"".sha3().into(), None);
+		assert!(engine.schedule(info.number).kill_empty);
+		state.apply(&info, &*engine, &touch, false).unwrap();
 
-		// Propose
-		let (b, _) = propose_default(&spec, v1.clone());
-		let proposal = Some(b.header().bare_hash());
+		for number in 2..5 {
+			credit_matured_reward(&mut state, &author, number, 3, &reward).unwrap();
+		}
+		assert_eq!(state.balance(&author).unwrap(), reward);
+		engine.stop();
+	}
 
-		let client = generate_dummy_client(0);
-		let notify = Arc::new(TestNotify::default());
-		client.add_notify(notify.clone());
+	#[test]
+	fn does_not_fork_below_commit() {
+		let client = generate_dummy_client(5);
+		let engine = new_engine(test_params());
 		engine.register_client(Arc::downgrade(&client));
+		assert_eq!(engine.finalized_block(5), Some(5));
+		// A precommit quorum for block 6 moves the height before block 6 is imported.
+		engine.to_next_height(6);
+		assert_eq!(engine.height.load(AtomicOrdering::SeqCst), 7);
+		assert_eq!(engine.finalized_block(5), Some(5));
+		// Best blocks from peers are not final until their commit is verified.
+		assert_eq!(engine.finalized_block(9), Some(5));
+		engine.stop();
+	}
+
+	#[test]
+	fn rejects_gas_used_above_limit() {
+		let engine = new_engine(test_params());
+		let parent = Spec::new_test_tendermint().genesis_header();
+		let mut header = Header::default();
+		header.set_number(1);
+		header.set_parent_hash(parent.hash());
+		header.set_gas_limit(parent.gas_limit().clone());
+		header.set_gas_used(*parent.gas_limit() + U256::from(1));
+		match engine.verify_block_family(&header, &parent, None) {
+			Err(Error::Block(BlockError::TooMuchGasUsed(_))) => {},
+			_ => panic!("Block using more gas than its limit should be rejected."),
+		}
+		engine.stop();
+	}
 
-		let prevote_current = vote(engine.as_ref(), |mh| tap.sign(v0, None, mh).map(H520::from), h, r, Step::Prevote, proposal);
+	#[test]
+	fn rejects_reproposed_committed_block() {
+		use client::BlockChainClient;
 
-		let precommit_current = vote(engine.as_ref(), |mh| tap.sign(v0, None, mh).map(H520::from), h, r, Step::Precommit, proposal);
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let (client, _) = sealing_client(&tap);
+		let engine = client.engine();
 
-		let prevote_future = vote(engine.as_ref(), |mh| tap.sign(v0, None, mh).map(H520::from), h + 1, r, Step::Prevote, proposal);
+		let proposal = Some(client.miner().pending_block().unwrap().header.bare_hash());
+		engine.step();
+		vote_as(engine, &tap, v1, 1, 0, Step::Prevote, proposal);
+		vote_as(engine, &tap, v0, 1, 0, Step::Prevote, proposal);
+		vote_as(engine, &tap, v1, 1, 0, Step::Precommit, proposal);
+		vote_as(engine, &tap, v0, 1, 0, Step::Precommit, proposal);
+		assert_eq!(client.chain_info().best_block_number, 1);
 
-		// Relays all valid present and future messages.
-		assert!(notify.messages.read().contains(&prevote_current));
-		assert!(notify.messages.read().contains(&precommit_current));
-		assert!(notify.messages.read().contains(&prevote_future));
+		// Proposer signs the committed block again.
+		let mut header = client.block_header(BlockId::Number(1)).unwrap().decode();
+		let parent = client.block_header(BlockId::Number(0)).unwrap().decode();
+		let seal = proposal_seal(&tap, &header, 0);
+		header.set_seal(seal);
+		match engine.verify_block_family(&header, &parent, None) {
+			Err(Error::Block(BlockError::InvalidSeal)) => {},
+			_ => panic!("Re-proposed committed block should be rejected."),
+		}
 	}
 
 	#[test]
-	fn seal_submission() {
-		use ethkey::{Generator, Random};
-		use types::transaction::{Transaction, Action};
+	fn keeps_height_on_peer_commit_if_not_adopting() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let mut params = test_params();
+		params.adopt_peer_commits = false;
+		let engine = new_engine(params);
+
+		let mut header = Header::default();
+		header.set_number(1);
+		let seal = commit_seal(&tap, &header, 0, &[v0, v1]);
+		header.set_seal(seal);
+		*engine.step.write() = Step::Precommit;
+		assert!(!engine.is_proposal(&header));
+		assert_eq!(engine.height.load(AtomicOrdering::SeqCst), 1);
+		assert_eq!(*engine.step.read(), Step::Precommit);
+		assert_eq!(*engine.last_commit.read(), None);
+		engine.stop();
+	}
+
+	#[test]
+	fn adopts_peer_commit_before_local_commit() {
 		use client::BlockChainClient;
 
 		let tap = Arc::new(AccountProvider::transient_provider());
 		// Accounts for signing votes.
 		let v0 = insert_and_unlock(&tap, "0");
 		let v1 = insert_and_unlock(&tap, "1");
-		let client = generate_dummy_client_with_spec_and_accounts(Spec::new_test_tendermint, Some(tap.clone()));
+		let (client, _) = sealing_client(&tap);
 		let engine = client.engine();
 
-		client.miner().set_engine_signer(v1.clone(), "1".into()).unwrap();
-
-		let notify = Arc::new(TestNotify::default());
-		client.add_notify(notify.clone());
-		engine.register_client(Arc::downgrade(&client));
-
-		let keypair = Random.generate().unwrap();
-		let transaction = Transaction {
-			action: Action::Create,
-			value: U256::zero(),
-			data: "3331600055".from_hex().unwrap(),
-			gas: U256::from(100_000),
-			gas_price: U256::zero(),
-			nonce: U256::zero(),
-		}.sign(keypair.secret(), None);
-		client.miner().import_own_transaction(client.as_ref(), transaction.into()).unwrap();
-
 		// Propose
-		let proposal = Some(client.miner().pending_block().unwrap().header.bare_hash());
+		let mut header = client.miner().pending_block().unwrap().header;
+		let proposal = Some(header.bare_hash());
 		// Propose timeout
 		engine.step();
 
 		let h = 1;
 		let r = 0;
 
-		// Prevote.
-		vote(engine, |mh| tap.sign(v1, None, mh).map(H520::from), h, r, Step::Prevote, proposal);
-		vote(engine, |mh| tap.sign(v0, None, mh).map(H520::from), h, r, Step::Prevote, proposal);
-		vote(engine, |mh| tap.sign(v1, None, mh).map(H520::from), h, r, Step::Precommit, proposal);
+		vote_as(engine, &tap, v1, h, r, Step::Prevote, proposal);
+		vote_as(engine, &tap, v0, h, r, Step::Prevote, proposal);
+		vote_as(engine, &tap, v1, h, r, Step::Precommit, proposal);
+
+		// A peer assembled the commit first.
+		let precommit_hash = message_info_rlp(&VoteStep::new(h, r, Step::Precommit), proposal).sha3();
+		let precommits: Vec<_> = vec![v0, v1].into_iter().map(|v| H520::from(tap.sign(v, None, precommit_hash).unwrap())).collect();
+		header.set_seal(vec![
+			::rlp::encode(&r).to_vec(),
+			::rlp::NULL_RLP.to_vec(),
+			::rlp::encode_list(&precommits).to_vec()
+		]);
+		assert!(!engine.is_proposal(&header));
 
+		// Last precommit arrives after the peer commit and does not produce another seal.
+		vote_as(engine, &tap, v0, h, r, Step::Precommit, proposal);
 		assert_eq!(client.chain_info().best_block_number, 0);
-		// Last precommit.
-		vote(engine, |mh| tap.sign(v0, None, mh).map(H520::from), h, r, Step::Precommit, proposal);
-		assert_eq!(client.chain_info().best_block_number, 1);
+		// Seeing the same commit again is harmless.
+		assert!(!engine.is_proposal(&header));
 	}
 }
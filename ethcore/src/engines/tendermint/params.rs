@@ -16,12 +16,16 @@
 
 //! Tendermint specific parameters.
 
+use std::collections::{BTreeMap, HashSet, HashMap};
 use ethjson;
-use util::{U256, Address};
+use util::{U256, Address, Bytes};
+use transaction::{Transaction, Action};
 use time::Duration;
-use super::super::validator_set::{ValidatorSet, new_validator_set};
+use header::BlockNumber;
+use super::super::validator_set::{ValidatorSet, Timelocked, new_validator_set};
 use super::super::transition::Timeouts;
-use super::Step;
+use super::{Step, Height, View, MAX_RETAINED_MESSAGES};
+use super::verify::QuorumRatio;
 
 /// `Tendermint` params.
 pub struct TendermintParams {
@@ -33,8 +37,133 @@ pub struct TendermintParams {
 	pub timeouts: TendermintTimeouts,
 	/// Block reward.
 	pub block_reward: U256,
+	/// Block rewards paid from given blocks on, sorted by block number.
+	/// Blocks before the first step get `block_reward`.
+	pub block_reward_schedule: Vec<(BlockNumber, U256)>,
 	/// Namereg contract address.
 	pub registrar: Address,
+	/// Senders whose transactions are not allowed in blocks.
+	pub sender_blacklist: HashSet<Address>,
+	/// Rotation of proposers between views and heights.
+	pub primary_strategy: PrimaryStrategy,
+	/// Map the proposer nonce onto cumulative validator weights, so that validators propose in proportion to their weight.
+	pub weighted_proposer: bool,
+	/// Time to keep collecting precommits for the commit seal after reaching quorum.
+	/// Minimal seals would drop the extra precommits, so the two are mutually exclusive.
+	pub commit_grace_window: Option<Duration>,
+	/// How far below the best block a proposal can be built.
+	pub max_proposal_depth: Option<BlockNumber>,
+	/// Count a proposal as a prevote of its proposer for the proposed block.
+	///
+	/// This lets a view lock without waiting for the prevote of the proposer, but the proposal
+	/// and a conflicting prevote are not detected as a double vote, so a faulty proposer can be
+	/// counted for different blocks by different validators. Enable only if proposers are trusted
+	/// not to equivocate this way.
+	pub proposer_vote_counts: bool,
+	/// Number of blocks a block reward is held for before it is credited to the author.
+	pub reward_maturity: Option<BlockNumber>,
+	/// Require transactions in blocks to be ordered by nonce and then hash.
+	pub canonical_transaction_order: bool,
+	/// Transactions which have to be included in every block.
+	pub required_transactions: Vec<RequiredTransaction>,
+	/// Precommits included in commit seals.
+	pub seal_signatures: SealSignatures,
+	/// Maximum number of heights the validators may vote ahead of this node for it to sign proposals and votes.
+	/// A node further behind the network is catching up and would vote on stale state.
+	pub sync_distance: Option<usize>,
+	/// Save votes of the current height to the vote store and restore them after a restart.
+	pub persist_votes: bool,
+	/// Directory of the vote database, required to persist votes.
+	pub vote_store_path: Option<String>,
+	/// Maximum number of new validator messages which are applied in one slice.
+	/// Messages above the limit are queued for later slices, which bounds CPU use during gossip floods.
+	/// Signers are recovered on arrival, so that messages of others never take a place.
+	pub max_messages_per_slice: Option<usize>,
+	/// Duration of a message processing slice, queued messages are retried when the next one starts.
+	pub message_slice_interval: Duration,
+	/// Highest view the engine moves to on its own timeouts within a height.
+	/// Reaching it usually means the quorum is lost, the engine then waits for peers or a new block.
+	pub max_view: Option<View>,
+	/// Seconds a block timestamp can be ahead of the local clock.
+	pub allowed_future_time: u64,
+	/// Verify blocks and follow votes, but never propose or vote, even with a validator signer.
+	pub observer_only: bool,
+	/// New messages applied from one validator per second, further ones are queued for later slices.
+	/// Honest validators send a few messages per view, the limit only slows down floods.
+	pub messages_per_second: Option<usize>,
+	/// Number of views below the current one which keep their votes when a height does not commit.
+	/// Votes of lower views are dropped on a view change. If not set, past views are only bounded by `max_retained_messages`.
+	pub max_retained_views: Option<View>,
+	/// Number of votes kept above which the oldest votes of past views are dropped as new ones arrive.
+	/// Votes of the current and later views are always kept.
+	pub max_retained_messages: usize,
+	/// Number of first block where EIP-160 rules begin.
+	pub eip160_transition: BlockNumber,
+	/// Number of first block where EIP-161.abc begin.
+	pub eip161abc_transition: BlockNumber,
+	/// Number of first block where EIP-161.d begins.
+	pub eip161d_transition: BlockNumber,
+	/// Maximum size of deployed contract code.
+	pub max_code_size: u64,
+	/// Backup signing keys, mapped to the validator address they sign for.
+	pub signer_keys: HashMap<Address, Address>,
+	/// Maximum random delay of the rebroadcast on timeouts, which spreads the messages of validators timing out together.
+	pub broadcast_jitter: Duration,
+	/// Share of the validator weight needed to commit, checked when the engine is created.
+	pub quorum_ratio: QuorumRatio,
+	/// First block whose commit seal has to list the signers in increasing address order, never by default.
+	pub ordered_signers_transition: BlockNumber,
+	/// Validators which propose in the first view of the given heights, regardless of the rotation.
+	/// Last resort for restarting a chain whose designated proposers are permanently gone,
+	/// later views rotate as usual in case the emergency proposer is unavailable too.
+	pub emergency_proposers: BTreeMap<Height, Address>,
+	/// Whether a commit of the current height received from a peer moves the engine to the next height.
+	/// Otherwise the height is only left once the engine commits it through its own votes.
+	pub adopt_peer_commits: bool,
+}
+
+/// Proposer rotation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrimaryStrategy {
+	/// Validators propose in turn, by height and view.
+	RoundRobin,
+	/// Proposer is selected using a seed derived from the parent block hash and the view.
+	/// The seed is unknown before the parent is sealed, so proposers further ahead can not be targeted.
+	HashBased,
+}
+
+/// Precommit signatures included in commit seals.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SealSignatures {
+	/// All precommits collected for the block.
+	All,
+	/// Only as many precommits as needed for quorum, from the lowest signer addresses.
+	Minimal,
+}
+
+/// Call which has to be included in every block, such as a system contract update.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequiredTransaction {
+	/// Called contract.
+	pub to: Address,
+	/// Required prefix of the call data.
+	pub data_prefix: Bytes,
+}
+
+impl RequiredTransaction {
+	/// Check if `transaction` is a matching call.
+	pub fn is_satisfied_by(&self, transaction: &Transaction) -> bool {
+		transaction.action == Action::Call(self.to) && transaction.data.starts_with(&self.data_prefix)
+	}
+}
+
+impl From<ethjson::spec::RequiredTransaction> for RequiredTransaction {
+	fn from(r: ethjson::spec::RequiredTransaction) -> Self {
+		RequiredTransaction {
+			to: r.to.into(),
+			data_prefix: r.data_prefix.map_or_else(Vec::new, Into::into),
+		}
+	}
 }
 
 /// Base timeout of each step in ms.
@@ -44,6 +173,27 @@ pub struct TendermintTimeouts {
 	pub prevote: Duration,
 	pub precommit: Duration,
 	pub commit: Duration,
+	/// Factor applied to the timeouts for each view of a height, 1 keeps them constant.
+	pub view_change_backoff: u32,
+	/// Cap of timeouts grown by the backoff.
+	pub max_timeout: Option<Duration>,
+}
+
+impl TendermintTimeouts {
+	/// Timeout of `step` in `view`, grown by the backoff once for every view change since the last commit.
+	pub fn view_timeout(&self, step: &Step, view: View) -> Duration {
+		let cap = self.max_timeout.map_or(i64::max_value(), |max| max.num_milliseconds());
+		let mut ms = self.timeout(step).num_milliseconds();
+		if self.view_change_backoff > 1 {
+			for _ in 0..view {
+				if ms >= cap {
+					break;
+				}
+				ms = ms.saturating_mul(self.view_change_backoff as i64);
+			}
+		}
+		Duration::milliseconds(::std::cmp::min(ms, cap))
+	}
 }
 
 impl Default for TendermintTimeouts {
@@ -53,6 +203,8 @@ impl Default for TendermintTimeouts {
 			prevote: Duration::milliseconds(1000),
 			precommit: Duration::milliseconds(1000),
 			commit: Duration::milliseconds(1000),
+			view_change_backoff: 1,
+			max_timeout: None,
 		}
 	}
 }
@@ -80,17 +232,67 @@ fn to_duration(ms: ethjson::uint::Uint) -> Duration {
 impl From<ethjson::spec::TendermintParams> for TendermintParams {
 	fn from(p: ethjson::spec::TendermintParams) -> Self {
 		let dt = TendermintTimeouts::default();
+		let validators = new_validator_set(p.validators);
 		TendermintParams {
 			gas_limit_bound_divisor: p.gas_limit_bound_divisor.into(),
-			validators: new_validator_set(p.validators),
+			validators: match p.set_change_timelock {
+				Some(timelock) => Box::new(Timelocked::new(validators, timelock.into())),
+				None => validators,
+			},
 			timeouts: TendermintTimeouts {
 				propose: p.timeout_propose.map_or(dt.propose, to_duration),
 				prevote: p.timeout_prevote.map_or(dt.prevote, to_duration),
 				precommit: p.timeout_precommit.map_or(dt.precommit, to_duration),
 				commit: p.timeout_commit.map_or(dt.commit, to_duration),
+				view_change_backoff: p.view_change_backoff.map_or(dt.view_change_backoff, |b| { let b: usize = b.into(); b as u32 }),
+				max_timeout: p.max_timeout.map(to_duration),
 			},
 			block_reward: p.block_reward.map_or_else(U256::zero, Into::into),
+			block_reward_schedule: {
+				let mut schedule: Vec<(BlockNumber, U256)> = p.block_reward_schedule.map_or_else(Vec::new, |steps| {
+					steps.into_iter().map(|s| (s.from.into(), s.reward.into())).collect()
+				});
+				schedule.sort_by_key(|&(from, _)| from);
+				schedule
+			},
 			registrar: p.registrar.map_or_else(Address::new, Into::into),
+			sender_blacklist: p.sender_blacklist.map_or_else(HashSet::new, |list| list.into_iter().map(Into::into).collect()),
+			primary_strategy: match (p.primary_strategy, p.seeded_proposer) {
+				(Some(ethjson::spec::PrimaryStrategy::HashBased), _) | (None, Some(true)) => PrimaryStrategy::HashBased,
+				_ => PrimaryStrategy::RoundRobin,
+			},
+			weighted_proposer: p.weighted_proposer.unwrap_or(false),
+			commit_grace_window: p.commit_grace_window.map(to_duration),
+			max_proposal_depth: p.max_proposal_depth.map(Into::into),
+			proposer_vote_counts: p.proposer_vote_counts.unwrap_or(false),
+			reward_maturity: p.reward_maturity.map(Into::into),
+			canonical_transaction_order: p.canonical_transaction_order.unwrap_or(false),
+			required_transactions: p.required_transactions.map_or_else(Vec::new, |list| list.into_iter().map(Into::into).collect()),
+			seal_signatures: match p.minimal_commit_seal {
+				Some(true) => SealSignatures::Minimal,
+				_ => SealSignatures::All,
+			},
+			sync_distance: p.sync_distance.map(Into::into),
+			persist_votes: p.persist_votes.unwrap_or(false),
+			vote_store_path: p.vote_store_path,
+			max_messages_per_slice: p.max_messages_per_slice.map(Into::into),
+			message_slice_interval: p.message_slice_interval.map_or(Duration::milliseconds(100), to_duration),
+			max_view: p.max_view.map(Into::into),
+			allowed_future_time: p.allowed_future_time.map_or(::verification::ALLOWED_FUTURE_BLOCK_TIME, Into::into),
+			observer_only: p.observer_only.unwrap_or(false),
+			messages_per_second: p.messages_per_second.map(Into::into),
+			max_retained_views: p.max_retained_views.map(Into::into),
+			max_retained_messages: p.max_retained_messages.map_or(MAX_RETAINED_MESSAGES, Into::into),
+			eip160_transition: p.eip160_transition.map_or(0, Into::into),
+			eip161abc_transition: p.eip161abc_transition.map_or(0, Into::into),
+			eip161d_transition: p.eip161d_transition.map_or(0, Into::into),
+			max_code_size: p.max_code_size.map_or(u64::max_value(), Into::into),
+			signer_keys: p.signer_keys.map_or_else(HashMap::new, |keys| keys.into_iter().map(|(key, validator)| (key.into(), validator.into())).collect()),
+			broadcast_jitter: p.broadcast_jitter.map_or(Duration::milliseconds(0), to_duration),
+			quorum_ratio: p.quorum_ratio.map_or_else(QuorumRatio::default, |r| QuorumRatio { numerator: r.numerator.into(), denominator: r.denominator.into() }),
+			ordered_signers_transition: p.ordered_signers_transition.map_or(BlockNumber::max_value(), Into::into),
+			emergency_proposers: p.emergency_proposers.map_or_else(BTreeMap::new, |proposers| proposers.into_iter().map(|(height, proposer)| (height.into(), proposer.into())).collect()),
+			adopt_peer_commits: p.adopt_peer_commits.unwrap_or(true),
 		}
 	}
 }
@@ -0,0 +1,115 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Round context of Tendermint log messages.
+
+use std::cell::Cell;
+use std::fmt;
+use super::{Height, View};
+
+thread_local! {
+	static CURRENT: Cell<Option<RoundSpan>> = Cell::new(None);
+}
+
+#[cfg(test)]
+thread_local! {
+	static LAST_ENTERED: Cell<Option<RoundSpan>> = Cell::new(None);
+}
+
+/// Round which the current thread is working on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoundSpan {
+	/// Height of the round.
+	pub height: Height,
+	/// View of the round.
+	pub view: View,
+}
+
+impl fmt::Display for RoundSpan {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "height={} view={}", self.height, self.view)
+	}
+}
+
+impl RoundSpan {
+	/// Tag the log messages of this thread with the round until the guard is dropped.
+	pub fn enter(height: Height, view: View) -> SpanGuard {
+		let span = RoundSpan { height: height, view: view };
+		note_entered(span);
+		let previous = CURRENT.with(|current| {
+			let previous = current.get();
+			current.set(Some(span));
+			previous
+		});
+		SpanGuard { previous: previous }
+	}
+
+	/// Round of the innermost entered span.
+	pub fn current() -> Option<RoundSpan> {
+		CURRENT.with(|current| current.get())
+	}
+
+	/// Most recently entered span on this thread, even if already left.
+	#[cfg(test)]
+	pub fn last_entered() -> Option<RoundSpan> {
+		LAST_ENTERED.with(|last| last.get())
+	}
+}
+
+#[cfg(test)]
+fn note_entered(span: RoundSpan) {
+	LAST_ENTERED.with(|last| last.set(Some(span)));
+}
+
+#[cfg(not(test))]
+fn note_entered(_span: RoundSpan) {}
+
+/// Restores the enclosing span when dropped.
+pub struct SpanGuard {
+	previous: Option<RoundSpan>,
+}
+
+impl Drop for SpanGuard {
+	fn drop(&mut self) {
+		CURRENT.with(|current| current.set(self.previous));
+	}
+}
+
+/// Prefix of log messages, carrying the current round if there is one.
+pub fn log_prefix() -> String {
+	RoundSpan::current().map_or_else(String::new, |span| format!("[{}] ", span))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn nested_spans_restore_outer() {
+		assert_eq!(log_prefix(), "");
+		{
+			let _outer = RoundSpan::enter(42, 3);
+			assert_eq!(log_prefix(), "[height=42 view=3] ");
+			{
+				let _inner = RoundSpan::enter(43, 0);
+				assert_eq!(RoundSpan::current(), Some(RoundSpan { height: 43, view: 0 }));
+			}
+			assert_eq!(RoundSpan::current(), Some(RoundSpan { height: 42, view: 3 }));
+		}
+		assert_eq!(RoundSpan::current(), None);
+		assert_eq!(RoundSpan::last_entered(), Some(RoundSpan { height: 43, view: 0 }));
+	}
+}
@@ -0,0 +1,149 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Durable storage of Tendermint votes, so that they survive a restart.
+
+use util::*;
+use super::Height;
+
+/// Key of the lock, votes are keyed by their height followed by the message hash.
+const LOCK_KEY: &'static [u8] = b"lock";
+/// Length of a vote key.
+const VOTE_KEY_LEN: usize = 40;
+
+/// Votes and lock saved before a restart.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StoredVotes {
+	/// RLP encoded messages, in no particular order.
+	pub votes: Vec<Bytes>,
+	/// RLP encoded prevote which locked the node on a block.
+	pub lock: Option<Bytes>,
+}
+
+/// Durable storage of the votes of the current height and the lock.
+pub trait VoteStore: Send + Sync {
+	/// Save a single RLP encoded vote of `height`.
+	fn insert(&self, height: Height, vote: &[u8]);
+	/// Save the RLP encoded prevote the engine is locked on, or clear the lock.
+	fn set_lock(&self, lock: Option<&[u8]>);
+	/// Remove votes below `height`.
+	fn prune(&self, height: Height);
+	/// Everything saved so far.
+	fn load(&self) -> StoredVotes;
+}
+
+/// Store which keeps nothing.
+pub struct NoopStore;
+
+impl VoteStore for NoopStore {
+	fn insert(&self, _height: Height, _vote: &[u8]) {}
+	fn set_lock(&self, _lock: Option<&[u8]>) {}
+	fn prune(&self, _height: Height) {}
+	fn load(&self) -> StoredVotes { StoredVotes::default() }
+}
+
+/// Store backed by a key-value database, each vote is written once.
+pub struct DatabaseStore {
+	db: Arc<KeyValueDB>,
+}
+
+impl DatabaseStore {
+	/// Store in the default column of `db`, which should not be shared with other data.
+	pub fn new(db: Arc<KeyValueDB>) -> Self {
+		DatabaseStore { db: db }
+	}
+
+	/// Open or create the database at `path`.
+	pub fn open(path: &str) -> Result<Self, String> {
+		Database::open(&DatabaseConfig::default(), path).map(|db| DatabaseStore::new(Arc::new(db)))
+	}
+
+	fn write(&self, batch: DBTransaction) {
+		if let Err(e) = self.db.write(batch) {
+			warn!(target: "engine", "Could not save votes: {}", e);
+		}
+	}
+}
+
+fn vote_key(height: Height, vote: &[u8]) -> Vec<u8> {
+	let mut key = H64::from(height as u64).to_vec();
+	key.extend_from_slice(&vote.sha3());
+	key
+}
+
+impl VoteStore for DatabaseStore {
+	fn insert(&self, height: Height, vote: &[u8]) {
+		let mut batch = self.db.transaction();
+		batch.put(None, &vote_key(height, vote), vote);
+		self.write(batch);
+	}
+
+	fn set_lock(&self, lock: Option<&[u8]>) {
+		let mut batch = self.db.transaction();
+		match lock {
+			Some(lock) => batch.put(None, LOCK_KEY, lock),
+			None => batch.delete(None, LOCK_KEY),
+		}
+		self.write(batch);
+	}
+
+	fn prune(&self, height: Height) {
+		let first = H64::from(height as u64);
+		let mut batch = self.db.transaction();
+		for (key, _) in self.db.iter(None) {
+			if key.len() == VOTE_KEY_LEN && key[..8] < first[..] {
+				batch.delete(None, &key);
+			}
+		}
+		self.write(batch);
+	}
+
+	fn load(&self) -> StoredVotes {
+		let mut stored = StoredVotes::default();
+		for (key, value) in self.db.iter(None) {
+			if &*key == LOCK_KEY {
+				stored.lock = Some(value.to_vec());
+			} else if key.len() == VOTE_KEY_LEN {
+				stored.votes.push(value.to_vec());
+			}
+		}
+		stored
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use super::{VoteStore, DatabaseStore};
+
+	#[test]
+	fn saves_votes_and_lock() {
+		let store = DatabaseStore::new(Arc::new(in_memory(0)));
+		store.insert(1, b"vote1");
+		store.insert(2, b"vote2");
+		store.insert(2, b"vote2");
+		store.set_lock(Some(&b"lock"[..]));
+		let stored = store.load();
+		assert_eq!(stored.votes, vec![b"vote1".to_vec(), b"vote2".to_vec()]);
+		assert_eq!(stored.lock, Some(b"lock".to_vec()));
+
+		store.prune(2);
+		store.set_lock(None);
+		let stored = store.load();
+		assert_eq!(stored.votes, vec![b"vote2".to_vec()]);
+		assert_eq!(stored.lock, None);
+	}
+}
@@ -0,0 +1,799 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Standalone Tendermint commit verification, usable without a running engine.
+
+use crossbeam;
+use num_cpus;
+use util::*;
+use error::{Error, BlockError};
+use header::{Header, BlockNumber};
+use rlp::{UntrustedRlp, RlpStream};
+use ethkey::{self, recover, public_to_address};
+use engines::{EngineError, EpochChange};
+use super::super::validator_set::{ValidatorSet, SimpleList};
+use super::message::*;
+use super::Step;
+
+/// Seals with at least this many signatures are recovered on several threads.
+const PARALLEL_RECOVERY_THRESHOLD: usize = 32;
+
+/// Recover the signer of a single vote `signature` over `hash`.
+pub fn recover_signer(signature: &H520, hash: &H256) -> Result<Address, ethkey::Error> {
+	Ok(public_to_address(&recover(&signature.clone().into(), hash)?))
+}
+
+/// Recover the signers of `signatures` over `hash`, in order.
+/// Large seals are split between threads, which are all joined before returning.
+pub fn recover_signers(signatures: &[H520], hash: &H256) -> Result<Vec<Address>, ethkey::Error> {
+	if signatures.len() < PARALLEL_RECOVERY_THRESHOLD {
+		return signatures.iter().map(|s| recover_signer(s, hash)).collect();
+	}
+	let chunk_size = (signatures.len() + num_cpus::get() - 1) / num_cpus::get();
+	crossbeam::scope(|scope| {
+		let guards: Vec<_> = signatures.chunks(chunk_size)
+			.map(|chunk| scope.spawn(move || chunk.iter().map(|s| recover_signer(s, hash)).collect::<Result<Vec<_>, _>>()))
+			.collect();
+		guards.into_iter()
+			.map(|guard| guard.join())
+			.collect::<Result<Vec<_>, _>>()
+			.map(|chunks| chunks.into_iter().flat_map(|chunk| chunk).collect())
+	})
+}
+
+/// Share of the validator weight which commits a round.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuorumRatio {
+	/// Numerator of the ratio.
+	pub numerator: usize,
+	/// Denominator of the ratio.
+	pub denominator: usize,
+}
+
+impl Default for QuorumRatio {
+	fn default() -> Self {
+		QuorumRatio { numerator: 2, denominator: 3 }
+	}
+}
+
+impl QuorumRatio {
+	/// Ratios of at most one half let two disjoint quorums commit conflicting blocks,
+	/// ratios above one can never be reached.
+	pub fn is_safe(&self) -> bool {
+		self.denominator != 0 && self.numerator * 2 > self.denominator && self.numerator <= self.denominator
+	}
+
+	/// Smallest vote weight above the ratio of `validators`, or all of it for unanimity.
+	pub fn quorum(&self, validators: usize) -> usize {
+		if self.numerator >= self.denominator {
+			max(validators, 1)
+		} else {
+			validators * self.numerator / self.denominator + 1
+		}
+	}
+}
+
+/// BFT quorum of `validators`: the smallest vote weight above two thirds of the total.
+///
+/// Any two quorums overlap in more than `f = (validators - 1) / 3` votes, so they share an honest
+/// vote as long as at most `f` are faulty, while the `validators - f` honest ones still form a quorum.
+pub fn quorum_2f_plus_1(validators: usize) -> usize {
+	QuorumRatio::default().quorum(validators)
+}
+
+/// Check that the vote weight `n` is at least the quorum of `validators`.
+pub fn check_above_threshold(n: usize, validators: usize) -> Result<(), EngineError> {
+	check_above_ratio(n, validators, &QuorumRatio::default())
+}
+
+/// Check that the vote weight `n` is at least the quorum of `validators` under `ratio`.
+pub fn check_above_ratio(n: usize, validators: usize, ratio: &QuorumRatio) -> Result<(), EngineError> {
+	let quorum = ratio.quorum(validators);
+	if n >= quorum {
+		Ok(())
+	} else {
+		Err(EngineError::BadSealFieldSize(OutOfBounds {
+			min: Some(quorum),
+			max: None,
+			found: n
+		}))
+	}
+}
+
+/// Chain specific rules which commit seals are checked against.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitRules {
+	/// First block whose seal has to list the precommit signers in strictly increasing address order.
+	pub ordered_signers_transition: BlockNumber,
+	/// Backup signing keys, mapped to the validator address they sign for.
+	pub signer_keys: HashMap<Address, Address>,
+	/// Share of the validator weight which has to precommit.
+	pub quorum_ratio: QuorumRatio,
+}
+
+impl Default for CommitRules {
+	fn default() -> Self {
+		CommitRules {
+			ordered_signers_transition: BlockNumber::max_value(),
+			signer_keys: HashMap::new(),
+			quorum_ratio: QuorumRatio::default(),
+		}
+	}
+}
+
+impl CommitRules {
+	/// Whether the seal of block `number` has to list its signers in order.
+	pub fn ordered_signers(&self, number: BlockNumber) -> bool {
+		number >= self.ordered_signers_transition
+	}
+
+	/// Validator address which the signing `key` stands for.
+	pub fn validator_of(&self, key: Address) -> Address {
+		self.signer_keys.get(&key).cloned().unwrap_or(key)
+	}
+}
+
+/// Recover the precommit signers of a committed header and check that they are distinct validators.
+/// Precommits have to be cast in `epoch`.
+/// `known_signer` allows the caller to skip recovery for already seen messages.
+/// Returns the total weight of the signers.
+pub fn verify_precommits<F>(header: &Header, validators: &ValidatorSet, epoch: Epoch, known_signer: F) -> Result<usize, Error>
+	where F: Fn(&ConsensusMessage) -> Option<Address>
+{
+	verify_precommits_with_rules(header, validators, epoch, &CommitRules::default(), known_signer)
+}
+
+/// Like `verify_precommits`, with recovered signers mapped to validators through the signer keys of `rules`.
+/// From the transition in `rules` on, signers have to appear in increasing order of their validator addresses.
+pub fn verify_precommits_with_rules<F>(header: &Header, validators: &ValidatorSet, epoch: Epoch, rules: &CommitRules, known_signer: F) -> Result<usize, Error>
+	where F: Fn(&ConsensusMessage) -> Option<Address>
+{
+	let vote_step = VoteStep::new(header.number() as usize, consensus_view(header)?, Step::Precommit);
+	let precommit_hash = message_hash(epoch, vote_step.clone(), header.bare_hash());
+	let ref signatures_field = header.seal().get(2).expect("block went through verify_block_basic; block has .seal_fields() fields; qed");
+	let signatures = UntrustedRlp::new(signatures_field).iter().map(|rlp| rlp.as_val::<H520>()).collect::<Result<Vec<_>, _>>()?;
+	let known: Vec<_> = signatures.iter()
+		.map(|signature| known_signer(&ConsensusMessage {
+			signature: signature.clone(),
+			block_hash: Some(header.bare_hash()),
+			vote_step: vote_step.clone(),
+		}))
+		.collect();
+	let unknown: Vec<_> = signatures.iter().zip(known.iter()).filter(|&(_, k)| k.is_none()).map(|(s, _)| s.clone()).collect();
+	let mut recovered = recover_signers(&unknown, &precommit_hash)?.into_iter();
+	let ordered = rules.ordered_signers(header.number());
+	let mut origins = HashSet::new();
+	let mut last_signer: Option<Address> = None;
+	let mut weight = 0;
+	for known in known {
+		let address = known.or_else(|| recovered.next().map(|key| rules.validator_of(key))).expect("one signer recovered for each unknown signature; qed");
+		if !validators.contains(header.parent_hash(), &address) {
+			warn!(target: "engine", "verify_block_unordered: Seal of block {} is signed by {}, which is not one of the {} validators at its parent.",
+				header.number(), address, validators.count(header.parent_hash()));
+			return Err(EngineError::NotAuthorized(address.to_owned()).into());
+		}
+
+		if !origins.insert(address) {
+			warn!(target: "engine", "verify_block_unordered: Duplicate signature from {} on the seal.", address);
+			return Err(BlockError::InvalidSeal.into());
+		}
+		if ordered && last_signer.map_or(false, |last| last > address) {
+			warn!(target: "engine", "verify_block_unordered: Signature from {} is out of order on the seal.", address);
+			return Err(BlockError::InvalidSeal.into());
+		}
+		weight += validators.weight(header.parent_hash(), &address) as usize;
+		last_signer = Some(address);
+	}
+	Ok(weight)
+}
+
+/// Recover the validators of all precommit signers of a header committed in `epoch`, in seal order.
+pub fn precommit_signers(header: &Header, epoch: Epoch, rules: &CommitRules) -> Result<Vec<Address>, Error> {
+	let vote_step = VoteStep::new(header.number() as usize, consensus_view(header)?, Step::Precommit);
+	let precommit_hash = message_hash(epoch, vote_step, header.bare_hash());
+	let signatures_field = header.seal().get(2).ok_or(BlockError::InvalidSeal)?;
+	let signatures = UntrustedRlp::new(signatures_field).iter().map(|rlp| rlp.as_val::<H520>()).collect::<Result<Vec<_>, _>>()?;
+	Ok(recover_signers(&signatures, &precommit_hash)?.into_iter().map(|key| rules.validator_of(key)).collect())
+}
+
+/// Reconstruct the raw precommit messages of a committed header, in seal order.
+/// Each message can be checked independently using the epoch of the header.
+pub fn commit_messages(header: &Header) -> Result<Vec<Bytes>, Error> {
+	let vote_step = VoteStep::new(header.number() as usize, consensus_view(header)?, Step::Precommit);
+	let vote_info = message_info_rlp(&vote_step, Some(header.bare_hash()));
+	let signatures_field = header.seal().get(2).ok_or(BlockError::InvalidSeal)?;
+	UntrustedRlp::new(signatures_field).iter()
+		.map(|rlp| -> Result<Bytes, Error> { Ok(message_full_rlp(&rlp.as_val()?, &vote_info)) })
+		.collect()
+}
+
+/// Finality proof of a committed header: RLP list of the commit view and the precommit signatures.
+pub fn finality_proof(header: &Header) -> Result<Bytes, Error> {
+	let seal = header.seal();
+	if seal.len() != 3 || seal[1] != ::rlp::NULL_RLP.to_vec() {
+		return Err(BlockError::InvalidSeal.into());
+	}
+	let mut stream = RlpStream::new_list(2);
+	stream.append_raw(&seal[0], 1).append_raw(&seal[2], 1);
+	Ok(stream.out())
+}
+
+/// Check that `proof` shows `header` committed by `validators` in `epoch`.
+/// The seal of `header` is not used, so a node can trust the block without its consensus history.
+pub fn verify_finality_proof(header: &Header, proof: &[u8], validators: &ValidatorSet, epoch: Epoch, rules: &CommitRules) -> Result<(), Error> {
+	let proof = UntrustedRlp::new(proof);
+	if proof.item_count()? != 2 {
+		return Err(BlockError::InvalidSeal.into());
+	}
+	let mut committed = header.clone();
+	committed.set_seal(vec![proof.at(0)?.as_raw().to_vec(), ::rlp::NULL_RLP.to_vec(), proof.at(1)?.as_raw().to_vec()]);
+	verify_commit(&committed, validators, epoch, rules)
+}
+
+/// Check that `a` and `b` are conflicting votes of one validator in `epoch`, returns the offender.
+///
+/// Both messages have to be signed by the same member of `validators` as of the parent block `bh`,
+/// in the same height, view and step, for different blocks.
+pub fn verify_equivocation(validators: &ValidatorSet, bh: &H256, epoch: Epoch, a: &ConsensusMessage, b: &ConsensusMessage) -> Result<Address, Error> {
+	if a.vote_step != b.vote_step {
+		return Err(EngineError::InsufficientProof(format!("Votes are cast in different rounds: {:?} and {:?}.", a.vote_step, b.vote_step)).into());
+	}
+	if a.block_hash == b.block_hash {
+		return Err(EngineError::InsufficientProof("Votes do not conflict.".into()).into());
+	}
+	let offender = a.verify(epoch)?;
+	let other = b.verify(epoch)?;
+	if offender != other {
+		return Err(EngineError::InsufficientProof(format!("Votes are signed by {} and {}.", offender, other)).into());
+	}
+	if !validators.contains(bh, &offender) {
+		return Err(EngineError::NotAuthorized(offender).into());
+	}
+	Ok(offender)
+}
+
+/// Recover the validator which signed the proposal seal of `header` in `epoch`.
+/// Commit seals do not carry the proposal signature.
+pub fn proposal_signer(header: &Header, epoch: Epoch, rules: &CommitRules) -> Option<Address> {
+	ConsensusMessage::new_proposal(header).ok()
+		.and_then(|proposal| proposal.verify(epoch).ok())
+		.map(|key| rules.validator_of(key))
+}
+
+/// Check if the validator `address` signed the proposal or a precommit of `header` in `epoch`.
+pub fn is_signed_by(header: &Header, epoch: Epoch, rules: &CommitRules, address: &Address) -> bool {
+	proposal_signer(header, epoch, rules).as_ref() == Some(address)
+		|| precommit_signers(header, epoch, rules).map_or(false, |signers| signers.contains(address))
+}
+
+/// Verify that the header is a commit finalized in `epoch` by the quorum of `validators` required by `rules`.
+pub fn verify_commit(header: &Header, validators: &ValidatorSet, epoch: Epoch, rules: &CommitRules) -> Result<(), Error> {
+	let seal = header.seal();
+	if seal.len() != 3 {
+		return Err(BlockError::InvalidSealArity(Mismatch { expected: 3, found: seal.len() }).into());
+	}
+	if seal[1] != ::rlp::NULL_RLP.to_vec() {
+		trace!(target: "engine", "verify_commit: Block {} is a proposal.", header.number());
+		return Err(BlockError::InvalidSeal.into());
+	}
+	let weight = verify_precommits_with_rules(header, validators, epoch, rules, |_| None)?;
+	check_above_ratio(weight, validators.total_weight(header.parent_hash()) as usize, &rules.quorum_ratio)?;
+	Ok(())
+}
+
+/// Verify a contiguous chain of committed headers, starting with `initial_validators`.
+///
+/// Validator set transitions are carried forward using `transitions`, which is consulted
+/// for epoch ends that can be determined from the header alone.
+/// Stops at the first header which does not verify.
+pub fn verify_proof_chain(headers: &[Header], initial_validators: SimpleList, transitions: &ValidatorSet, rules: &CommitRules) -> Result<(), Error> {
+	verify_chain_from(None, headers, initial_validators, 0, transitions, rules).map(|_| ())
+}
+
+/// Verify `headers` as children of `parent`, committed by `validators` in `epoch` until the next transition.
+/// Returns the validators and epoch of the block after the last header.
+fn verify_chain_from<'a>(mut parent: Option<&'a Header>, headers: &'a [Header], mut validators: SimpleList, mut epoch: Epoch, transitions: &ValidatorSet, rules: &CommitRules) -> Result<(SimpleList, Epoch), Error> {
+	for header in headers {
+		if let Some(parent) = parent {
+			if *header.parent_hash() != parent.hash() {
+				return Err(BlockError::InvalidParentHash(Mismatch { expected: parent.hash(), found: header.parent_hash().clone() }).into());
+			}
+		}
+		verify_commit(header, &validators, epoch, rules)?;
+		if let EpochChange::Yes(next) = transitions.is_epoch_end(header, None, None) {
+			trace!(target: "engine", "verify_proof_chain: Epoch {} starts after block {}.", next, header.number());
+			validators = transitions.epoch_set(header, &[])?.1;
+			epoch = next;
+		}
+		parent = Some(header);
+	}
+	Ok((validators, epoch))
+}
+
+/// Snapshot of the validator set and its epoch after a known block.
+///
+/// Checkpoints start from a block trusted out of band, for example the genesis of the chain spec.
+/// Later checkpoints are derived from an earlier one by verifying the commits of the blocks in between.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+	header: Header,
+	validators: SimpleList,
+	epoch: Epoch,
+}
+
+impl Checkpoint {
+	/// Create a checkpoint at `header`, which has to be the block `trusted_hash`.
+	/// `validators` and `epoch` are trusted along with the hash.
+	pub fn trusted(header: Header, validators: SimpleList, epoch: Epoch, trusted_hash: &H256) -> Result<Self, Error> {
+		if header.hash() != *trusted_hash {
+			return Err(EngineError::InsufficientProof(format!("Checkpoint block {} is not the trusted block {}.", header.hash(), trusted_hash)).into());
+		}
+		Ok(Checkpoint {
+			header: header,
+			validators: validators,
+			epoch: epoch,
+		})
+	}
+
+	/// Checkpoint after `headers`, which have to extend this checkpoint and be committed by its validators.
+	/// Validator set transitions on the way are followed using `transitions`.
+	pub fn extend(&self, headers: &[Header], transitions: &ValidatorSet, rules: &CommitRules) -> Result<Self, Error> {
+		let last = headers.last().ok_or_else(|| EngineError::InsufficientProof("No blocks to extend the checkpoint with.".into()))?;
+		let (validators, epoch) = verify_chain_from(Some(&self.header), headers, self.validators.clone(), self.epoch, transitions, rules)?;
+		Ok(Checkpoint {
+			header: last.clone(),
+			validators: validators,
+			epoch: epoch,
+		})
+	}
+
+	/// Number of the checkpointed block.
+	pub fn number(&self) -> BlockNumber {
+		self.header.number()
+	}
+
+	/// Validators of the blocks after the checkpointed one.
+	pub fn validators(&self) -> &SimpleList {
+		&self.validators
+	}
+
+	/// Epoch of the blocks after the checkpointed one.
+	pub fn epoch(&self) -> Epoch {
+		self.epoch
+	}
+}
+
+/// Verify a historical commit without state, using the closest checkpoint below it.
+/// Only valid as long as the validator set did not change between the checkpoint and `header`.
+pub fn verify_with_checkpoints(header: &Header, checkpoints: &[Checkpoint], rules: &CommitRules) -> Result<(), Error> {
+	let checkpoint = checkpoints.iter()
+		.filter(|c| c.number() < header.number())
+		.max_by_key(|c| c.number())
+		.ok_or_else(|| EngineError::InsufficientProof(format!("No checkpoint below block {}.", header.number())))?;
+	trace!(target: "engine", "verify_with_checkpoints: Using checkpoint {} for block {}.", checkpoint.number(), header.number());
+	verify_commit(header, checkpoint.validators(), checkpoint.epoch(), rules)
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use header::{Header, BlockNumber};
+	use account_provider::AccountProvider;
+	use error::{Error, BlockError};
+	use engines::EngineError;
+	use engines::validator_set::{SimpleList, TestSet};
+	use super::super::message::*;
+	use super::super::Step;
+	use super::*;
+
+	fn insert_and_unlock(tap: &Arc<AccountProvider>, acc: &str) -> Address {
+		let addr = tap.insert_account(acc.sha3().into(), acc).unwrap();
+		tap.unlock_account_permanently(addr, acc.into()).unwrap();
+		addr
+	}
+
+	fn committed_header(tap: &Arc<AccountProvider>, parent: &H256, number: BlockNumber, signers: &[Address]) -> Header {
+		committed_header_in_epoch(tap, parent, number, signers, 0)
+	}
+
+	fn committed_header_in_epoch(tap: &Arc<AccountProvider>, parent: &H256, number: BlockNumber, signers: &[Address], epoch: Epoch) -> Header {
+		let mut header = Header::default();
+		header.set_number(number);
+		header.set_parent_hash(parent.clone());
+		let view = 0usize;
+		let vote_info = message_info_rlp(&VoteStep::new(number as usize, view, Step::Precommit), Some(header.bare_hash()));
+		let precommits: Vec<_> = signers.iter().map(|s| H520::from(tap.sign(*s, None, vote_hash(epoch, &vote_info)).unwrap())).collect();
+		header.set_seal(vec![
+			::rlp::encode(&view).to_vec(),
+			::rlp::NULL_RLP.to_vec(),
+			::rlp::encode_list(&precommits).to_vec()
+		]);
+		header
+	}
+
+	#[test]
+	fn verifies_chain_with_transition() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let v2 = insert_and_unlock(&tap, "2");
+		let initial = SimpleList::new(vec![v0, v1]);
+		let transitions = TestSet::from_list(vec![v0, v1]).with_transition(2, vec![v1, v2]);
+
+		let h1 = committed_header(&tap, &H256::default(), 1, &[v0, v1]);
+		let h2 = committed_header(&tap, &h1.hash(), 2, &[v0, v1]);
+		let h3 = committed_header_in_epoch(&tap, &h2.hash(), 3, &[v1, v2], 1);
+		let chain = vec![h1, h2, h3];
+
+		assert!(verify_proof_chain(&chain, initial.clone(), &transitions, &CommitRules::default()).is_ok());
+
+		// New set signing in the old epoch.
+		let replayed = vec![chain[0].clone(), chain[1].clone(), committed_header(&tap, &chain[1].hash(), 3, &[v1, v2])];
+		assert!(verify_proof_chain(&replayed, initial.clone(), &transitions, &CommitRules::default()).is_err());
+
+		// Without the transition the last block is signed by an unknown validator.
+		let no_transition = TestSet::from_list(vec![v0, v1]);
+		match verify_proof_chain(&chain, initial.clone(), &no_transition, &CommitRules::default()) {
+			Err(Error::Engine(EngineError::NotAuthorized(a))) => assert_eq!(a, v2),
+			_ => panic!(),
+		}
+
+		// Broken link.
+		let unlinked = vec![chain[0].clone(), chain[2].clone()];
+		assert!(verify_proof_chain(&unlinked, initial, &transitions, &CommitRules::default()).is_err());
+	}
+
+	#[test]
+	fn identifies_unknown_seal_signer() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let v2 = insert_and_unlock(&tap, "2");
+		let validators = SimpleList::new(vec![v0, v1]);
+
+		let header = committed_header(&tap, &H256::default(), 1, &[v1, v2]);
+		let err = verify_precommits(&header, &validators, 0, |_| None).unwrap_err();
+		assert!(format!("{}", err).contains(&format!("{}", v2)));
+		match err {
+			Error::Engine(EngineError::NotAuthorized(a)) => assert_eq!(a, v2),
+			_ => panic!("Unknown signer should be reported."),
+		}
+	}
+
+	#[test]
+	fn rejects_precommits_for_other_hash() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let validators = SimpleList::new(vec![v0, v1]);
+
+		// Seal of a different block at the same height.
+		let other = committed_header(&tap, &H256::from(1), 1, &[v1, v0]);
+		let mut header = committed_header(&tap, &H256::default(), 1, &[v1, v0]);
+		assert!(verify_commit(&header, &validators, 0, &CommitRules::default()).is_ok());
+		header.set_seal(other.seal().to_vec());
+		match verify_commit(&header, &validators, 0, &CommitRules::default()) {
+			Err(Error::Engine(EngineError::NotAuthorized(_))) => {},
+			_ => panic!("Precommits for another block should be rejected."),
+		}
+	}
+
+	#[test]
+	fn requires_ordered_signers() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let v2 = insert_and_unlock(&tap, "2");
+		let validators = SimpleList::new(vec![v0, v1, v2]);
+		assert!(v1 < v0 && v0 < v2);
+		let rules = CommitRules { ordered_signers_transition: 2, ..CommitRules::default() };
+
+		let ordered = committed_header(&tap, &H256::default(), 2, &[v1, v0, v2]);
+		assert!(verify_commit(&ordered, &validators, 0, &rules).is_ok());
+
+		// Seals before the transition are accepted in any order.
+		let unordered = committed_header(&tap, &H256::default(), 1, &[v0, v1, v2]);
+		assert!(verify_commit(&unordered, &validators, 0, &rules).is_ok());
+		let unordered = committed_header(&tap, &H256::default(), 2, &[v0, v1, v2]);
+		match verify_commit(&unordered, &validators, 0, &rules) {
+			Err(Error::Block(BlockError::InvalidSeal)) => {},
+			_ => panic!(),
+		}
+
+		for number in 1..3 {
+			let duplicate = committed_header(&tap, &H256::default(), number, &[v1, v0, v0]);
+			match verify_commit(&duplicate, &validators, 0, &rules) {
+				Err(Error::Block(BlockError::InvalidSeal)) => {},
+				_ => panic!(),
+			}
+		}
+	}
+
+	#[test]
+	fn requires_quorum_of_rules() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let v2 = insert_and_unlock(&tap, "2");
+		let v3 = insert_and_unlock(&tap, "3");
+		let validators = SimpleList::new(vec![v0, v1, v2, v3]);
+		let rules = CommitRules { quorum_ratio: QuorumRatio { numerator: 3, denominator: 4 }, ..CommitRules::default() };
+
+		let header = committed_header(&tap, &H256::default(), 1, &[v0, v1, v2]);
+		assert!(verify_commit(&header, &validators, 0, &CommitRules::default()).is_ok());
+		match verify_commit(&header, &validators, 0, &rules) {
+			Err(Error::Engine(EngineError::BadSealFieldSize(_))) => {},
+			_ => panic!(),
+		}
+		let proof = finality_proof(&header).unwrap();
+		assert!(verify_finality_proof(&header, &proof, &validators, 0, &rules).is_err());
+
+		let header = committed_header(&tap, &H256::default(), 1, &[v0, v1, v2, v3]);
+		assert!(verify_commit(&header, &validators, 0, &rules).is_ok());
+		let proof = finality_proof(&header).unwrap();
+		assert!(verify_finality_proof(&header, &proof, &validators, 0, &rules).is_ok());
+	}
+
+	#[test]
+	fn maps_backup_keys_to_validators() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let backup = insert_and_unlock(&tap, "2");
+		let validators = SimpleList::new(vec![v0, v1]);
+		let mut rules = CommitRules::default();
+		rules.signer_keys.insert(backup, v1);
+
+		let header = committed_header(&tap, &H256::default(), 1, &[backup, v0]);
+		assert!(verify_commit(&header, &validators, 0, &rules).is_ok());
+		assert_eq!(precommit_signers(&header, 0, &rules).unwrap(), vec![v1, v0]);
+		assert!(is_signed_by(&header, 0, &rules, &v1));
+		assert!(!is_signed_by(&header, 0, &rules, &backup));
+		match verify_commit(&header, &validators, 0, &CommitRules::default()) {
+			Err(Error::Engine(EngineError::NotAuthorized(address))) => assert_eq!(address, backup),
+			_ => panic!(),
+		}
+
+		// A backup key can not sign twice for its validator.
+		let duplicate = committed_header(&tap, &H256::default(), 1, &[backup, v1]);
+		match verify_commit(&duplicate, &validators, 0, &rules) {
+			Err(Error::Block(BlockError::InvalidSeal)) => {},
+			_ => panic!(),
+		}
+	}
+
+	#[test]
+	fn recovers_large_seal_in_order() {
+		use ethkey::{Generator, Random, sign};
+		use super::{recover_signers, recover_signer, PARALLEL_RECOVERY_THRESHOLD};
+
+		let hash = H256::from(1);
+		let keys: Vec<_> = (0..(PARALLEL_RECOVERY_THRESHOLD * 2 + 1)).map(|_| Random.generate().unwrap()).collect();
+		let signatures: Vec<_> = keys.iter().map(|k| H520::from(sign(k.secret(), &hash).unwrap())).collect();
+		let serial: Vec<_> = signatures.iter().map(|s| recover_signer(s, &hash).unwrap()).collect();
+		assert_eq!(serial, keys.iter().map(|k| k.address()).collect::<Vec<_>>());
+		assert_eq!(recover_signers(&signatures, &hash).unwrap(), serial);
+	}
+
+	#[test]
+	fn verifies_large_seal_like_serial_path() {
+		use ethkey::{Generator, Random, sign};
+
+		let mut keys: Vec<_> = (0..100).map(|_| Random.generate().unwrap()).collect();
+		keys.sort_by_key(|k| k.address());
+		let validators = SimpleList::new(keys.iter().map(|k| k.address()).collect());
+		let mut header = Header::default();
+		header.set_number(1);
+		let vote_info = message_info_rlp(&VoteStep::new(1, 0, Step::Precommit), Some(header.bare_hash()));
+		let hash = vote_hash(0, &vote_info);
+		let mut precommits: Vec<_> = keys.iter().map(|k| H520::from(sign(k.secret(), &hash).unwrap())).collect();
+		let seal = |precommits: &Vec<H520>| vec![
+			::rlp::encode(&0usize).to_vec(),
+			::rlp::NULL_RLP.to_vec(),
+			::rlp::encode_list(precommits).to_vec()
+		];
+		header.set_seal(seal(&precommits));
+
+		let serial: Vec<_> = precommits.iter().map(|s| recover_signer(s, &hash).unwrap()).collect();
+		assert_eq!(precommit_signers(&header, 0, &CommitRules::default()).unwrap(), serial);
+		assert_eq!(verify_precommits(&header, &validators, 0, |_| None).unwrap(), 100);
+		assert!(verify_commit(&header, &validators, 0, &CommitRules::default()).is_ok());
+
+		let last = precommits[99].clone();
+		precommits[98] = last;
+		header.set_seal(seal(&precommits));
+		match verify_commit(&header, &validators, 0, &CommitRules::default()) {
+			Err(Error::Block(BlockError::InvalidSeal)) => {},
+			_ => panic!(),
+		}
+	}
+
+	#[test]
+	fn quorum_tolerates_faults() {
+		use super::quorum_2f_plus_1;
+
+		let expected = [(1, 1), (2, 2), (3, 3), (4, 3), (5, 4), (6, 5), (7, 5), (8, 6), (9, 7), (10, 7)];
+		for &(validators, quorum) in expected.iter() {
+			assert_eq!(quorum_2f_plus_1(validators), quorum, "quorum of {} validators", validators);
+			let faulty = (validators - 1) / 3;
+			// Honest validators alone reach the quorum.
+			assert!(quorum <= validators - faulty);
+			// Two quorums share more than the faulty validators.
+			assert!(2 * quorum - validators > faulty);
+			assert!(check_above_threshold(quorum, validators).is_ok());
+			assert!(check_above_threshold(quorum - 1, validators).is_err());
+		}
+	}
+
+	#[test]
+	fn quorum_follows_ratio() {
+		use super::{quorum_2f_plus_1, check_above_ratio, QuorumRatio};
+
+		let two_thirds = QuorumRatio::default();
+		for validators in 1..11 {
+			assert_eq!(two_thirds.quorum(validators), quorum_2f_plus_1(validators));
+		}
+		let three_quarters = QuorumRatio { numerator: 3, denominator: 4 };
+		let expected = [(1, 1), (3, 3), (4, 4), (7, 6), (8, 7), (100, 76)];
+		for &(validators, quorum) in expected.iter() {
+			assert_eq!(three_quarters.quorum(validators), quorum, "quorum of {} validators", validators);
+			assert!(check_above_ratio(quorum, validators, &three_quarters).is_ok());
+			assert!(check_above_ratio(quorum - 1, validators, &three_quarters).is_err());
+		}
+		let unanimity = QuorumRatio { numerator: 1, denominator: 1 };
+		assert_eq!(unanimity.quorum(4), 4);
+
+		assert!(two_thirds.is_safe() && three_quarters.is_safe() && unanimity.is_safe());
+		for &(numerator, denominator) in [(1, 2), (1, 3), (0, 0), (5, 4)].iter() {
+			assert!(!QuorumRatio { numerator: numerator, denominator: denominator }.is_safe());
+		}
+	}
+
+	#[test]
+	fn verifies_equivocation() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let validators = SimpleList::new(vec![v0, v1]);
+		let bh = H256::default();
+		let vote = |signer: Address, view: usize, block_hash: Option<H256>| {
+			let vote_step = VoteStep::new(1, view, Step::Prevote);
+			let vote_info = message_info_rlp(&vote_step, block_hash);
+			ConsensusMessage {
+				signature: H520::from(tap.sign(signer, None, vote_hash(0, &vote_info)).unwrap()),
+				vote_step: vote_step,
+				block_hash: block_hash,
+			}
+		};
+
+		let first = vote(v0, 0, Some(H256::from(1)));
+		let second = vote(v0, 0, Some(H256::from(2)));
+		assert_eq!(verify_equivocation(&validators, &bh, 0, &first, &second).unwrap(), v0);
+		assert_eq!(verify_equivocation(&validators, &bh, 0, &first, &vote(v0, 0, None)).unwrap(), v0);
+
+		// Same vote twice.
+		assert!(verify_equivocation(&validators, &bh, 0, &first, &first.clone()).is_err());
+		// Different signers.
+		assert!(verify_equivocation(&validators, &bh, 0, &first, &vote(v1, 0, Some(H256::from(2)))).is_err());
+		// Different views.
+		assert!(verify_equivocation(&validators, &bh, 0, &first, &vote(v0, 1, Some(H256::from(2)))).is_err());
+		// Not a validator.
+		match verify_equivocation(&SimpleList::new(vec![v1]), &bh, 0, &first, &second) {
+			Err(Error::Engine(EngineError::NotAuthorized(address))) => assert_eq!(address, v0),
+			_ => panic!(),
+		}
+	}
+
+	#[test]
+	fn finds_signers() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let v2 = insert_and_unlock(&tap, "2");
+
+		let h1 = committed_header(&tap, &H256::default(), 1, &[v1, v0]);
+		let h2 = committed_header(&tap, &h1.hash(), 2, &[v1, v2]);
+		let mut h3 = committed_header(&tap, &h2.hash(), 3, &[v0]);
+		// The author is not signed.
+		h3.set_author(v2);
+		let mut h4 = Header::default();
+		h4.set_number(4);
+		h4.set_parent_hash(h3.hash());
+		h4.set_author(v0);
+		let vote_info = message_info_rlp(&VoteStep::new(4, 0, Step::Propose), Some(h4.bare_hash()));
+		let signature = H520::from(tap.sign(v2, None, vote_info.sha3()).unwrap());
+		h4.set_seal(vec![::rlp::encode(&0usize).to_vec(), ::rlp::encode(&signature).to_vec(), ::rlp::EMPTY_LIST_RLP.to_vec()]);
+		assert_eq!(precommit_signers(&h2, 0, &CommitRules::default()).unwrap(), vec![v1, v2]);
+		assert_eq!(proposal_signer(&h4, 0, &CommitRules::default()), Some(v2));
+		assert_eq!(proposal_signer(&h3, 0, &CommitRules::default()), None);
+
+		let signed = |v: &Address| [&h1, &h2, &h3, &h4].iter().filter(|h| is_signed_by(h, 0, &CommitRules::default(), v)).map(|h| h.number()).collect::<Vec<_>>();
+		assert_eq!(signed(&v0), vec![1, 3]);
+		assert_eq!(signed(&v1), vec![1, 2]);
+		// Proposer of the last block.
+		assert_eq!(signed(&v2), vec![2, 4]);
+	}
+
+	#[test]
+	fn reconstructs_commit_messages() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let validators = SimpleList::new(vec![v0, v1]);
+		let header = committed_header(&tap, &H256::default(), 1, &[v1, v0]);
+
+		let signers: Vec<_> = commit_messages(&header).unwrap().iter()
+			.map(|m| {
+				let message: ConsensusMessage = UntrustedRlp::new(m).as_val().unwrap();
+				assert_eq!(message.vote_step, VoteStep::new(1, 0, Step::Precommit));
+				assert_eq!(message.block_hash, Some(header.bare_hash()));
+				message.verify(0).unwrap()
+			})
+			.collect();
+		assert_eq!(signers, vec![v1, v0]);
+		assert!(check_above_threshold(signers.len(), validators.count(header.parent_hash())).is_ok());
+	}
+
+	#[test]
+	fn verifies_with_checkpoint() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let v0 = insert_and_unlock(&tap, "0");
+		let v1 = insert_and_unlock(&tap, "1");
+		let v2 = insert_and_unlock(&tap, "2");
+		let old = SimpleList::new(vec![v0, v1]);
+
+		let rules = CommitRules::default();
+		let mut trusted = Header::default();
+		trusted.set_number(10);
+		// The first checkpoint is anchored to a hash known out of band.
+		assert!(Checkpoint::trusted(trusted.clone(), old.clone(), 0, &H256::default()).is_err());
+		let first = Checkpoint::trusted(trusted.clone(), old.clone(), 0, &trusted.hash()).unwrap();
+
+		// The set changes after block 15.
+		let transitions = TestSet::from_list(vec![v0, v1]).with_transition(15, vec![v1, v2]);
+		let mut chain: Vec<Header> = Vec::new();
+		for number in 11..21 {
+			let parent = chain.last().map_or_else(|| trusted.hash(), Header::hash);
+			let header = if number > 15 {
+				committed_header_in_epoch(&tap, &parent, number, &[v1, v2], 1)
+			} else {
+				committed_header(&tap, &parent, number, &[v1, v0])
+			};
+			chain.push(header);
+		}
+		// Later checkpoints have to be reached through commits of the known sets.
+		assert!(first.extend(&chain[1..], &transitions, &rules).is_err());
+		let no_transition = TestSet::from_list(vec![v0, v1]);
+		assert!(first.extend(&chain, &no_transition, &rules).is_err());
+		let second = first.extend(&chain, &transitions, &rules).unwrap();
+		assert_eq!(second.number(), 20);
+		assert_eq!(second.epoch(), 1);
+		let checkpoints = vec![first, second];
+
+		assert!(verify_with_checkpoints(&committed_header(&tap, &H256::default(), 15, &[v1, v0]), &checkpoints, &CommitRules::default()).is_ok());
+		assert!(verify_with_checkpoints(&committed_header_in_epoch(&tap, &H256::default(), 25, &[v1, v2], 1), &checkpoints, &CommitRules::default()).is_ok());
+		assert!(verify_with_checkpoints(&committed_header(&tap, &H256::default(), 25, &[v1, v2]), &checkpoints, &CommitRules::default()).is_err());
+		match verify_with_checkpoints(&committed_header_in_epoch(&tap, &H256::default(), 25, &[v1, v0], 1), &checkpoints, &CommitRules::default()) {
+			Err(Error::Engine(EngineError::NotAuthorized(a))) => assert_eq!(a, v0),
+			_ => panic!(),
+		}
+		match verify_with_checkpoints(&committed_header(&tap, &H256::default(), 5, &[v1, v0]), &checkpoints, &CommitRules::default()) {
+			Err(Error::Engine(EngineError::InsufficientProof(_))) => {},
+			_ => panic!(),
+		}
+		assert!(verify_with_checkpoints(&committed_header(&tap, &H256::default(), 10, &[v1, v0]), &checkpoints, &CommitRules::default()).is_err());
+	}
+}
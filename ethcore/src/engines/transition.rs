@@ -18,7 +18,7 @@
 
 use std::sync::Weak;
 use time::Duration;
-use io::{IoContext, IoHandler, TimerToken};
+use io::{IoContext, IoHandler, IoError, TimerToken};
 use engines::Engine;
 
 /// Timeouts lookup
@@ -49,8 +49,27 @@ impl <S> TransitionHandler<S> where S: Sync + Send + Clone {
 /// Timer token representing the consensus step timeouts.
 pub const ENGINE_TIMEOUT_TOKEN: TimerToken = 23;
 
+/// Attempts made to reach the io service before giving up.
+pub const IO_SEND_ATTEMPTS: usize = 3;
+
+/// Call `f` until it succeeds or `IO_SEND_ATTEMPTS` are made.
+/// Retries are immediate, the callers run on the io and consensus threads which must not block.
+pub fn retry_io<F, T>(mut f: F) -> Result<T, IoError> where F: FnMut() -> Result<T, IoError> {
+	let mut attempt = 1;
+	loop {
+		match f() {
+			Ok(t) => return Ok(t),
+			Err(ref e) if attempt < IO_SEND_ATTEMPTS => {
+				debug!(target: "engine", "Io service unreachable ({}), retrying.", e);
+				attempt += 1;
+			},
+			Err(e) => return Err(e),
+		}
+	}
+}
+
 fn set_timeout<S: Sync + Send + Clone>(io: &IoContext<S>, timeout: Duration) {
-	io.register_timer_once(ENGINE_TIMEOUT_TOKEN, timeout.num_milliseconds() as u64)
+	retry_io(|| io.register_timer_once(ENGINE_TIMEOUT_TOKEN, timeout.num_milliseconds() as u64))
 		.unwrap_or_else(|e| warn!(target: "engine", "Failed to set consensus step timeout: {}.", e))
 }
 
@@ -92,6 +92,14 @@ impl ValidatorSet for ValidatorContract {
 		self.validators.count_with_caller(bh, caller)
 	}
 
+	fn weight(&self, bh: &H256, address: &Address) -> u64 {
+		self.validators.weight(bh, address)
+	}
+
+	fn total_weight(&self, bh: &H256) -> u64 {
+		self.validators.total_weight(bh)
+	}
+
 	fn report_malicious(&self, address: &Address, block: BlockNumber, proof: Bytes) {
 		match self.provider.report_malicious(&*self.transact(), *address, block.into(), proof).wait() {
 			Ok(_) => warn!(target: "engine", "Reported malicious validator {}", address),
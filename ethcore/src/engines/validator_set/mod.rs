@@ -19,9 +19,11 @@
 #[cfg(test)]
 mod test;
 mod simple_list;
+mod weighted_list;
 mod safe_contract;
 mod contract;
 mod multi;
+mod timelock;
 
 use std::sync::Weak;
 use ids::BlockId;
@@ -33,16 +35,32 @@ use header::{Header, BlockNumber};
 #[cfg(test)]
 pub use self::test::TestSet;
 pub use self::simple_list::SimpleList;
+pub use self::weighted_list::WeightedList;
 use self::contract::ValidatorContract;
 use self::safe_contract::ValidatorSafeContract;
 use self::multi::Multi;
+pub use self::timelock::Timelocked;
 
 use super::Call;
 
+/// Nobody can sign for the zero address, a configured list which includes it would stall on its turns.
+fn is_signable(address: &Address) -> bool {
+	if address.is_zero() {
+		warn!(target: "engine", "Ignoring the zero address in the validator set.");
+		false
+	} else {
+		true
+	}
+}
+
 /// Creates a validator set from spec.
 pub fn new_validator_set(spec: ValidatorSpec) -> Box<ValidatorSet> {
 	match spec {
-		ValidatorSpec::List(list) => Box::new(SimpleList::new(list.into_iter().map(Into::into).collect())),
+		ValidatorSpec::List(list) => Box::new(SimpleList::new(list.into_iter().map(Into::into).filter(is_signable).collect())),
+		ValidatorSpec::WeightedList(weights) => Box::new(WeightedList::new(weights.into_iter()
+			.map(|(a, w)| (a.into(), w.into()))
+			.filter(|&(ref a, _)| is_signable(a))
+			.collect())),
 		ValidatorSpec::SafeContract(address) => Box::new(ValidatorSafeContract::new(address.into())),
 		ValidatorSpec::Contract(address) => Box::new(ValidatorContract::new(address.into())),
 		ValidatorSpec::Multi(sequence) => Box::new(
@@ -114,6 +132,17 @@ pub trait ValidatorSet: Send + Sync {
 	/// Returns the current number of validators.
 	fn count_with_caller(&self, parent_block_hash: &H256, caller: &Call) -> usize;
 
+	/// Voting weight of a validator, zero if it is not one.
+	/// Validators have equal weight unless the set overrides this.
+	fn weight(&self, parent_block_hash: &H256, address: &Address) -> u64 {
+		if self.contains(parent_block_hash, address) { 1 } else { 0 }
+	}
+
+	/// Sum of the weights of all validators.
+	fn total_weight(&self, parent_block_hash: &H256) -> u64 {
+		self.count(parent_block_hash) as u64
+	}
+
 	/// Notifies about malicious behaviour.
 	fn report_malicious(&self, _validator: &Address, _block: BlockNumber, _proof: Bytes) {}
 	/// Notifies about benign misbehaviour.
@@ -121,3 +150,29 @@ pub trait ValidatorSet: Send + Sync {
 	/// Allows blockchain state access.
 	fn register_contract(&self, _client: Weak<Client>) {}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+	use std::collections::BTreeMap;
+	use util::Address;
+	use ethjson;
+	use ethjson::spec::ValidatorSet as ValidatorSpec;
+	use super::new_validator_set;
+
+	#[test]
+	fn filters_zero_address_from_spec() {
+		let a1 = Address::from_str("cd1722f3947def4cf144679da39c4c32bdc35681").unwrap();
+		let list = new_validator_set(ValidatorSpec::List(vec![Address::default().into(), a1.into(), Address::default().into()]));
+		assert!(!list.contains(&Default::default(), &Address::default()));
+		assert_eq!(list.count(&Default::default()), 1);
+		assert_eq!(list.get(&Default::default(), 0), a1);
+
+		let mut weights = BTreeMap::new();
+		weights.insert(Address::default().into(), ethjson::uint::Uint(1.into()));
+		weights.insert(a1.into(), ethjson::uint::Uint(1.into()));
+		let weighted = new_validator_set(ValidatorSpec::WeightedList(weights));
+		assert!(!weighted.contains(&Default::default(), &Address::default()));
+		assert_eq!(weighted.count(&Default::default()), 1);
+	}
+}
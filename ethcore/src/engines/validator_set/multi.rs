@@ -132,6 +132,16 @@ impl ValidatorSet for Multi {
 			.map_or_else(usize::max_value, |set| set.count_with_caller(bh, caller))
 	}
 
+	fn weight(&self, bh: &H256, address: &Address) -> u64 {
+		self.correct_set(BlockId::Hash(*bh))
+			.map_or(0, |set| set.weight(bh, address))
+	}
+
+	fn total_weight(&self, bh: &H256) -> u64 {
+		self.correct_set(BlockId::Hash(*bh))
+			.map_or_else(u64::max_value, |set| set.total_weight(bh))
+	}
+
 	fn report_malicious(&self, validator: &Address, block: BlockNumber, proof: Bytes) {
 		self.correct_set_by_number(block).1.report_malicious(validator, block, proof);
 	}
@@ -17,16 +17,20 @@
 /// Used for Engine testing.
 
 use std::str::FromStr;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
-use util::{Arc, Bytes, H256, Address, HeapSizeOf};
+use util::{Arc, RwLock, Bytes, H256, Address, HeapSizeOf};
 
-use engines::Call;
+use engines::{Call, EpochChange};
 use header::{Header, BlockNumber};
 use super::{ValidatorSet, SimpleList};
 
-/// Set used for testing with a single validator.
+/// Set used for testing, with a single validator unless configured otherwise.
 pub struct TestSet {
-	validator: SimpleList,
+	validators: Arc<RwLock<SimpleList>>,
+	parent_sets: HashMap<H256, SimpleList>,
+	transition: Option<(BlockNumber, SimpleList)>,
+	lookups: Arc<AtomicUsize>,
 	last_malicious: Arc<AtomicUsize>,
 	last_benign: Arc<AtomicUsize>,
 }
@@ -34,16 +38,57 @@ pub struct TestSet {
 impl TestSet {
 	pub fn new(last_malicious: Arc<AtomicUsize>, last_benign: Arc<AtomicUsize>) -> Self {
 		TestSet {
-			validator: SimpleList::new(vec![Address::from_str("7d577a597b2742b498cb5cf0c26cdcd726d39e6e").unwrap()]),
+			validators: Arc::new(RwLock::new(SimpleList::new(vec![Address::from_str("7d577a597b2742b498cb5cf0c26cdcd726d39e6e").unwrap()]))),
+			parent_sets: HashMap::new(),
+			transition: None,
+			lookups: Default::default(),
 			last_malicious: last_malicious,
 			last_benign: last_benign,
 		}
 	}
+
+	/// Set of the given validators.
+	pub fn from_list(validators: Vec<Address>) -> Self {
+		TestSet::shared(Arc::new(RwLock::new(SimpleList::new(validators))))
+	}
+
+	/// Set following changes made to `validators` while it is in use.
+	pub fn shared(validators: Arc<RwLock<SimpleList>>) -> Self {
+		TestSet {
+			validators: validators,
+			..TestSet::new(Default::default(), Default::default())
+		}
+	}
+
+	/// Use `validators` for the children of `parent`.
+	pub fn with_parent_set(mut self, parent: H256, validators: Vec<Address>) -> Self {
+		self.parent_sets.insert(parent, SimpleList::new(validators));
+		self
+	}
+
+	/// Signal a change to `validators` in block `number`, starting epoch 1.
+	pub fn with_transition(mut self, number: BlockNumber, validators: Vec<Address>) -> Self {
+		self.transition = Some((number, SimpleList::new(validators)));
+		self
+	}
+
+	/// Count validator lookups by index in `lookups`.
+	pub fn with_lookup_counter(mut self, lookups: Arc<AtomicUsize>) -> Self {
+		self.lookups = lookups;
+		self
+	}
+
+	fn set(&self, bh: &H256) -> SimpleList {
+		match self.parent_sets.get(bh) {
+			Some(set) => set.clone(),
+			None => self.validators.read().clone(),
+		}
+	}
 }
 
 impl HeapSizeOf for TestSet {
 	fn heap_size_of_children(&self) -> usize {
-		self.validator.heap_size_of_children()
+		self.validators.read().heap_size_of_children()
 	}
 }
 
@@ -52,30 +97,37 @@ impl ValidatorSet for TestSet {
 		Box::new(|_, _| Err("Test set doesn't require calls.".into()))
 	}
 
-	fn is_epoch_end(&self, _header: &Header, _block: Option<&[u8]>, _receipts: Option<&[::receipt::Receipt]>)
-		-> ::engines::EpochChange
+	fn is_epoch_end(&self, header: &Header, _block: Option<&[u8]>, _receipts: Option<&[::receipt::Receipt]>)
+		-> EpochChange
 	{
-		::engines::EpochChange::No
+		match self.transition {
+			Some((number, _)) if number == header.number() => EpochChange::Yes(1),
+			_ => EpochChange::No,
+		}
 	}
 
 	fn epoch_proof(&self, _header: &Header, _caller: &Call) -> Result<Vec<u8>, String> {
 		Ok(Vec::new())
 	}
 
-	fn epoch_set(&self, _header: &Header, _: &[u8]) -> Result<(u64, SimpleList), ::error::Error> {
-		Ok((0, self.validator.clone()))
+	fn epoch_set(&self, header: &Header, _: &[u8]) -> Result<(u64, SimpleList), ::error::Error> {
+		match self.transition {
+			Some((number, ref next)) if number == header.number() => Ok((1, next.clone())),
+			_ => Ok((0, self.validators.read().clone())),
+		}
 	}
 
 	fn contains_with_caller(&self, bh: &H256, address: &Address, _: &Call) -> bool {
-		self.validator.contains(bh, address)
+		self.set(bh).contains(bh, address)
 	}
 
 	fn get_with_caller(&self, bh: &H256, nonce: usize, _: &Call) -> Address {
-		self.validator.get(bh, nonce)
+		self.lookups.fetch_add(1, AtomicOrdering::SeqCst);
+		self.set(bh).get(bh, nonce)
 	}
 
-	fn count_with_caller(&self, _bh: &H256, _: &Call) -> usize {
-		1
+	fn count_with_caller(&self, bh: &H256, _: &Call) -> usize {
+		self.set(bh).count(bh)
 	}
 
 	fn report_malicious(&self, _validator: &Address, block: BlockNumber, _proof: Bytes) {
@@ -0,0 +1,243 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+/// Validator set whose changes take effect only after a number of blocks.
+
+use std::sync::{Arc, Weak};
+use engines::{Call, EngineError, EpochChange, Unsure};
+use util::{Bytes, H256, Address, RwLock, HashMap};
+use rlp::UntrustedRlp;
+use ids::BlockId;
+use header::{BlockNumber, Header};
+use receipt::Receipt;
+use client::{Client, BlockChainClient};
+use super::{ValidatorSet, SimpleList};
+
+/// Resolved lookups kept before the cache is cleared.
+const MAX_CACHED_ANCESTORS: usize = 1024;
+
+/// Delays every change of the inner set by `timelock` committed blocks,
+/// so that a bare quorum can not alter the set in a single block.
+///
+/// A change signalled by the inner set in block `n` takes effect after block `n + timelock`,
+/// for lookups as well as for epoch ends. Finding the signalling block needs the chain,
+/// so until a client is registered lookups use the genesis set and epoch ends are unknown.
+pub struct Timelocked {
+	set: Box<ValidatorSet>,
+	timelock: u64,
+	client: RwLock<Option<Weak<Client>>>,
+	/// Block whose state determines the validators for children of a block.
+	locked_parents: RwLock<HashMap<H256, H256>>,
+}
+
+impl Timelocked {
+	pub fn new(set: Box<ValidatorSet>, timelock: u64) -> Self {
+		Timelocked {
+			set: set,
+			timelock: timelock,
+			client: RwLock::new(None),
+			locked_parents: RwLock::new(HashMap::new()),
+		}
+	}
+
+	fn client(&self) -> Option<Arc<Client>> {
+		self.client.read().as_ref().and_then(Weak::upgrade)
+	}
+
+	/// Walk `distance` blocks back from `bh`, stopping at genesis.
+	/// None if a block on the way is unknown.
+	fn ancestor(client: &Client, bh: H256, distance: u64) -> Option<H256> {
+		let mut ancestor = bh;
+		for _ in 0..distance {
+			let header = match client.block_header(BlockId::Hash(ancestor)) {
+				Some(header) => header,
+				None => return None,
+			};
+			if header.number() == 0 {
+				break;
+			}
+			ancestor = header.parent_hash();
+		}
+		Some(ancestor)
+	}
+
+	/// Block whose state determines the validators for children of `bh`.
+	fn locked_parent(&self, bh: &H256) -> H256 {
+		if self.timelock == 0 {
+			return *bh;
+		}
+		if let Some(parent) = self.locked_parents.read().get(bh) {
+			return *parent;
+		}
+		let client = match self.client() {
+			Some(client) => client,
+			None => return H256::default(),
+		};
+		match Timelocked::ancestor(&*client, *bh, self.timelock) {
+			Some(parent) => {
+				let mut cache = self.locked_parents.write();
+				if cache.len() >= MAX_CACHED_ANCESTORS {
+					cache.clear();
+				}
+				cache.insert(*bh, parent);
+				parent
+			},
+			// Blocks which are not imported yet are not cached, they may become known later.
+			None => *bh,
+		}
+	}
+
+	/// Header of the block whose change signal takes effect after `header`, with its body and receipts.
+	fn signalling_block(&self, header: &Header) -> Result<Option<(Header, Bytes, Vec<Receipt>)>, String> {
+		let client = self.client().ok_or_else(|| "No client to find the signalling block with.".to_owned())?;
+		if header.number() < self.timelock {
+			return Ok(None);
+		}
+		let hash = Timelocked::ancestor(&*client, *header.parent_hash(), self.timelock - 1)
+			.ok_or_else(|| format!("Ancestor of block {} is unknown.", header.number()))?;
+		let block = client.block(BlockId::Hash(hash)).ok_or_else(|| format!("Block {} is unknown.", hash))?;
+		let receipts = client.block_receipts(&hash).ok_or_else(|| format!("Receipts of block {} are unknown.", hash))?;
+		let receipts = UntrustedRlp::new(&receipts).as_list().map_err(|e| format!("Invalid receipts of block {}: {}", hash, e))?;
+		Ok(Some((block.decode_header(), block.into_inner(), receipts)))
+	}
+}
+
+impl ValidatorSet for Timelocked {
+	fn default_caller(&self, block_id: BlockId) -> Box<Call> {
+		self.set.default_caller(block_id)
+	}
+
+	fn is_epoch_end(&self, header: &Header, block: Option<&[u8]>, receipts: Option<&[::receipt::Receipt]>)
+		-> EpochChange
+	{
+		if self.timelock == 0 {
+			return self.set.is_epoch_end(header, block, receipts);
+		}
+		match self.signalling_block(header) {
+			Ok(Some((signal, block, receipts))) => self.set.is_epoch_end(&signal, Some(&block), Some(&receipts)),
+			Ok(None) => EpochChange::No,
+			Err(e) => {
+				warn!(target: "engine", "Timelocked set can not tell if block {} ends an epoch: {}", header.number(), e);
+				EpochChange::Unsure(Unsure::NeedsBoth)
+			},
+		}
+	}
+
+	fn epoch_proof(&self, header: &Header, caller: &Call) -> Result<Vec<u8>, String> {
+		if self.timelock == 0 {
+			return self.set.epoch_proof(header, caller);
+		}
+		match self.signalling_block(header)? {
+			Some((signal, _, _)) => self.set.epoch_proof(&signal, &*self.set.default_caller(BlockId::Hash(signal.hash()))),
+			None => Err(format!("Block {} is within the timelock of genesis.", header.number())),
+		}
+	}
+
+	fn epoch_set(&self, header: &Header, proof: &[u8]) -> Result<(u64, SimpleList), ::error::Error> {
+		if self.timelock == 0 {
+			return self.set.epoch_set(header, proof);
+		}
+		match self.signalling_block(header).map_err(EngineError::InsufficientProof)? {
+			Some((signal, _, _)) => self.set.epoch_set(&signal, proof),
+			None => Err(EngineError::InsufficientProof(format!("Block {} is within the timelock of genesis.", header.number())).into()),
+		}
+	}
+
+	fn contains_with_caller(&self, bh: &H256, address: &Address, caller: &Call) -> bool {
+		self.set.contains_with_caller(&self.locked_parent(bh), address, caller)
+	}
+
+	fn get_with_caller(&self, bh: &H256, nonce: usize, caller: &Call) -> Address {
+		self.set.get_with_caller(&self.locked_parent(bh), nonce, caller)
+	}
+
+	fn count_with_caller(&self, bh: &H256, caller: &Call) -> usize {
+		self.set.count_with_caller(&self.locked_parent(bh), caller)
+	}
+
+	fn weight(&self, bh: &H256, address: &Address) -> u64 {
+		self.set.weight(&self.locked_parent(bh), address)
+	}
+
+	fn total_weight(&self, bh: &H256) -> u64 {
+		self.set.total_weight(&self.locked_parent(bh))
+	}
+
+	fn report_malicious(&self, validator: &Address, block: BlockNumber, proof: Bytes) {
+		self.set.report_malicious(validator, block, proof);
+	}
+
+	fn report_benign(&self, validator: &Address, block: BlockNumber) {
+		self.set.report_benign(validator, block);
+	}
+
+	fn register_contract(&self, client: Weak<Client>) {
+		self.set.register_contract(client.clone());
+		*self.client.write() = Some(client);
+		self.locked_parents.write().clear();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use ids::BlockId;
+	use engines::EpochChange;
+	use client::BlockChainClient;
+	use tests::helpers::generate_dummy_client;
+	use super::super::{ValidatorSet, SimpleList, TestSet};
+	use super::Timelocked;
+
+	#[test]
+	fn change_waits_for_timelock() {
+		let client = generate_dummy_client(5);
+		let hash = |n: u64| client.block_hash(BlockId::Number(n)).unwrap();
+		let old = Address::from(1);
+		let new = Address::from(2);
+		let header = |n: u64| client.block_header(BlockId::Number(n)).unwrap().decode();
+		// Set changes in block 2.
+		let inner = (2..6).fold(TestSet::from_list(vec![old]).with_transition(2, vec![new]), |set, n| set.with_parent_set(hash(n), vec![new]));
+		let set = Timelocked::new(Box::new(inner), 2);
+		// Without a client the chain is unknown, lookups use the genesis set.
+		assert!(set.contains(&hash(2), &old));
+		assert!(!set.contains(&hash(5), &new));
+		match set.is_epoch_end(&header(4), None, None) {
+			EpochChange::Unsure(_) => {},
+			_ => panic!("Epoch ends are unknown without a client."),
+		}
+		assert!(set.epoch_set(&header(4), &[]).is_err());
+
+		set.register_contract(Arc::downgrade(&client));
+		assert!(set.contains(&hash(1), &old));
+		assert!(set.contains(&hash(2), &old));
+		assert!(set.contains(&hash(3), &old));
+		assert!(!set.contains(&hash(3), &new));
+		assert!(set.contains(&hash(4), &new));
+		assert!(set.contains(&hash(5), &new));
+		// Walks are cached.
+		assert_eq!(set.locked_parents.read().get(&hash(4)), Some(&hash(2)));
+
+		// The epoch ends once the change takes effect, with the signalled set.
+		for n in 1..6 {
+			match set.is_epoch_end(&header(n), None, None) {
+				EpochChange::Yes(1) => assert_eq!(n, 4),
+				EpochChange::No => assert!(n != 4),
+				_ => panic!(),
+			}
+		}
+		assert_eq!(set.epoch_set(&header(4), &[]).unwrap(), (1, SimpleList::new(vec![new])));
+	}
+}
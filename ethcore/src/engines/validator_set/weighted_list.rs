@@ -0,0 +1,128 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+/// Preconfigured validator list with voting weights.
+
+use std::collections::BTreeMap;
+use util::{H256, Address, HeapSizeOf};
+
+use engines::{Call, EngineError};
+use header::Header;
+use super::{ValidatorSet, SimpleList};
+
+/// Validator set containing a known set of addresses, each with a voting weight.
+/// Validators are ordered by address.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct WeightedList {
+	validators: SimpleList,
+	weights: BTreeMap<Address, u64>,
+}
+
+impl WeightedList {
+	/// Create a new `WeightedList`.
+	/// Validators with zero weight could never contribute to a quorum, so they are left out.
+	pub fn new(weights: BTreeMap<Address, u64>) -> Self {
+		let (weights, ignored): (BTreeMap<_, _>, BTreeMap<_, _>) = weights.into_iter().partition(|&(_, w)| w > 0);
+		if !ignored.is_empty() {
+			warn!(target: "engine", "Ignoring {} validators with zero weight.", ignored.len());
+		}
+		WeightedList {
+			validators: SimpleList::new(weights.keys().cloned().collect()),
+			weights: weights,
+		}
+	}
+}
+
+impl HeapSizeOf for WeightedList {
+	fn heap_size_of_children(&self) -> usize {
+		self.validators.heap_size_of_children() + self.weights.len() * ::std::mem::size_of::<(Address, u64)>()
+	}
+}
+
+impl ValidatorSet for WeightedList {
+	fn default_caller(&self, _block_id: ::ids::BlockId) -> Box<Call> {
+		Box::new(|_, _| Err("Weighted list doesn't require calls.".into()))
+	}
+
+	fn is_epoch_end(&self, _header: &Header, _block: Option<&[u8]>, _receipts: Option<&[::receipt::Receipt]>)
+		-> ::engines::EpochChange
+	{
+		::engines::EpochChange::No
+	}
+
+	fn epoch_proof(&self, _header: &Header, _caller: &Call) -> Result<Vec<u8>, String> {
+		Ok(Vec::new())
+	}
+
+	/// Epoch sets are plain lists, which only keep the quorum if all validators weigh the same.
+	fn epoch_set(&self, _header: &Header, _: &[u8]) -> Result<(u64, SimpleList), ::error::Error> {
+		let mut weights = self.weights.values();
+		let first = weights.next();
+		if weights.all(|w| Some(w) == first) {
+			Ok((0, self.validators.clone()))
+		} else {
+			Err(EngineError::InsufficientProof("Validators of different weight can not be listed in an epoch set.".into()).into())
+		}
+	}
+
+	fn contains_with_caller(&self, bh: &H256, address: &Address, caller: &Call) -> bool {
+		self.validators.contains_with_caller(bh, address, caller)
+	}
+
+	fn get_with_caller(&self, bh: &H256, nonce: usize, caller: &Call) -> Address {
+		self.validators.get_with_caller(bh, nonce, caller)
+	}
+
+	fn count_with_caller(&self, bh: &H256, caller: &Call) -> usize {
+		self.validators.count_with_caller(bh, caller)
+	}
+
+	fn weight(&self, _bh: &H256, address: &Address) -> u64 {
+		self.weights.get(address).cloned().unwrap_or(0)
+	}
+
+	fn total_weight(&self, _bh: &H256) -> u64 {
+		self.weights.values().sum()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+	use util::Address;
+	use header::Header;
+	use super::super::{ValidatorSet, SimpleList};
+	use super::WeightedList;
+
+	#[test]
+	fn weighted_list() {
+		let a1 = Address::from_str("cd1722f3947def4cf144679da39c4c32bdc35681").unwrap();
+		let a2 = Address::from_str("0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6").unwrap();
+		let a3 = Address::from_str("6d1722f3947def4cf144679da39c4c32bdc35681").unwrap();
+		let list = WeightedList::new(vec![(a1, 3), (a2, 1), (a3, 0)].into_iter().collect());
+		assert_eq!(list.count(&Default::default()), 2);
+		assert_eq!(list.get(&Default::default(), 0), a2);
+		assert_eq!(list.get(&Default::default(), 1), a1);
+		assert!(!list.contains(&Default::default(), &a3));
+		assert_eq!(list.weight(&Default::default(), &a1), 3);
+		assert_eq!(list.weight(&Default::default(), &a3), 0);
+		assert_eq!(list.total_weight(&Default::default()), 4);
+		assert!(list.epoch_set(&Header::default(), &[]).is_err());
+
+		let equal = WeightedList::new(vec![(a1, 2), (a2, 2)].into_iter().collect());
+		assert_eq!(equal.epoch_set(&Header::default(), &[]).unwrap(), (0, SimpleList::new(vec![a2, a1])));
+	}
+}
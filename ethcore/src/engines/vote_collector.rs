@@ -48,8 +48,10 @@ struct StepCollector<M: Message> {
 #[derive(Debug)]
 pub struct DoubleVote<'a, M: Message> {
 	pub author: &'a Address,
-	vote_one: M,
-	vote_two: M,
+	/// Vote seen first.
+	pub vote_one: M,
+	/// Conflicting vote.
+	pub vote_two: M,
 }
 
 impl<'a, M: Message> Encodable for DoubleVote<'a, M> {
@@ -83,14 +85,22 @@ impl <M: Message> StepCollector<M> {
 		None
 	}
 
-	/// Count all votes for the given block hash at this round.
-	fn count_block(&self, block_hash: &Option<H256>) -> usize {
-		self.block_votes.get(block_hash).map_or(0, HashMap::len)
+	/// Stop counting votes for blocks matching `is_stale`, returns the number of votes no longer counted.
+	/// Messages and voters are kept, so voting again in the round is still a double vote.
+	fn throw_out_blocks<F>(&mut self, is_stale: &F) -> usize where F: Fn(&H256) -> bool {
+		let before: usize = self.block_votes.values().map(HashMap::len).sum();
+		self.block_votes.retain(|h, _| h.as_ref().map_or(true, |h| !is_stale(h)));
+		before - self.block_votes.values().map(HashMap::len).sum::<usize>()
 	}
 
-	/// Count all votes collected for the given round.
-	fn count(&self) -> usize {
-		self.block_votes.values().map(HashMap::len).sum()
+	/// Sum the weights of all votes for the given block hash at this round.
+	fn count_block<F>(&self, block_hash: &Option<H256>, weight: &F) -> usize where F: Fn(&Address) -> usize {
+		self.block_votes.get(block_hash).map_or(0, |votes| votes.values().map(|voter| weight(voter)).sum())
+	}
+
+	/// Sum the weights of all votes collected for the given round.
+	fn count<F>(&self, weight: &F) -> usize where F: Fn(&Address) -> usize {
+		self.block_votes.values().flat_map(HashMap::values).map(|voter| weight(voter)).sum()
 	}
 }
 
@@ -155,28 +165,89 @@ impl <M: Message + Default + Encodable + Debug> VoteCollector<M> {
 		*guard = new_collector;
 	}
 
-	/// Collects the signatures for a given round and hash.
+	/// Throws out messages up to and including `vote_round`, which is left empty as the marker for the oldest.
+	pub fn throw_out_up_to(&self, vote_round: &M::Round) {
+		let mut guard = self.votes.write();
+		let mut new_collector = guard.split_off(vote_round);
+		new_collector.insert(vote_round.clone(), Default::default());
+		*guard = new_collector;
+	}
+
+	/// Throw out the oldest rounds below `keep_from` until at most `max_messages` are held.
+	/// The last round thrown out is left empty as the marker for the oldest. Returns the number of messages removed.
+	pub fn throw_out_oldest(&self, keep_from: &M::Round, max_messages: usize) -> usize {
+		let mut guard = self.votes.write();
+		let mut count: usize = guard.values().map(|c| c.messages.len()).sum();
+		let mut removed = 0;
+		let mut marker = None;
+		while count > max_messages {
+			let oldest = match guard.keys().next() {
+				Some(round) if round < keep_from => round.clone(),
+				_ => break,
+			};
+			let collector = guard.remove(&oldest).expect("round is a key of the collector; qed");
+			count -= collector.messages.len();
+			removed += collector.messages.len();
+			marker = Some(oldest);
+		}
+		if let Some(marker) = marker {
+			guard.insert(marker, Default::default());
+		}
+		removed
+	}
+
+	/// Stop counting votes for blocks matching `is_stale` in all rounds, returns the number no longer counted.
+	/// Voters stay recorded, so voting again in the round is still a double vote.
+	pub fn throw_out_blocks<F>(&self, is_stale: F) -> usize where F: Fn(&H256) -> bool {
+		self.votes.write().values_mut().map(|c| c.throw_out_blocks(&is_stale)).sum()
+	}
+
+	/// Collects the signatures for a given round and hash, ordered by signer address.
 	pub fn round_signatures(&self, round: &M::Round, block_hash: &H256) -> Vec<H520> {
+		self.round_signers(round, block_hash).into_iter().map(|(_, signature)| signature).collect()
+	}
+
+	/// Collects the signers and their signatures for a given round and hash, ordered by signer address.
+	pub fn round_signers(&self, round: &M::Round, block_hash: &H256) -> Vec<(Address, H520)> {
 		let guard = self.votes.read();
-		guard
+		let mut votes = guard
 			.get(round)
 			.and_then(|c| c.block_votes.get(&Some(*block_hash)))
-			.map(|votes| votes.keys().cloned().collect())
-			.unwrap_or_else(Vec::new)
+			.map(|votes| votes.iter().map(|(signature, address)| (address.clone(), signature.clone())).collect())
+			.unwrap_or_else(Vec::new);
+		votes.sort();
+		votes
+	}
+
+	/// Voters in the given round along with the block hash they voted for.
+	pub fn round_voters(&self, round: &M::Round) -> Vec<(Address, Option<H256>)> {
+		self.votes.read().get(round).map_or_else(Vec::new, |c| c.voted.iter().map(|(address, m)| (address.clone(), m.block_hash())).collect())
 	}
 
 	/// Count votes which agree with the given message.
+	#[cfg(test)]
 	pub fn count_aligned_votes(&self, message: &M) -> usize {
+		self.aligned_votes_weight(message, |_| 1)
+	}
+
+	/// Sum the weights of voters which agree with the given message.
+	pub fn aligned_votes_weight<F>(&self, message: &M, weight: F) -> usize where F: Fn(&Address) -> usize {
 		self
 			.votes
 			.read()
 			.get(&message.round())
-			.map_or(0, |m| m.count_block(&message.block_hash()))
+			.map_or(0, |m| m.count_block(&message.block_hash(), &weight))
 	}
 
 	/// Count all votes collected for a given round.
+	#[cfg(test)]
 	pub fn count_round_votes(&self, vote_round: &M::Round) -> usize {
-		self.votes.read().get(vote_round).map_or(0, StepCollector::count)
+		self.round_votes_weight(vote_round, |_| 1)
+	}
+
+	/// Sum the weights of all voters in a given round.
+	pub fn round_votes_weight<F>(&self, vote_round: &M::Round, weight: F) -> usize where F: Fn(&Address) -> usize {
+		self.votes.read().get(vote_round).map_or(0, |c| c.count(&weight))
 	}
 
 	/// Get all messages older than the round.
@@ -200,6 +271,11 @@ impl <M: Message + Default + Encodable + Debug> VoteCollector<M> {
 	pub fn len(&self) -> usize {
 		self.votes.read().len()
 	}
+
+	/// Number of messages collected in all rounds.
+	pub fn message_count(&self) -> usize {
+		self.votes.read().values().map(|c| c.messages.len()).sum()
+	}
 }
 
 #[cfg(test)]
@@ -333,6 +409,66 @@ mod tests {
 		assert_eq!(collector.len(), 2);
 	}
 
+	#[test]
+	fn remove_oldest_over_limit() {
+		let collector = VoteCollector::default();
+		for round in 1..9 {
+			random_vote(&collector, H520::random(), round, Some("0".sha3()));
+			random_vote(&collector, H520::random(), round, Some("1".sha3()));
+		}
+		assert_eq!(collector.message_count(), 16);
+
+		// Rounds from 7 on are never thrown out.
+		assert_eq!(collector.throw_out_oldest(&7, 5), 12);
+		assert_eq!(collector.message_count(), 4);
+		assert_eq!(collector.count_round_votes(&7), 2);
+		assert_eq!(collector.count_round_votes(&8), 2);
+		// Thrown out rounds are old.
+		let message = |step| TestMessage { signature: H520::random(), step: step, block_hash: Some("0".sha3()) };
+		assert!(collector.is_old_or_known(&message(6)));
+		assert!(!collector.is_old_or_known(&message(7)));
+		random_vote(&collector, H520::random(), 9, Some("0".sha3()));
+
+		collector.throw_out_up_to(&7);
+		assert_eq!(collector.message_count(), 3);
+		assert!(collector.is_old_or_known(&message(7)));
+		assert!(!collector.is_old_or_known(&message(8)));
+	}
+
+	#[test]
+	fn remove_block_votes() {
+		let collector = VoteCollector::default();
+		let stale = "0".sha3();
+		let kept = "1".sha3();
+		let voter = Address::default();
+		full_vote(&collector, H520::random(), 3, Some(stale), &voter);
+		random_vote(&collector, H520::random(), 3, Some(kept));
+		random_vote(&collector, H520::random(), 4, Some(stale));
+		random_vote(&collector, H520::random(), 4, None);
+
+		assert_eq!(collector.throw_out_blocks(|h| *h == stale), 2);
+		assert_eq!(collector.message_count(), 4);
+		assert!(collector.round_signatures(&3, &stale).is_empty());
+		assert_eq!(collector.round_signatures(&3, &kept).len(), 1);
+		// Voting again after the removal is still a double vote.
+		assert!(!full_vote(&collector, H520::random(), 3, Some(kept), &voter));
+		assert_eq!(collector.round_signatures(&3, &kept).len(), 1);
+	}
+
+	#[test]
+	fn signatures_ordered_by_signer() {
+		let collector = VoteCollector::default();
+		let round = 3;
+		let bh = Some("0".sha3());
+		let mut votes: Vec<_> = (0..5).map(|_| (H160::random(), H520::random())).collect();
+		for &(ref address, ref signature) in &votes {
+			full_vote(&collector, signature.clone(), round, bh.clone(), address);
+		}
+		votes.sort();
+		let expected: Vec<_> = votes.into_iter().map(|(_, signature)| signature).collect();
+		assert_eq!(collector.round_signatures(&round, &bh.unwrap()), expected);
+	}
+
 	#[test]
 	fn malicious_authority() {
 		let collector = VoteCollector::default();
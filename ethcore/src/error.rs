@@ -171,6 +171,10 @@ pub enum BlockError {
 	UnknownUncleParent(H256),
 	/// No transition to epoch number.
 	UnknownEpochTransition(u64),
+	/// Transaction is out of the order required by the engine.
+	TransactionOutOfOrder(H256),
+	/// Block lacks a call to the given contract required by the engine.
+	MissingRequiredTransaction(Address),
 }
 
 impl fmt::Display for BlockError {
@@ -205,6 +209,8 @@ impl fmt::Display for BlockError {
 			UnknownParent(ref hash) => format!("Unknown parent: {}", hash),
 			UnknownUncleParent(ref hash) => format!("Unknown uncle parent: {}", hash),
 			UnknownEpochTransition(ref num) => format!("Unknown transition to epoch number: {}", num),
+			TransactionOutOfOrder(ref hash) => format!("Transaction {} is out of the required order", hash),
+			MissingRequiredTransaction(ref address) => format!("Block lacks a required call to {}", address),
 		};
 
 		f.write_fmt(format_args!("Block error ({})", msg))
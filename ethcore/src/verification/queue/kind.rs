@@ -34,6 +34,9 @@ pub trait BlockLike {
 
 	/// Get the difficulty of this item.
 	fn difficulty(&self) -> U256;
+
+	/// Get the timestamp of this item.
+	fn timestamp(&self) -> u64;
 }
 
 /// Defines transitions between stages of verification.
@@ -141,6 +144,10 @@ pub mod blocks {
 		fn difficulty(&self) -> U256 {
 			self.header.difficulty().clone()
 		}
+
+		fn timestamp(&self) -> u64 {
+			self.header.timestamp()
+		}
 	}
 
 	impl BlockLike for PreverifiedBlock {
@@ -155,6 +162,10 @@ pub mod blocks {
 		fn difficulty(&self) -> U256 {
 			self.header.difficulty().clone()
 		}
+
+		fn timestamp(&self) -> u64 {
+			self.header.timestamp()
+		}
 	}
 }
 
@@ -174,6 +185,7 @@ pub mod headers {
 		fn hash(&self) -> H256 { self.hash() }
 		fn parent_hash(&self) -> H256 { self.parent_hash().clone() }
 		fn difficulty(&self) -> U256 { self.difficulty().clone() }
+		fn timestamp(&self) -> u64 { self.timestamp() }
 	}
 
 	/// A mode for verifying headers.
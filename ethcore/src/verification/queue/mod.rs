@@ -25,6 +25,7 @@ use io::*;
 use error::*;
 use engines::Engine;
 use service::*;
+use time::get_time;
 
 use self::kind::{BlockLike, Kind};
 
@@ -55,6 +56,11 @@ pub struct Config {
 	pub max_mem_use: usize,
 	/// Settings for the number of verifiers and adaptation strategy.
 	pub verifier_settings: VerifierSettings,
+	/// Seconds past the allowed future block time within which a block is kept
+	/// and retried once local time catches up, rather than rejected.
+	pub max_clock_drift: u64,
+	/// Maximum number of blocks kept for a retry.
+	pub max_future_items: usize,
 }
 
 impl Default for Config {
@@ -63,6 +69,8 @@ impl Default for Config {
 			max_queue_size: 30000,
 			max_mem_use: 50 * 1024 * 1024,
 			verifier_settings: VerifierSettings::default(),
+			max_clock_drift: 0,
+			max_future_items: 128,
 		}
 	}
 }
@@ -140,6 +148,10 @@ pub struct VerificationQueue<K: Kind> {
 	verifier_handles: Vec<JoinHandle<()>>,
 	state: Arc<(Mutex<State>, Condvar)>,
 	total_difficulty: RwLock<U256>,
+	future: Mutex<BTreeMap<u64, Vec<K::Input>>>, // timestamp to items ahead of local time
+	max_clock_drift: u64,
+	max_future_items: usize,
+	clock: Box<Fn() -> u64 + Send + Sync>, // local time in seconds
 }
 
 struct QueueSignal {
@@ -279,9 +291,24 @@ impl<K: Kind> VerificationQueue<K> {
 			verifier_handles: verifier_handles,
 			state: state,
 			total_difficulty: RwLock::new(0.into()),
+			future: Mutex::new(BTreeMap::new()),
+			max_clock_drift: config.max_clock_drift,
+			max_future_items: config.max_future_items,
+			clock: Box::new(|| get_time().sec as u64),
 		}
 	}
 
+	/// Use `clock` as the local time in seconds when holding back items ahead of it.
+	#[cfg(test)]
+	fn set_clock<F>(&mut self, clock: F) where F: Fn() -> u64 + Send + Sync + 'static {
+		self.clock = Box::new(clock);
+	}
+
+	// latest timestamp the engine accepts at the moment.
+	fn max_time(&self) -> u64 {
+		(self.clock)() + self.engine.allowed_future_block_time()
+	}
+
 	fn verify(
 		verification: Arc<Verification<K>>,
 		engine: Arc<Engine>,
@@ -439,6 +466,7 @@ impl<K: Kind> VerificationQueue<K> {
 		*self.total_difficulty.write() = 0.into();
 
 		self.processing.write().clear();
+		self.future.lock().clear();
 	}
 
 	/// Wait for unverified queue to be empty
@@ -451,7 +479,7 @@ impl<K: Kind> VerificationQueue<K> {
 
 	/// Check if the item is currently in the queue
 	pub fn status(&self, hash: &H256) -> Status {
-		if self.processing.read().contains_key(hash) {
+		if self.processing.read().contains_key(hash) || self.is_future(hash) {
 			return Status::Queued;
 		}
 		if self.verification.bad.lock().contains(hash) {
@@ -464,7 +492,7 @@ impl<K: Kind> VerificationQueue<K> {
 	pub fn import(&self, input: K::Input) -> ImportResult {
 		let h = input.hash();
 		{
-			if self.processing.read().contains_key(&h) {
+			if self.processing.read().contains_key(&h) || self.is_future(&h) {
 				return Err(ImportError::AlreadyQueued.into());
 			}
 
@@ -479,6 +507,11 @@ impl<K: Kind> VerificationQueue<K> {
 			}
 		}
 
+		let max_time = self.max_time();
+		if input.timestamp() > max_time && input.timestamp() <= max_time + self.max_clock_drift {
+			return self.defer_future(input, max_time);
+		}
+
 		match K::create(input, &*self.engine) {
 			Ok(item) => {
 				self.verification.sizes.unverified.fetch_add(item.heap_size_of_children(), AtomicOrdering::SeqCst);
@@ -505,6 +538,40 @@ impl<K: Kind> VerificationQueue<K> {
 		}
 	}
 
+	fn is_future(&self, hash: &H256) -> bool {
+		self.future.lock().values().any(|items| items.iter().any(|item| &item.hash() == hash))
+	}
+
+	// keep an item which is slightly ahead of local time until `retry_future` imports it.
+	fn defer_future(&self, input: K::Input, max_time: u64) -> ImportResult {
+		let mut future = self.future.lock();
+		if future.values().map(Vec::len).sum::<usize>() >= self.max_future_items {
+			return Err(BlockError::InvalidTimestamp(OutOfBounds { max: Some(max_time), min: None, found: input.timestamp() }).into());
+		}
+		let h = input.hash();
+		trace!(target: "verification", "Item {} is ahead of local time, retrying at {}", h, input.timestamp());
+		future.entry(input.timestamp()).or_insert_with(Vec::new).push(input);
+		Ok(h)
+	}
+
+	/// Import the items held back for a future timestamp which local time has caught up with.
+	pub fn retry_future(&self) {
+		let max_time = self.max_time();
+		let ready = {
+			let mut future = self.future.lock();
+			let later = future.split_off(&(max_time + 1));
+			::std::mem::replace(&mut *future, later)
+		};
+		for (_, items) in ready {
+			for item in items {
+				let h = item.hash();
+				if let Err(e) = self.import(item) {
+					debug!(target: "verification", "Retried item {} failed: {:?}", h, e);
+				}
+			}
+		}
+	}
+
 	/// Mark given item and all its children as bad. pauses verification
 	/// until complete.
 	pub fn mark_as_bad(&self, hashes: &[H256]) {
@@ -726,6 +793,7 @@ impl<K: Kind> Drop for VerificationQueue<K> {
 
 #[cfg(test)]
 mod tests {
+	use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 	use util::*;
 	use io::*;
 	use spec::*;
@@ -734,6 +802,8 @@ mod tests {
 	use tests::helpers::*;
 	use error::*;
 	use views::*;
+	use header::Header;
+	use time::get_time;
 
 	// create a test block queue.
 	// auto_scaling enables verifier adjustment.
@@ -840,6 +910,43 @@ mod tests {
 		assert!(queue.queue_info().is_full());
 	}
 
+	#[test]
+	fn retries_block_from_near_future() {
+		let spec = get_test_spec();
+		let mut config = Config::default();
+		config.max_clock_drift = 10;
+		let mut queue = BlockQueue::new(config, spec.engine.clone(), IoChannel::disconnected(), true);
+		// The queue clock lags behind, verification still uses the real time.
+		let now = get_time().sec as u64;
+		let clock = Arc::new(AtomicUsize::new(now as usize - 40));
+		let queue_clock = clock.clone();
+		queue.set_clock(move || queue_clock.load(AtomicOrdering::SeqCst) as u64);
+
+		let mut header = Header::new();
+		header.set_gas_limit(spec.engine.params().min_gas_limit);
+		header.set_difficulty(U256::from(0x20000));
+		header.set_timestamp(now - 5);
+		header.set_number(1);
+		header.set_parent_hash(spec.genesis_header().hash());
+		header.set_state_root(spec.genesis_header().state_root().clone());
+		let block = create_test_block(&header);
+
+		assert_eq!(queue.import(Unverified::new(block.clone())).unwrap(), header.hash());
+		assert!(queue.queue_info().is_empty());
+		match queue.import(Unverified::new(block)) {
+			Err(Error::Import(ImportError::AlreadyQueued)) => {},
+			other => panic!("must return AlreadyQueued, got {:?}", other),
+		}
+		queue.retry_future();
+		assert!(queue.queue_info().is_empty());
+
+		// Once local time catches up the block goes through verification.
+		clock.store(now as usize, AtomicOrdering::SeqCst);
+		queue.retry_future();
+		queue.flush();
+		assert_eq!(queue.drain(10).len(), 1);
+	}
+
 	#[test]
 	fn scaling_limits() {
 		use super::MAX_VERIFIERS;
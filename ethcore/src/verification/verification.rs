@@ -31,6 +31,9 @@ use transaction::SignedTransaction;
 use views::BlockView;
 use time::get_time;
 
+/// Seconds a block timestamp may be ahead of local time, unless the engine sets another bound.
+pub const ALLOWED_FUTURE_BLOCK_TIME: u64 = 30;
+
 /// Preprocessed block data gathered in `verify_block_unordered` call
 pub struct PreverifiedBlock {
 	/// Populated block header
@@ -211,7 +214,7 @@ pub fn verify_header_params(header: &Header, engine: &Engine, is_full: bool) ->
 		return Err(From::from(BlockError::ExtraDataOutOfBounds(OutOfBounds { min: None, max: Some(maximum_extra_data_size), found: header.extra_data().len() })));
 	}
 	if is_full {
-		let max_time = get_time().sec as u64 + 30;
+		let max_time = get_time().sec as u64 + engine.allowed_future_block_time();
 		if header.timestamp() > max_time {
 			return Err(From::from(BlockError::InvalidTimestamp(OutOfBounds { max: Some(max_time), min: None, found: header.timestamp() })))
 		}
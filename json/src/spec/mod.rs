@@ -44,4 +44,4 @@ pub use self::validator_set::ValidatorSet;
 pub use self::instant_seal::{InstantSeal, InstantSealParams};
 pub use self::basic_authority::{BasicAuthority, BasicAuthorityParams};
 pub use self::authority_round::{AuthorityRound, AuthorityRoundParams};
-pub use self::tendermint::{Tendermint, TendermintParams};
+pub use self::tendermint::{Tendermint, TendermintParams, RequiredTransaction, BlockRewardStep, PrimaryStrategy, QuorumRatio};
@@ -16,10 +16,51 @@
 
 //! Tendermint params deserialization.
 
+use std::collections::BTreeMap;
 use uint::Uint;
 use hash::Address;
+use bytes::Bytes;
 use super::ValidatorSet;
 
+/// Transaction which has to be included in every block.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct RequiredTransaction {
+	/// Called contract.
+	pub to: Address,
+	/// Prefix of the call data, usually a method selector.
+	#[serde(rename="dataPrefix")]
+	pub data_prefix: Option<Bytes>,
+}
+
+/// Proposer rotation.
+#[derive(Debug, PartialEq, Deserialize)]
+pub enum PrimaryStrategy {
+	/// Validators propose in turn.
+	#[serde(rename="roundRobin")]
+	RoundRobin,
+	/// Proposer derived from the parent block hash and the view.
+	#[serde(rename="hashBased")]
+	HashBased,
+}
+
+/// Share of the validator weight needed to commit.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct QuorumRatio {
+	/// Numerator of the ratio.
+	pub numerator: Uint,
+	/// Denominator of the ratio.
+	pub denominator: Uint,
+}
+
+/// Block reward paid from a given block on.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct BlockRewardStep {
+	/// First block paying the reward.
+	pub from: Uint,
+	/// Block reward.
+	pub reward: Uint,
+}
+
 /// Tendermint params deserialization.
 #[derive(Debug, PartialEq, Deserialize)]
 pub struct TendermintParams {
@@ -40,11 +81,121 @@ pub struct TendermintParams {
 	/// Commit step timeout in milliseconds.
 	#[serde(rename="timeoutCommit")]
 	pub timeout_commit: Option<Uint>,
+	/// Factor by which step timeouts grow with each view change.
+	#[serde(rename="viewChangeBackoff")]
+	pub view_change_backoff: Option<Uint>,
+	/// Upper bound in milliseconds of step timeouts grown by view changes.
+	#[serde(rename="maxTimeout")]
+	pub max_timeout: Option<Uint>,
 	/// Block reward.
 	#[serde(rename="blockReward")]
 	pub block_reward: Option<Uint>,
+	/// Block rewards which replace `blockReward` from given blocks on.
+	#[serde(rename="blockRewardSchedule")]
+	pub block_reward_schedule: Option<Vec<BlockRewardStep>>,
 	/// Address of the registrar contract.
 	pub registrar: Option<Address>,
+	/// Senders whose transactions make a block invalid.
+	#[serde(rename="senderBlacklist")]
+	pub sender_blacklist: Option<Vec<Address>>,
+	/// Select proposers using a seed derived from the parent block hash.
+	/// Same as the `hashBased` primary strategy, which takes precedence if given.
+	#[serde(rename="seededProposer")]
+	pub seeded_proposer: Option<bool>,
+	/// Proposer rotation.
+	#[serde(rename="primaryStrategy")]
+	pub primary_strategy: Option<PrimaryStrategy>,
+	/// Select proposers in proportion to their voting weight.
+	#[serde(rename="weightedProposer")]
+	pub weighted_proposer: Option<bool>,
+	/// Time in milliseconds to keep collecting precommits for the seal after reaching quorum.
+	/// Can not be combined with `minimalCommitSeal`.
+	#[serde(rename="commitGraceWindow")]
+	pub commit_grace_window: Option<Uint>,
+	/// Number of blocks before a validator set change takes effect.
+	#[serde(rename="setChangeTimelock")]
+	pub set_change_timelock: Option<Uint>,
+	/// How far below the best block a proposal can be built.
+	#[serde(rename="maxProposalDepth")]
+	pub max_proposal_depth: Option<Uint>,
+	/// Count a proposal as the prevote of its proposer.
+	#[serde(rename="proposerVoteCounts")]
+	pub proposer_vote_counts: Option<bool>,
+	/// Number of blocks before a block reward is credited to the author.
+	#[serde(rename="rewardMaturity")]
+	pub reward_maturity: Option<Uint>,
+	/// Require transactions ordered by nonce and then hash.
+	#[serde(rename="canonicalTransactionOrder")]
+	pub canonical_transaction_order: Option<bool>,
+	/// Transactions which have to be included in every block.
+	#[serde(rename="requiredTransactions")]
+	pub required_transactions: Option<Vec<RequiredTransaction>>,
+	/// Include only as many precommits in commit seals as needed for quorum.
+	#[serde(rename="minimalCommitSeal")]
+	pub minimal_commit_seal: Option<bool>,
+	/// Maximum number of heights the network may be ahead while still signing consensus messages.
+	#[serde(rename="syncDistance")]
+	pub sync_distance: Option<Uint>,
+	/// Persist votes of the current height, so that they survive a restart.
+	#[serde(rename="persistVotes")]
+	pub persist_votes: Option<bool>,
+	/// Directory of the database which votes are persisted to.
+	#[serde(rename="voteStorePath")]
+	pub vote_store_path: Option<String>,
+	/// Maximum number of new messages processed per slice, the rest waits for the next slice.
+	#[serde(rename="maxMessagesPerSlice")]
+	pub max_messages_per_slice: Option<Uint>,
+	/// Duration of a message processing slice in ms.
+	#[serde(rename="messageSliceInterval")]
+	pub message_slice_interval: Option<Uint>,
+	/// Highest view entered on timeouts within a height.
+	#[serde(rename="maxView")]
+	pub max_view: Option<Uint>,
+	/// How many seconds a block timestamp can be ahead of the local clock.
+	#[serde(rename="allowedFutureTime")]
+	pub allowed_future_time: Option<Uint>,
+	/// Follow consensus without signing anything.
+	#[serde(rename="observerOnly")]
+	pub observer_only: Option<bool>,
+	/// Messages applied from each validator per second, unlimited by default.
+	#[serde(rename="messagesPerSecond")]
+	pub messages_per_second: Option<Uint>,
+	/// Number of views below the current one which keep their votes.
+	#[serde(rename="maxRetainedViews")]
+	pub max_retained_views: Option<Uint>,
+	/// Number of votes kept above which votes of past views are dropped.
+	#[serde(rename="maxRetainedMessages")]
+	pub max_retained_messages: Option<Uint>,
+	/// See main EthashParams docs.
+	#[serde(rename="eip160Transition")]
+	pub eip160_transition: Option<Uint>,
+	/// See main EthashParams docs.
+	#[serde(rename="eip161abcTransition")]
+	pub eip161abc_transition: Option<Uint>,
+	/// See main EthashParams docs.
+	#[serde(rename="eip161dTransition")]
+	pub eip161d_transition: Option<Uint>,
+	/// Maximum size of deployed contract code.
+	#[serde(rename="maxCodeSize")]
+	pub max_code_size: Option<Uint>,
+	/// Additional signing keys of validators, mapped to the validator address.
+	#[serde(rename="signerKeys")]
+	pub signer_keys: Option<BTreeMap<Address, Address>>,
+	/// Maximum random delay in ms of the rebroadcast on timeouts.
+	#[serde(rename="broadcastJitter")]
+	pub broadcast_jitter: Option<Uint>,
+	/// Vote weight above which a round is committed, defaults to two thirds.
+	#[serde(rename="quorumRatio")]
+	pub quorum_ratio: Option<QuorumRatio>,
+	/// First block whose commit seal has to list the signers in increasing address order.
+	#[serde(rename="orderedSignersTransition")]
+	pub ordered_signers_transition: Option<Uint>,
+	/// Validators proposing in the first view of the given heights, to restart a stalled chain.
+	#[serde(rename="emergencyProposers")]
+	pub emergency_proposers: Option<BTreeMap<Uint, Address>>,
+	/// Whether a commit of the current height from a peer ends the height, true by default.
+	#[serde(rename="adoptPeerCommits")]
+	pub adopt_peer_commits: Option<bool>,
 }
 
 /// Tendermint engine deserialization.
@@ -61,7 +212,7 @@ mod tests {
 	use util::U256;
 	use hash::Address;
 	use util::hash::H160;
-	use spec::tendermint::Tendermint;
+	use spec::tendermint::{Tendermint, BlockRewardStep, PrimaryStrategy};
 	use spec::validator_set::ValidatorSet;
 
 	#[test]
@@ -82,4 +233,54 @@ mod tests {
 		assert_eq!(deserialized.params.validators, vs);
 		assert_eq!(deserialized.params.block_reward, Some(Uint(U256::from(0x50))));
 	}
+
+	#[test]
+	fn rejects_block_reward_above_u256() {
+		let s = format!(r#"{{
+			"params": {{
+				"gasLimitBoundDivisor": "0x0400",
+				"validators": {{
+					"list": ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"]
+				}},
+				"blockReward": "0x1{}"
+			}}
+		}}"#, "0".repeat(64));
+		assert!(serde_json::from_str::<Tendermint>(&s).is_err());
+	}
+
+	#[test]
+	fn primary_strategy_deserialization() {
+		let s = r#"{
+			"params": {
+				"gasLimitBoundDivisor": "0x0400",
+				"validators": {
+					"list": ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"]
+				},
+				"primaryStrategy": "hashBased"
+			}
+		}"#;
+
+		let deserialized: Tendermint = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.params.primary_strategy, Some(PrimaryStrategy::HashBased));
+	}
+
+	#[test]
+	fn block_reward_schedule_deserialization() {
+		let s = r#"{
+			"params": {
+				"gasLimitBoundDivisor": "0x0400",
+				"validators": {
+					"list": ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"]
+				},
+				"blockReward": "0x50",
+				"blockRewardSchedule": [{ "from": 100, "reward": "0x28" }]
+			}
+		}"#;
+
+		let deserialized: Tendermint = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.params.block_reward_schedule, Some(vec![BlockRewardStep {
+			from: Uint(U256::from(100)),
+			reward: Uint(U256::from(0x28)),
+		}]));
+	}
 }
@@ -26,6 +26,9 @@ pub enum ValidatorSet {
 	/// A simple list of authorities.
 	#[serde(rename="list")]
 	List(Vec<Address>),
+	/// Authorities along with their voting weights.
+	#[serde(rename="weightedList")]
+	WeightedList(BTreeMap<Address, Uint>),
 	/// Address of a contract that indicates the list of authorities.
 	#[serde(rename="safeContract")]
 	SafeContract(Address),
@@ -78,4 +81,23 @@ mod tests {
 			_ => assert!(false),
 		}
 	}
+
+	#[test]
+	fn weighted_list_deserialization() {
+		let s = r#"{
+			"weightedList": {
+				"0xc6d9d2cd449a754c494264e1809c50e34d64562b": "0x3",
+				"0xd6d9d2cd449a754c494264e1809c50e34d64562b": "0x1"
+			}
+		}"#;
+
+		let deserialized: ValidatorSet = serde_json::from_str(s).unwrap();
+		match deserialized {
+			ValidatorSet::WeightedList(ref weights) => {
+				assert_eq!(weights.len(), 2);
+				assert_eq!(weights[&Address(H160::from("0xc6d9d2cd449a754c494264e1809c50e34d64562b"))], Uint(U256::from(3)));
+			},
+			_ => assert!(false),
+		}
+	}
 }
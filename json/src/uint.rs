@@ -74,6 +74,9 @@ impl Visitor for UintVisitor {
 		let value = match value.len() {
 			0 => U256::from(0),
 			2 if value.starts_with("0x") => U256::from(0),
+			_ if value.starts_with("0x") && value[2..].trim_left_matches('0').len() > 64 => {
+				return Err(Error::custom(format!("Hex value {} does not fit in 256 bits", value).as_str()));
+			},
 			_ if value.starts_with("0x") => U256::from_str(&value[2..]).map_err(|e| {
 				Error::custom(format!("Invalid hex value {}: {}", value, e).as_str())
 			})?,
@@ -109,6 +112,15 @@ mod test {
 		]);
 	}
 
+	#[test]
+	fn uint_overflow() {
+		let max = format!(r#""0x00{}""#, "f".repeat(64));
+		let deserialized: Uint = serde_json::from_str(&max).unwrap();
+		assert_eq!(deserialized, Uint(!U256::zero()));
+		let overflow = format!(r#""0x1{}""#, "0".repeat(64));
+		assert!(serde_json::from_str::<Uint>(&overflow).is_err());
+	}
+
 	#[test]
 	fn uint_into() {
 		assert_eq!(U256::from(10), Uint(U256::from(10)).into());
@@ -327,6 +327,8 @@ usage! {
 			or |c: &Config| otry!(c.footprint).scale_verifiers.clone(),
 		flag_num_verifiers: Option<usize> = None,
 			or |c: &Config| otry!(c.footprint).num_verifiers.clone().map(Some),
+		flag_max_clock_drift: u64 = 0u64,
+			or |c: &Config| otry!(c.footprint).max_clock_drift.clone(),
 
 		// -- Import/Export Options
 		flag_from: String = "1", or |_| None,
@@ -567,6 +569,7 @@ struct Footprint {
 	fat_db: Option<String>,
 	scale_verifiers: Option<bool>,
 	num_verifiers: Option<usize>,
+	max_clock_drift: Option<u64>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -823,6 +826,7 @@ mod tests {
 			flag_fat_db: "auto".into(),
 			flag_scale_verifiers: true,
 			flag_num_verifiers: Some(6),
+			flag_max_clock_drift: 5u64,
 
 			// -- Import/Export Options
 			flag_from: "1".into(),
@@ -1043,6 +1047,7 @@ mod tests {
 				fat_db: Some("off".into()),
 				scale_verifiers: Some(false),
 				num_verifiers: None,
+				max_clock_drift: None,
 			}),
 			snapshots: Some(Snapshots {
 				disable_periodic: Some(true),
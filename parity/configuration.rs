@@ -380,6 +380,7 @@ impl Configuration {
 				check_seal: !self.args.flag_no_seal_check,
 				download_old_blocks: !self.args.flag_no_ancient_blocks,
 				verifier_settings: verifier_settings,
+				max_clock_drift: self.args.flag_max_clock_drift,
 				serve_light: !self.args.flag_no_serve_light,
 				light: self.args.flag_light,
 				no_persistent_txqueue: self.args.flag_no_persistent_txqueue,
@@ -1302,6 +1303,7 @@ mod tests {
 			check_seal: true,
 			download_old_blocks: true,
 			verifier_settings: Default::default(),
+			max_clock_drift: 0,
 			serve_light: true,
 			light: false,
 			no_persistent_txqueue: false,
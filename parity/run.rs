@@ -113,6 +113,7 @@ pub struct RunCmd {
 	pub check_seal: bool,
 	pub download_old_blocks: bool,
 	pub verifier_settings: VerifierSettings,
+	pub max_clock_drift: u64,
 	pub serve_light: bool,
 	pub light: bool,
 	pub no_persistent_txqueue: bool,
@@ -214,6 +215,7 @@ fn execute_light(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) ->
 
 	config.queue.max_mem_use = cmd.cache_config.queue() as usize * 1024 * 1024;
 	config.queue.verifier_settings = cmd.verifier_settings;
+	config.queue.max_clock_drift = cmd.max_clock_drift;
 
 	let service = light_client::Service::start(config, &spec, &db_dirs.client_path(algorithm), cache.clone())
 		.map_err(|e| format!("Error starting light client: {}", e))?;
@@ -491,6 +493,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	);
 
 	client_config.queue.verifier_settings = cmd.verifier_settings;
+	client_config.queue.max_clock_drift = cmd.max_clock_drift;
 
 	// set up bootnodes
 	let mut net_conf = cmd.net_conf;
@@ -45,6 +45,7 @@ use v1::types::{
 	BlockNumber, ConsensusCapability, VersionInfo,
 	OperationsInfo, DappId, ChainStatus,
 	AccountInfo, HwAccountInfo, Header, RichHeader,
+	ConsensusState, ViewLatency, BlockCheck, RuntimeStats, ConsensusConfig,
 };
 
 /// Parity implementation for light client.
@@ -343,6 +344,42 @@ impl Parity for ParityClient {
 		})
 	}
 
+	fn consensus_state(&self) -> Result<Option<ConsensusState>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn consensus_view_latency(&self) -> Result<Option<ViewLatency>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn validator_signed_blocks(&self, _address: H160, _from: u64, _to: u64) -> Result<Option<Vec<u64>>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn check_block(&self, _block: Bytes) -> Result<Option<BlockCheck>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn equivocating_validators(&self) -> Result<Option<Vec<H160>>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn last_commit_view_changes(&self) -> Result<Option<u64>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn commit_messages(&self, _number: BlockNumber) -> Result<Option<Vec<Bytes>>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn consensus_runtime_stats(&self) -> Result<Option<RuntimeStats>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn consensus_config(&self) -> Result<Option<ConsensusConfig>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn node_kind(&self) -> Result<::v1::types::NodeKind, Error> {
 		use ::v1::types::{NodeKind, Availability, Capability};
 
@@ -48,7 +48,8 @@ use v1::types::{
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
 	OperationsInfo, DappId, ChainStatus,
-	AccountInfo, HwAccountInfo, RichHeader
+	AccountInfo, HwAccountInfo, RichHeader,
+	ConsensusState, ViewLatency, BlockCheck, RuntimeStats, ConsensusConfig,
 };
 
 /// Parity implementation.
@@ -377,6 +378,48 @@ impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 		})
 	}
 
+	fn consensus_state(&self) -> Result<Option<ConsensusState>, Error> {
+		Ok(self.client.consensus().map(|consensus| consensus.consensus_state().into()))
+	}
+
+	fn consensus_view_latency(&self) -> Result<Option<ViewLatency>, Error> {
+		Ok(self.client.consensus().map(|consensus| consensus.view_latency_stats().into()))
+	}
+
+	fn validator_signed_blocks(&self, address: H160, from: u64, to: u64) -> Result<Option<Vec<u64>>, Error> {
+		Ok(self.client.consensus().map(|consensus| consensus.validator_signed_blocks(&address.into(), from, to)))
+	}
+
+	fn check_block(&self, block: Bytes) -> Result<Option<BlockCheck>, Error> {
+		Ok(self.client.consensus().map(|consensus| consensus.check_block(&block.into_vec()).into()))
+	}
+
+	fn equivocating_validators(&self) -> Result<Option<Vec<H160>>, Error> {
+		Ok(self.client.consensus().map(|consensus| consensus.equivocating_validators().into_iter().map(Into::into).collect()))
+	}
+
+	fn last_commit_view_changes(&self) -> Result<Option<u64>, Error> {
+		Ok(self.client.consensus().map(|consensus| consensus.last_commit_view_changes() as u64))
+	}
+
+	fn commit_messages(&self, number: BlockNumber) -> Result<Option<Vec<Bytes>>, Error> {
+		let consensus = match self.client.consensus() {
+			Some(consensus) => consensus,
+			None => return Ok(None),
+		};
+		Ok(self.client.block_header(number.into())
+			.and_then(|header| consensus.seal_messages(&header.decode()))
+			.map(|messages| messages.into_iter().map(Into::into).collect()))
+	}
+
+	fn consensus_runtime_stats(&self) -> Result<Option<RuntimeStats>, Error> {
+		Ok(self.client.consensus().map(|consensus| consensus.runtime_stats().into()))
+	}
+
+	fn consensus_config(&self) -> Result<Option<ConsensusConfig>, Error> {
+		Ok(self.client.consensus().map(|consensus| consensus.effective_config().into()))
+	}
+
 	fn node_kind(&self) -> Result<::v1::types::NodeKind, Error> {
 		use ::v1::types::{NodeKind, Availability, Capability};
 
@@ -31,7 +31,7 @@ use jsonrpc_core::Error;
 use v1::helpers::dapps::DappsService;
 use v1::helpers::errors;
 use v1::traits::ParitySet;
-use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp};
+use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp, ConsensusPhase};
 
 /// Parity-specific rpc interface for operations altering the settings.
 pub struct ParitySetClient<C, M, U, F = fetch::Client> {
@@ -165,6 +165,17 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		Ok(true)
 	}
 
+	fn set_consensus_log_phases(&self, phases: Vec<ConsensusPhase>) -> Result<bool, Error> {
+		match self.client.consensus() {
+			Some(consensus) => {
+				let phases: Vec<_> = phases.into_iter().map(Into::into).collect();
+				consensus.set_log_phases(&phases);
+				Ok(true)
+			},
+			None => Ok(false),
+		}
+	}
+
 	fn hash_content(&self, url: String) -> BoxFuture<H256, Error> {
 		self.fetch.process(self.fetch.fetch(&url).then(move |result| {
 			result
@@ -29,6 +29,7 @@ use v1::types::{
 	BlockNumber, ConsensusCapability, VersionInfo,
 	OperationsInfo, DappId, ChainStatus,
 	AccountInfo, HwAccountInfo, RichHeader,
+	ConsensusState, ViewLatency, BlockCheck, RuntimeStats, ConsensusConfig,
 };
 
 build_rpc_trait! {
@@ -195,6 +196,52 @@ build_rpc_trait! {
 		#[rpc(name = "parity_nodeKind")]
 		fn node_kind(&self) -> Result<::v1::types::NodeKind, Error>;
 
+		/// Get the live state of the consensus engine.
+		/// Returns `null` if the engine does not run consensus rounds.
+		#[rpc(name = "parity_consensusState")]
+		fn consensus_state(&self) -> Result<Option<ConsensusState>, Error>;
+
+		/// Get the average and longest time in milliseconds spent in recent consensus views.
+		/// Returns `null` if the engine does not run consensus rounds.
+		#[rpc(name = "parity_consensusViewLatency")]
+		fn consensus_view_latency(&self) -> Result<Option<ViewLatency>, Error>;
+
+		/// Get the blocks between the given numbers (inclusive) which the validator proposed or voted for.
+		/// At most 10000 blocks from the first number are scanned.
+		/// Returns `null` if the engine does not run consensus rounds.
+		#[rpc(name = "parity_validatorSignedBlocks")]
+		fn validator_signed_blocks(&self, H160, u64, u64) -> Result<Option<Vec<u64>>, Error>;
+
+		/// Check an RLP encoded block against the consensus rules without importing it.
+		/// The result names the failing verification stage, `null` if the engine does not run consensus rounds.
+		#[rpc(name = "parity_checkBlock")]
+		fn check_block(&self, Bytes) -> Result<Option<BlockCheck>, Error>;
+
+		/// Get the validators which double voted often enough to raise an alert.
+		/// Returns `null` if the engine does not run consensus rounds.
+		#[rpc(name = "parity_equivocatingValidators")]
+		fn equivocating_validators(&self) -> Result<Option<Vec<H160>>, Error>;
+
+		/// Get the number of view changes which occurred at the last committed height.
+		/// Returns `null` if the engine does not run consensus rounds.
+		#[rpc(name = "parity_lastCommitViewChanges")]
+		fn last_commit_view_changes(&self) -> Result<Option<u64>, Error>;
+
+		/// Get the raw RLP of the consensus messages rebuilt from the seal of a committed block.
+		/// Returns `null` if the block is unknown or its seal carries no messages.
+		#[rpc(name = "parity_commitMessages")]
+		fn commit_messages(&self, BlockNumber) -> Result<Option<Vec<Bytes>>, Error>;
+
+		/// Get the uptime of the consensus engine and the number of messages, proposals and commits it handled.
+		/// Returns `null` if the engine does not run consensus rounds.
+		#[rpc(name = "parity_consensusRuntimeStats")]
+		fn consensus_runtime_stats(&self) -> Result<Option<RuntimeStats>, Error>;
+
+		/// Get the parameters the consensus engine runs with, including the defaults for missing spec values.
+		/// Returns `null` if the engine does not run consensus rounds.
+		#[rpc(name = "parity_consensusConfig")]
+		fn consensus_config(&self) -> Result<Option<ConsensusConfig>, Error>;
+
 		/// Get block header.
 		/// Same as `eth_getBlockByNumber` but without uncles and transactions.
 		#[rpc(async, name = "parity_getBlockHeaderByNumber")]
@@ -19,7 +19,7 @@
 use jsonrpc_core::Error;
 use futures::BoxFuture;
 
-use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp};
+use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp, ConsensusPhase};
 
 build_rpc_trait! {
 	/// Parity-specific rpc interface for operations altering the settings.
@@ -92,6 +92,11 @@ build_rpc_trait! {
 		#[rpc(name = "parity_setChain")]
 		fn set_spec_name(&self, String) -> Result<bool, Error>;
 
+		/// Only log the given phases of the consensus engine. Phases are any of: "propose", "prevote", "precommit", "commit", "newView".
+		/// Returns `false` if the engine does not run consensus rounds.
+		#[rpc(name = "parity_setConsensusLogPhases")]
+		fn set_consensus_log_phases(&self, Vec<ConsensusPhase>) -> Result<bool, Error>;
+
 		/// Hash a file content under given URL.
 		#[rpc(async, name = "parity_hashContent")]
 		fn hash_content(&self, String) -> BoxFuture<H256, Error>;
@@ -0,0 +1,301 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Introspection of the consensus rounds of the engine.
+
+use std::time::Duration;
+use rustc_serialize::json::Json;
+use serde_json::{Value, Number};
+use ethcore::engines;
+use ethcore::error::Error;
+
+/// Step of a consensus round.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum ConsensusStep {
+	/// Waiting for the proposal.
+	#[serde(rename="propose")]
+	Propose,
+	/// Voting for the proposal.
+	#[serde(rename="prevote")]
+	Prevote,
+	/// Committing to the proposal.
+	#[serde(rename="precommit")]
+	Precommit,
+	/// Proposal is committed.
+	#[serde(rename="commit")]
+	Commit,
+}
+
+impl From<engines::ConsensusStep> for ConsensusStep {
+	fn from(step: engines::ConsensusStep) -> Self {
+		match step {
+			engines::ConsensusStep::Propose => ConsensusStep::Propose,
+			engines::ConsensusStep::Prevote => ConsensusStep::Prevote,
+			engines::ConsensusStep::Precommit => ConsensusStep::Precommit,
+			engines::ConsensusStep::Commit => ConsensusStep::Commit,
+		}
+	}
+}
+
+/// Part of a consensus round which can be logged separately.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum ConsensusPhase {
+	/// Proposal handling.
+	#[serde(rename="propose")]
+	Propose,
+	/// Prevote handling.
+	#[serde(rename="prevote")]
+	Prevote,
+	/// Precommit handling.
+	#[serde(rename="precommit")]
+	Precommit,
+	/// Commits of proposals.
+	#[serde(rename="commit")]
+	Commit,
+	/// Changes of the view.
+	#[serde(rename="newView")]
+	NewView,
+}
+
+impl Into<engines::ConsensusPhase> for ConsensusPhase {
+	fn into(self) -> engines::ConsensusPhase {
+		match self {
+			ConsensusPhase::Propose => engines::ConsensusPhase::Propose,
+			ConsensusPhase::Prevote => engines::ConsensusPhase::Prevote,
+			ConsensusPhase::Precommit => engines::ConsensusPhase::Precommit,
+			ConsensusPhase::Commit => engines::ConsensusPhase::Commit,
+			ConsensusPhase::NewView => engines::ConsensusPhase::NewView,
+		}
+	}
+}
+
+/// Live state of the consensus rounds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConsensusState {
+	/// Height being voted on.
+	pub height: u64,
+	/// View within the height.
+	pub view: u64,
+	/// Current step.
+	pub step: ConsensusStep,
+	/// This node is the proposer of the current view.
+	#[serde(rename="isProposer")]
+	pub is_proposer: bool,
+	/// Votes in the current step for the proposal of the current view.
+	#[serde(rename="proposalVotes")]
+	pub proposal_votes: u64,
+}
+
+impl From<engines::ConsensusState> for ConsensusState {
+	fn from(state: engines::ConsensusState) -> Self {
+		ConsensusState {
+			height: state.height as u64,
+			view: state.view as u64,
+			step: state.step.into(),
+			is_proposer: state.is_proposer,
+			proposal_votes: state.proposal_votes as u64,
+		}
+	}
+}
+
+/// Time spent in recent consensus views.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ViewLatency {
+	/// Number of views measured.
+	pub views: u64,
+	/// Average time spent in a view, in milliseconds.
+	#[serde(rename="averageMs")]
+	pub average_ms: u64,
+	/// Longest time spent in a view, in milliseconds.
+	#[serde(rename="maxMs")]
+	pub max_ms: u64,
+}
+
+impl From<engines::ViewLatencyStats> for ViewLatency {
+	fn from(stats: engines::ViewLatencyStats) -> Self {
+		let millis = |d: Duration| d.as_secs() * 1000 + (d.subsec_nanos() / 1_000_000) as u64;
+		ViewLatency {
+			views: stats.views as u64,
+			average_ms: millis(stats.average),
+			max_ms: millis(stats.max),
+		}
+	}
+}
+
+/// Uptime of the consensus engine and the number of messages, proposals and commits it handled.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuntimeStats {
+	/// Seconds since the engine was created.
+	#[serde(rename="uptimeSecs")]
+	pub uptime_secs: u64,
+	/// Valid consensus messages handled.
+	#[serde(rename="messagesProcessed")]
+	pub messages_processed: u64,
+	/// Proposals received from peers.
+	#[serde(rename="proposalsSeen")]
+	pub proposals_seen: u64,
+	/// Heights committed.
+	pub commits: u64,
+}
+
+impl From<engines::RuntimeStats> for RuntimeStats {
+	fn from(stats: engines::RuntimeStats) -> Self {
+		RuntimeStats {
+			uptime_secs: stats.uptime_secs,
+			messages_processed: stats.messages_processed as u64,
+			proposals_seen: stats.proposals_seen as u64,
+			commits: stats.commits as u64,
+		}
+	}
+}
+
+/// Verification stage which rejected a block.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum VerificationStage {
+	/// Checks of the header alone.
+	#[serde(rename="basic")]
+	Basic,
+	/// Checks of the block contents.
+	#[serde(rename="unordered")]
+	Unordered,
+	/// Checks against the parent block and the current validators.
+	#[serde(rename="family")]
+	Family,
+}
+
+impl From<engines::VerificationStage> for VerificationStage {
+	fn from(stage: engines::VerificationStage) -> Self {
+		match stage {
+			engines::VerificationStage::Basic => VerificationStage::Basic,
+			engines::VerificationStage::Unordered => VerificationStage::Unordered,
+			engines::VerificationStage::Family => VerificationStage::Family,
+		}
+	}
+}
+
+/// Result of the consensus checks of a block which was not imported.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockCheck {
+	/// Block passed all checks.
+	pub valid: bool,
+	/// Stage which rejected the block.
+	pub stage: Option<VerificationStage>,
+	/// Reason of the rejection.
+	pub error: Option<String>,
+}
+
+impl From<Result<(), (engines::VerificationStage, Error)>> for BlockCheck {
+	fn from(result: Result<(), (engines::VerificationStage, Error)>) -> Self {
+		match result {
+			Ok(()) => BlockCheck { valid: true, stage: None, error: None },
+			Err((stage, e)) => BlockCheck { valid: false, stage: Some(stage.into()), error: Some(e.to_string()) },
+		}
+	}
+}
+
+/// Parameters the consensus engine runs with.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConsensusConfig(Value);
+
+impl From<Json> for ConsensusConfig {
+	fn from(config: Json) -> Self {
+		fn to_value(json: Json) -> Value {
+			match json {
+				Json::I64(n) => Value::Number(n.into()),
+				Json::U64(n) => Value::Number(n.into()),
+				Json::F64(n) => Number::from_f64(n).map_or(Value::Null, Value::Number),
+				Json::String(s) => Value::String(s),
+				Json::Boolean(b) => Value::Bool(b),
+				Json::Array(items) => Value::Array(items.into_iter().map(to_value).collect()),
+				Json::Object(fields) => Value::Object(fields.into_iter().map(|(key, value)| (key, to_value(value))).collect()),
+				Json::Null => Value::Null,
+			}
+		}
+		ConsensusConfig(to_value(config))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use serde_json;
+	use ethcore::engines;
+	use ethcore::error::{Error, BlockError};
+	use util::H256;
+	use rustc_serialize::json::Json;
+	use super::{ConsensusState, ConsensusStep, ConsensusPhase, ViewLatency, RuntimeStats, BlockCheck, ConsensusConfig};
+
+	#[test]
+	fn consensus_state_serialization() {
+		let state = ConsensusState {
+			height: 12,
+			view: 1,
+			step: ConsensusStep::Prevote,
+			is_proposer: false,
+			proposal_votes: 3,
+		};
+		let serialized = serde_json::to_string(&state).unwrap();
+		assert_eq!(serialized, r#"{"height":12,"view":1,"step":"prevote","isProposer":false,"proposalVotes":3}"#);
+	}
+
+	#[test]
+	fn consensus_phase_deserialization() {
+		let phases: Vec<ConsensusPhase> = serde_json::from_str(r#"["propose","newView"]"#).unwrap();
+		assert_eq!(phases, vec![ConsensusPhase::Propose, ConsensusPhase::NewView]);
+		assert!(serde_json::from_str::<ConsensusPhase>(r#""vote""#).is_err());
+	}
+
+	#[test]
+	fn view_latency_in_millis() {
+		let latency: ViewLatency = engines::ViewLatencyStats {
+			views: 4,
+			average: Duration::from_millis(1500),
+			max: Duration::new(3, 250_900_000),
+		}.into();
+		let serialized = serde_json::to_string(&latency).unwrap();
+		assert_eq!(serialized, r#"{"views":4,"averageMs":1500,"maxMs":3250}"#);
+	}
+
+	#[test]
+	fn runtime_stats_serialization() {
+		let stats: RuntimeStats = engines::RuntimeStats {
+			uptime_secs: 60,
+			messages_processed: 12,
+			proposals_seen: 2,
+			commits: 1,
+		}.into();
+		let serialized = serde_json::to_string(&stats).unwrap();
+		assert_eq!(serialized, r#"{"uptimeSecs":60,"messagesProcessed":12,"proposalsSeen":2,"commits":1}"#);
+	}
+
+	#[test]
+	fn block_check_names_stage() {
+		let valid: BlockCheck = Ok(()).into();
+		assert_eq!(serde_json::to_string(&valid).unwrap(), r#"{"valid":true,"stage":null,"error":null}"#);
+
+		let rejected: BlockCheck = Err((engines::VerificationStage::Family, Error::from(BlockError::UnknownParent(H256::default())))).into();
+		let serialized = serde_json::to_string(&rejected).unwrap();
+		assert!(serialized.starts_with(r#"{"valid":false,"stage":"family","error":"Block"#));
+	}
+
+	#[test]
+	fn consensus_config_keeps_structure() {
+		let json = Json::from_str(r#"{"maxTimeout":null,"quorumRatio":"2/3","senderBlacklist":["0x01"],"timeoutPropose":10000,"weightedProposer":false}"#).unwrap();
+		let config: ConsensusConfig = json.into();
+		let serialized = serde_json::to_string(&config).unwrap();
+		assert_eq!(serialized, r#"{"maxTimeout":null,"quorumRatio":"2/3","senderBlacklist":["0x01"],"timeoutPropose":10000,"weightedProposer":false}"#);
+	}
+}
@@ -23,6 +23,7 @@ mod block_number;
 mod bytes;
 mod call_request;
 mod confirmations;
+mod consensus;
 mod consensus_status;
 mod dapps;
 mod derivation;
@@ -55,6 +56,7 @@ pub use self::confirmations::{
 	ConfirmationPayload, ConfirmationRequest, ConfirmationResponse, ConfirmationResponseWithToken,
 	TransactionModification, SignRequest, DecryptRequest, Either
 };
+pub use self::consensus::{ConsensusState, ConsensusStep, ConsensusPhase, ViewLatency, RuntimeStats, VerificationStage, BlockCheck, ConsensusConfig};
 pub use self::consensus_status::*;
 pub use self::dapps::LocalDapp;
 pub use self::derivation::{DeriveHash, DeriveHierarchical, Derive};
@@ -16,7 +16,7 @@
 
 //! TestNet peer definition.
 
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 
 use light_sync::*;
@@ -224,6 +224,8 @@ impl TestNet<Peer> {
 			peers: peers,
 			started: false,
 			disconnect_events: Vec::new(),
+			links: HashMap::new(),
+			delayed: Vec::new(),
 		}
 	}
 }
@@ -217,3 +217,52 @@ fn tendermint() {
 	assert_eq!(ci1.best_block_number, 3);
 	assert_eq!(ci0.best_block_hash, ci1.best_block_hash);
 }
+
+#[test]
+fn tendermint_commits_with_delayed_proposer() {
+	let s0 = KeyPair::from_secret_slice(&"1".sha3()).unwrap();
+	let s1 = KeyPair::from_secret_slice(&"0".sha3()).unwrap();
+	let ap = Arc::new(AccountProvider::transient_provider());
+	ap.insert_account(s0.secret().clone(), "").unwrap();
+	ap.insert_account(s1.secret().clone(), "").unwrap();
+
+	let network_id = Spec::new_test_tendermint().network_id();
+	let mut net = TestNet::with_spec_and_accounts(2, SyncConfig::default(), Spec::new_test_tendermint, Some(ap));
+	let io_handler0: Arc<IoHandler<ClientIoMessage>> = Arc::new(TestIoHandler { client: net.peer(0).chain.clone() });
+	let io_handler1: Arc<IoHandler<ClientIoMessage>> = Arc::new(TestIoHandler { client: net.peer(1).chain.clone() });
+	// Peer 0 is the proposer at the first height.
+	net.peer(0).chain.miner().set_engine_signer(s0.address(), "".to_owned()).unwrap();
+	net.peer(1).chain.miner().set_engine_signer(s1.address(), "".to_owned()).unwrap();
+	net.peer(0).chain.engine().register_client(Arc::downgrade(&net.peer(0).chain));
+	net.peer(1).chain.engine().register_client(Arc::downgrade(&net.peer(1).chain));
+	net.peer(0).chain.set_io_channel(IoChannel::to_handler(Arc::downgrade(&io_handler0)));
+	net.peer(1).chain.set_io_channel(IoChannel::to_handler(Arc::downgrade(&io_handler1)));
+	// Exhange statuses
+	net.sync();
+	// Messages of the proposer arrive one timeout late.
+	net.set_link(0, 1, Link::Delay(1));
+	net.peer(0).chain.miner().import_own_transaction(&*net.peer(0).chain, new_tx(s0.secret(), 0.into(), network_id)).unwrap();
+	net.peer(1).chain.miner().import_own_transaction(&*net.peer(1).chain, new_tx(s0.secret(), 0.into(), network_id)).unwrap();
+	net.sync();
+
+	let mut rounds = 0;
+	while net.peer(1).chain.chain_info().best_block_number == 0 {
+		assert!(rounds < 10, "Network did not commit within 10 timeouts.");
+		// Timeout, synchronous for now
+		net.peer(0).chain.engine().step();
+		net.peer(1).chain.engine().step();
+		net.sync();
+		net.tick();
+		net.sync();
+		rounds += 1;
+	}
+	net.set_link(0, 1, Link::Up);
+	net.tick();
+	net.sync();
+
+	let ci0 = net.peer(0).chain.chain_info();
+	let ci1 = net.peer(1).chain.chain_info();
+	assert_eq!(ci0.best_block_number, 1);
+	assert_eq!(ci1.best_block_number, 1);
+	assert_eq!(ci0.best_block_hash, ci1.best_block_hash);
+}
@@ -230,10 +230,25 @@ impl<C: FlushingBlockChainClient> Peer for EthPeer<C> {
 	}
 }
 
-pub struct TestNet<P> {
+/// Behaviour of a directed link between two peers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Link {
+	/// Messages are delivered immediately.
+	Up,
+	/// Messages are held back for the given number of ticks.
+	Delay(usize),
+	/// Messages are lost.
+	Down,
+}
+
+pub struct TestNet<P: Peer> {
 	pub peers: Vec<Arc<P>>,
 	pub started: bool,
 	pub disconnect_events: Vec<(PeerId, PeerId)>, //disconnected (initiated by, to)
+	/// Links which are not `Link::Up`, keyed by (from, to).
+	pub links: HashMap<(PeerId, PeerId), Link>,
+	/// Held back messages along with the sender and remaining ticks.
+	pub delayed: Vec<(PeerId, P::Message, usize)>,
 }
 
 impl TestNet<EthPeer<TestBlockChainClient>> {
@@ -252,6 +267,8 @@ impl TestNet<EthPeer<TestBlockChainClient>> {
 			peers: Vec::new(),
 			started: false,
 			disconnect_events: Vec::new(),
+			links: HashMap::new(),
+			delayed: Vec::new(),
 		};
 		for _ in 0..n {
 			let chain = TestBlockChainClient::new();
@@ -276,6 +293,8 @@ impl TestNet<EthPeer<EthcoreClient>> {
 			peers: Vec::new(),
 			started: false,
 			disconnect_events: Vec::new(),
+			links: HashMap::new(),
+			delayed: Vec::new(),
 		};
 		for _ in 0..n {
 			net.add_peer(config.clone(), spec_factory(), accounts.clone());
@@ -324,24 +343,22 @@ impl<P> TestNet<P> where P: Peer {
 		self.started = true;
 	}
 
+	/// Change the behaviour of messages sent from `from` to `to`.
+	pub fn set_link(&mut self, from: PeerId, to: PeerId, link: Link) {
+		match link {
+			Link::Up => self.links.remove(&(from, to)),
+			other => self.links.insert((from, to), other),
+		};
+	}
+
 	pub fn sync_step(&mut self) {
 		for peer in 0..self.peers.len() {
 			let packet = self.peers[peer].pending_message();
 			if let Some(packet) = packet {
-				let disconnecting = {
-					let recipient = packet.recipient();
-					trace!("--- {} -> {} ---", peer, recipient);
-					let to_disconnect = self.peers[recipient].receive_message(peer as PeerId, packet);
-					for d in &to_disconnect {
-						// notify this that disconnecting peers are disconnecting
-						self.peers[recipient].on_disconnect(*d as PeerId);
-						self.disconnect_events.push((peer, *d));
-					}
-					to_disconnect
-				};
-				for d in &disconnecting {
-					// notify other peers that this peer is disconnecting
-					self.peers[*d].on_disconnect(peer as PeerId);
+				match self.links.get(&(peer, packet.recipient())).cloned() {
+					Some(Link::Down) => trace!("--- {} -> {} dropped ---", peer, packet.recipient()),
+					Some(Link::Delay(ticks)) => self.delayed.push((peer, packet, ticks)),
+					_ => self.deliver(peer, packet),
 				}
 			}
 
@@ -349,6 +366,35 @@ impl<P> TestNet<P> where P: Peer {
 		}
 	}
 
+	/// Advance time for held back messages, delivering the ones which are due.
+	pub fn tick(&mut self) {
+		let (due, delayed): (Vec<_>, Vec<_>) = self.delayed.drain(..)
+			.map(|(peer, packet, ticks)| (peer, packet, ticks.saturating_sub(1)))
+			.partition(|&(_, _, ticks)| ticks == 0);
+		self.delayed = delayed;
+		for (peer, packet, _) in due {
+			self.deliver(peer, packet);
+		}
+	}
+
+	fn deliver(&mut self, peer: PeerId, packet: P::Message) {
+		let disconnecting = {
+			let recipient = packet.recipient();
+			trace!("--- {} -> {} ---", peer, recipient);
+			let to_disconnect = self.peers[recipient].receive_message(peer as PeerId, packet);
+			for d in &to_disconnect {
+				// notify this that disconnecting peers are disconnecting
+				self.peers[recipient].on_disconnect(*d as PeerId);
+				self.disconnect_events.push((peer, *d));
+			}
+			to_disconnect
+		};
+		for d in &disconnecting {
+			// notify other peers that this peer is disconnecting
+			self.peers[*d].on_disconnect(peer as PeerId);
+		}
+	}
+
 	pub fn sync_step_peer(&mut self, peer_num: usize) {
 		self.peers[peer_num].sync_step();
 	}